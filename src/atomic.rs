@@ -0,0 +1,79 @@
+//! Atomic file writes.
+//!
+//! Data is written to a temporary file in the target directory which is then
+//! renamed to the target path. A crash mid-write leaves the old file untouched.
+//! Symlinks are followed and the permissions of an existing file are kept.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// Replaces the content of the file at `path` with `data`.
+pub fn write(path: impl AsRef<Path>, data: impl AsRef<[u8]>) -> Result<()> {
+    let path = path.as_ref();
+
+    let fun = || -> Result<()> {
+        // Replace the file a symlink points to instead of the symlink itself.
+        let target = match fs::canonicalize(path) {
+            Ok(target) => target,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => path.to_path_buf(),
+            Err(e) => return Err(e.into()),
+        };
+        let dir = match target.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let mut file = tempfile::NamedTempFile::new_in(dir)?;
+        file.write_all(data.as_ref())?;
+        if let Ok(metadata) = fs::metadata(&target) {
+            file.as_file().set_permissions(metadata.permissions())?;
+        }
+        file.as_file().sync_all()?;
+        file.persist(&target)?;
+        Ok(())
+    };
+    fun().with_context(|| format!("Failed to write file {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_file_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("en.json");
+
+        write(&path, "first").unwrap();
+        write(&path, "second").unwrap();
+
+        assert_eq!("second", std::fs::read_to_string(&path).unwrap());
+        assert_eq!(1, std::fs::read_dir(dir.path()).unwrap().count());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keep_permissions_and_symlinks() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("en.json");
+        let link = dir.path().join("link.json");
+        std::fs::write(&target, "first").unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o644)).unwrap();
+        symlink(&target, &link).unwrap();
+
+        write(&link, "second").unwrap();
+
+        assert!(std::fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!("second", std::fs::read_to_string(&target).unwrap());
+        let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(0o644, mode & 0o777);
+    }
+}
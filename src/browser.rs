@@ -0,0 +1,37 @@
+//! Links into the web interface of Traduora.
+
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use crate::config::AppConfig;
+
+/// Page of the web interface that lists the translations of the configured locale.
+///
+/// Traduora has no page for a single term, so the term has to be looked up there.
+pub fn translations_url(config: &AppConfig) -> String {
+    format!(
+        "{}/projects/{}/translations/{}",
+        config.server_url(),
+        config.project_id(),
+        config.locale()
+    )
+}
+
+/// Opens the url in the default browser of the system.
+pub fn open(url: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command
+        .arg(url)
+        .spawn()
+        .with_context(|| format!("Failed to open {} in the browser", url))?;
+    Ok(())
+}
@@ -0,0 +1,569 @@
+//! Command line interface.
+//!
+//! The arguments are parsed once at startup and passed on to the parts that need them,
+//! e.g. to the configuration when it looks for the config file.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use clap::{CommandFactory, ErrorKind, Parser};
+
+use crate::completions::Shell;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Gui,
+    Diff,
+    Apply,
+    Check,
+    Terminal,
+    ListProjects,
+    ListLocales,
+    Pick,
+    Export {
+        /// Defaults to the configured translation file.
+        output: Option<PathBuf>,
+    },
+    Preflight,
+    StoreCredentials,
+    EncryptSecret,
+    ValidateConfig {
+        /// Whether the checks that talk to Traduora are run as well.
+        ping: bool,
+    },
+    Schema(PathBuf),
+    Completions(Shell),
+    /// Hidden command to measure load and merge performance.
+    Bench(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cli {
+    pub command: Command,
+    pub config: Option<PathBuf>,
+    /// Name or id of the configured project to work on.
+    pub project: Option<String>,
+    pub locale: Option<String>,
+    /// Translation file to use instead of the configured one, `-` for stdin.
+    pub translation_file: Option<PathBuf>,
+    /// Host of the Traduora instance to use instead of the configured one.
+    pub host: Option<String>,
+    /// Project id to use instead of the configured one.
+    pub project_id: Option<String>,
+    /// Git revision to use instead of the configured one.
+    pub revision: Option<String>,
+    /// Whether Traduora must not be changed, regardless of the configuration.
+    pub read_only: bool,
+    pub terms: TermFilter,
+    /// File to write a report of the changes to, for diff, apply and check.
+    pub report: Option<PathBuf>,
+    /// Number of `-v` flags minus the number of `-q` flags.
+    pub verbosity: i8,
+}
+
+/// Glob patterns that restrict which terms are compared, e.g. for partial rollouts.
+/// `*` matches any number of characters and `?` a single one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TermFilter {
+    /// If not empty, only terms matching one of the patterns are kept.
+    pub only: Vec<String>,
+    /// Terms matching one of the patterns are dropped.
+    pub exclude: Vec<String>,
+}
+
+impl TermFilter {
+    pub fn matches(&self, term: &str) -> bool {
+        (self.only.is_empty() || self.only.iter().any(|p| glob_match(p, term)))
+            && !self.exclude.iter().any(|p| glob_match(p, term))
+    }
+}
+
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<_>, Vec<_>) = (pattern.chars().collect(), text.chars().collect());
+    // Position after the last `*` and the text position it was matched up to, to backtrack.
+    let mut star = None;
+    let (mut p, mut t) = (0, 0);
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((after_star, matched)) => {
+                    p = after_star;
+                    t = matched + 1;
+                    star = Some((after_star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+impl Default for Cli {
+    fn default() -> Self {
+        Self {
+            command: Command::Gui,
+            config: None,
+            project: None,
+            locale: None,
+            translation_file: None,
+            host: None,
+            project_id: None,
+            revision: None,
+            read_only: false,
+            terms: TermFilter::default(),
+            report: None,
+            verbosity: 0,
+        }
+    }
+}
+
+/// Parses the arguments of the process. Prints the help or the version and exits if they were
+/// asked for, or the usage if the arguments are invalid.
+pub fn init() -> Cli {
+    match Cli::parse(std::env::args_os().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) if e.use_stderr() => {
+            let _ = e.print();
+            crate::exit_code::ExitCode::Failure.exit();
+        }
+        Err(e) => e.exit(),
+    }
+}
+
+/// Keeps the terms of a Traduora project up to date with a local translation file.
+///
+/// Without a command, the differences are shown in a window.
+#[derive(Parser)]
+#[clap(name = "traduora-update", version)]
+struct Args {
+    #[clap(subcommand)]
+    command: Option<Subcommand>,
+    #[clap(flatten)]
+    config_file: ConfigFile,
+    #[clap(flatten)]
+    options: Options,
+}
+
+/// Options that are valid for all commands.
+#[derive(clap::Args)]
+struct Options {
+    /// Config file to use. Can also be given as CONFIG_FILE. Defaults to
+    /// $TRADUORA_UPDATE_CONFIG or the first traduora-update.json, .toml, .yaml or .yml found in
+    /// the working directory or its parents
+    #[clap(short, long, value_name = "FILE", value_parser, global = true)]
+    config: Option<PathBuf>,
+    /// Project to work on if several are configured, by name or id. Defaults to the one
+    /// configured at the top level
+    #[clap(short, long, value_name = "NAME", value_parser, global = true)]
+    project: Option<String>,
+    /// Only update this locale. If it is not configured, it is updated from the translation file
+    /// of the first configured locale
+    #[clap(short, long, value_name = "CODE", value_parser, global = true)]
+    locale: Option<String>,
+    /// Translation file to use instead of the configured one. Use - to read it from stdin, or
+    /// with export to write it to stdout
+    #[clap(short, long, value_name = "FILE", value_parser, global = true)]
+    translation_file: Option<PathBuf>,
+    /// Traduora instance to use instead of the configured one, e.g. a test instance
+    #[clap(long, value_name = "HOST", value_parser, global = true)]
+    host: Option<String>,
+    /// Project id to use instead of the configured one
+    #[clap(long, value_name = "ID", value_parser, global = true)]
+    project_id: Option<String>,
+    /// Git revision for the sanity checks instead of the configured one
+    #[clap(long, value_name = "REV", value_parser, global = true)]
+    revision: Option<String>,
+    /// Only show the differences, updating terms is disabled
+    #[clap(long, action, global = true)]
+    read_only: bool,
+    /// Log more details, repeat for even more (-vv, -vvv)
+    #[clap(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+    /// Do not log anything
+    #[clap(short, long, action = clap::ArgAction::Count, global = true)]
+    quiet: u8,
+}
+
+#[derive(clap::Args)]
+struct ConfigFile {
+    /// Config file to use, the same as --config
+    #[clap(value_name = "CONFIG_FILE", value_parser)]
+    file: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+struct Terms {
+    /// Only compare terms matching the pattern, e.g. 'checkout.*'. Can be given multiple times
+    #[clap(long, value_name = "GLOB", value_parser)]
+    only: Vec<String>,
+    /// Do not compare terms matching the pattern, e.g. 'legacy.*'. Can be given multiple times
+    #[clap(long, value_name = "GLOB", value_parser)]
+    exclude: Vec<String>,
+}
+
+/// Options of the commands that compute the changes without a window.
+#[derive(clap::Args)]
+struct Changes {
+    #[clap(flatten)]
+    terms: Terms,
+    /// Write a report of the changes as Markdown, or as HTML if FILE ends with .html
+    #[clap(long, value_name = "FILE", value_parser)]
+    report: Option<PathBuf>,
+    #[clap(flatten)]
+    config_file: ConfigFile,
+}
+
+#[derive(clap::Subcommand)]
+enum Subcommand {
+    /// Show the differences in a window and apply the selected ones (default)
+    Gui(ConfigFile),
+    /// Print the operations an update would perform without applying them
+    Diff(Changes),
+    /// Apply all differences to Traduora without asking
+    Apply(Changes),
+    /// Fail if the local translations and Traduora differ
+    Check(Changes),
+    /// Show the differences interactively in the terminal
+    Terminal {
+        #[clap(flatten)]
+        terms: Terms,
+        #[clap(flatten)]
+        config_file: ConfigFile,
+    },
+    /// Print the ids and names of the projects the account has access to
+    ListProjects(ConfigFile),
+    /// Print the locales of the configured project
+    ListLocales(ConfigFile),
+    /// Choose a project and locale on Traduora and print how to configure them
+    Pick(ConfigFile),
+    /// Write the terms and translations on Traduora to the translation file
+    Export {
+        /// File to write to instead of the configured translation file. Use - to write to
+        /// stdout
+        #[clap(short, long, value_name = "FILE", value_parser)]
+        output: Option<PathBuf>,
+        #[clap(flatten)]
+        config_file: ConfigFile,
+    },
+    /// Run the pre-flight checks and print the results
+    Preflight(ConfigFile),
+    /// Ask for the password or client secret and store it in the keyring
+    StoreCredentials(ConfigFile),
+    /// Ask for a passphrase and a password or client secret and print the encrypted secret for
+    /// the config file
+    EncryptSecret(ConfigFile),
+    /// Work with the config file
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Write the JSON schema of the config file to FILE
+    Schema {
+        #[clap(value_name = "FILE", value_parser)]
+        path: PathBuf,
+        #[clap(flatten)]
+        config_file: ConfigFile,
+    },
+    /// Print a completion script for bash, zsh, fish or powershell
+    Completions {
+        #[clap(value_name = "SHELL", value_parser)]
+        shell: Shell,
+    },
+    /// Measure the performance of loading and merging the translations
+    #[clap(hide = true)]
+    Bench {
+        #[clap(default_value_t = 20, value_parser)]
+        iterations: usize,
+        #[clap(flatten)]
+        config_file: ConfigFile,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum ConfigCommand {
+    /// Report all problems of the config file, the translation file and the git revision
+    Validate {
+        /// Check the login to Traduora as well
+        #[clap(long, action)]
+        ping: bool,
+        #[clap(flatten)]
+        config_file: ConfigFile,
+    },
+}
+
+/// Flags of earlier versions and the commands that replaced them.
+const DEPRECATED_FLAGS: &[(&str, &str)] = &[
+    ("--dry-run", "diff"),
+    ("--check", "check"),
+    ("--apply", "apply"),
+    ("--terminal", "terminal"),
+    ("--preflight", "preflight"),
+    ("--generate-config-schema", "schema"),
+];
+
+/// Turns a flag of an earlier version into the command that replaced it and warns that it is
+/// deprecated, e.g. `--dry-run project.json` into `diff project.json`.
+fn replace_deprecated_flag(mut args: Vec<OsString>) -> Vec<OsString> {
+    let deprecated = args.iter().enumerate().find_map(|(i, arg)| {
+        DEPRECATED_FLAGS
+            .iter()
+            .find(|(flag, _)| arg == flag)
+            .map(|&(flag, command)| (i, flag, command))
+    });
+    if let Some((i, flag, command)) = deprecated {
+        eprintln!(
+            "warning: {} is deprecated, use the command `traduora-update {}` instead.",
+            flag, command
+        );
+        args.remove(i);
+        let mut replacement = vec![OsString::from(command)];
+        // The flag was followed by the file to write the schema to.
+        if command == "schema" && i < args.len() {
+            replacement.push(args.remove(i));
+        }
+        args.splice(0..0, replacement);
+    }
+    args
+}
+
+impl Cli {
+    pub fn parse(args: impl IntoIterator<Item = OsString>) -> Result<Self, clap::Error> {
+        let args = replace_deprecated_flag(args.into_iter().collect());
+        let args =
+            Args::try_parse_from(std::iter::once(OsString::from("traduora-update")).chain(args))?;
+        let options = args.options;
+        let mut cli = Cli {
+            command: Command::Gui,
+            config: options.config,
+            project: options.project,
+            locale: options.locale,
+            translation_file: options.translation_file,
+            host: options.host,
+            project_id: options.project_id,
+            revision: options.revision,
+            read_only: options.read_only,
+            terms: TermFilter::default(),
+            report: None,
+            verbosity: (i16::from(options.verbose) - i16::from(options.quiet))
+                .clamp(i8::MIN.into(), i8::MAX.into()) as i8,
+        };
+
+        let mut changes = |changes: Changes| {
+            cli.terms = changes.terms.into();
+            cli.report = changes.report;
+            changes.config_file
+        };
+        let (command, config_file) = match args.command {
+            None => (Command::Gui, ConfigFile { file: None }),
+            Some(Subcommand::Gui(config_file)) => (Command::Gui, config_file),
+            Some(Subcommand::Diff(c)) => (Command::Diff, changes(c)),
+            Some(Subcommand::Apply(c)) => (Command::Apply, changes(c)),
+            Some(Subcommand::Check(c)) => (Command::Check, changes(c)),
+            Some(Subcommand::Terminal { terms, config_file }) => {
+                cli.terms = terms.into();
+                (Command::Terminal, config_file)
+            }
+            Some(Subcommand::ListProjects(config_file)) => (Command::ListProjects, config_file),
+            Some(Subcommand::ListLocales(config_file)) => (Command::ListLocales, config_file),
+            Some(Subcommand::Pick(config_file)) => (Command::Pick, config_file),
+            Some(Subcommand::Export {
+                output,
+                config_file,
+            }) => (Command::Export { output }, config_file),
+            Some(Subcommand::Preflight(config_file)) => (Command::Preflight, config_file),
+            Some(Subcommand::StoreCredentials(config_file)) => {
+                (Command::StoreCredentials, config_file)
+            }
+            Some(Subcommand::EncryptSecret(config_file)) => (Command::EncryptSecret, config_file),
+            Some(Subcommand::Config {
+                command: ConfigCommand::Validate { ping, config_file },
+            }) => (Command::ValidateConfig { ping }, config_file),
+            Some(Subcommand::Schema { path, config_file }) => (Command::Schema(path), config_file),
+            Some(Subcommand::Completions { shell }) => {
+                (Command::Completions(shell), ConfigFile { file: None })
+            }
+            Some(Subcommand::Bench {
+                iterations,
+                config_file,
+            }) => (Command::Bench(iterations), config_file),
+        };
+        cli.command = command;
+
+        // Without a command, the only argument is the config file as in earlier versions.
+        for file in args.config_file.file.into_iter().chain(config_file.file) {
+            if cli.config.is_some() {
+                return Err(Args::command().error(
+                    ErrorKind::ArgumentConflict,
+                    "The config file was given twice.",
+                ));
+            }
+            cli.config = Some(file);
+        }
+        Ok(cli)
+    }
+}
+
+impl From<Terms> for TermFilter {
+    fn from(terms: Terms) -> Self {
+        Self {
+            only: terms.only,
+            exclude: terms.exclude,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::parse(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn parse_commands_and_options() {
+        Args::command().debug_assert();
+        assert_eq!(Cli::default(), parse(&[]).unwrap());
+        assert_eq!(
+            Cli {
+                command: Command::Check,
+                config: Some("project.json".into()),
+                project: Some("admin".into()),
+                locale: Some("de".into()),
+                translation_file: Some("-".into()),
+                host: Some("staging:8080".into()),
+                project_id: None,
+                revision: Some("v1.0".into()),
+                read_only: true,
+                terms: TermFilter::default(),
+                report: None,
+                verbosity: 0,
+            },
+            parse(&[
+                "check",
+                "--locale",
+                "de",
+                "-c",
+                "project.json",
+                "-t",
+                "-",
+                "-p",
+                "admin",
+                "--host",
+                "staging:8080",
+                "--revision",
+                "v1.0",
+                "--read-only"
+            ])
+            .unwrap()
+        );
+        assert_eq!(
+            Some(PathBuf::from("project.json")),
+            parse(&["project.json"]).unwrap().config
+        );
+        assert_eq!(
+            Command::Completions(Shell::Zsh),
+            parse(&["completions", "zsh"]).unwrap().command
+        );
+        assert_eq!(
+            Command::Schema("schema.json".into()),
+            parse(&["schema", "schema.json", "project.json"])
+                .unwrap()
+                .command
+        );
+        assert_eq!(
+            ErrorKind::DisplayHelp,
+            parse(&["apply", "--help"]).unwrap_err().kind()
+        );
+        assert_eq!(
+            ErrorKind::DisplayVersion,
+            parse(&["-V"]).unwrap_err().kind()
+        );
+        assert_eq!(
+            Command::EncryptSecret,
+            parse(&["encrypt-secret"]).unwrap().command
+        );
+        assert_eq!(3, parse(&["-vv", "--verbose"]).unwrap().verbosity);
+        assert_eq!(-1, parse(&["-q"]).unwrap().verbosity);
+        assert_eq!(
+            Command::ValidateConfig { ping: true },
+            parse(&["config", "validate", "--ping"]).unwrap().command
+        );
+        assert_eq!(
+            Command::Export {
+                output: Some("-".into())
+            },
+            parse(&["export", "-o", "-"]).unwrap().command
+        );
+    }
+
+    #[test]
+    fn filter_terms() {
+        let filter = parse(&[
+            "apply",
+            "--only",
+            "checkout.*",
+            "--only",
+            "cart.?",
+            "--exclude",
+            "*.legacy.*",
+        ])
+        .unwrap()
+        .terms;
+        assert!(filter.matches("checkout.title"));
+        assert!(filter.matches("cart.1"));
+        assert!(!filter.matches("cart.10"));
+        assert!(!filter.matches("checkout.legacy.title"));
+        assert!(!filter.matches("home.title"));
+        assert!(TermFilter::default().matches("home.title"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b", "aXbY"));
+    }
+
+    #[test]
+    fn reject_invalid_arguments() {
+        assert!(parse(&["-vx"]).is_err());
+        assert!(parse(&["--config"]).is_err());
+        assert!(parse(&["schema"]).is_err());
+        assert!(parse(&["config"]).is_err());
+        assert!(parse(&["completions"]).is_err());
+        assert!(parse(&["completions", "cmd"]).is_err());
+        assert!(parse(&["diff", "--ping"]).is_err());
+        assert!(parse(&["diff", "-o", "en.json"]).is_err());
+        assert!(parse(&["--only", "checkout.*"]).is_err());
+        assert!(parse(&["terminal", "--report", "report.md"]).is_err());
+        assert!(parse(&["diff", "a.json", "b.json"]).is_err());
+        assert!(parse(&["-c", "a.json", "b.json"]).is_err());
+        assert!(parse(&["a.json", "diff", "b.json"]).is_err());
+    }
+
+    #[test]
+    fn replace_deprecated_flags() {
+        assert_eq!(Command::Diff, parse(&["--dry-run"]).unwrap().command);
+        let check = parse(&["project.json", "--check", "-v"]).unwrap();
+        assert_eq!(Command::Check, check.command);
+        assert_eq!(Some(PathBuf::from("project.json")), check.config);
+        assert_eq!(1, check.verbosity);
+        assert_eq!(
+            Command::Schema("schema.json".into()),
+            parse(&["--generate-config-schema", "schema.json"])
+                .unwrap()
+                .command
+        );
+    }
+
+    #[test]
+    fn verbosity_needs_dash() {
+        let cli = parse(&["vvv"]).unwrap();
+        assert_eq!(0, cli.verbosity);
+        assert_eq!(Some(PathBuf::from("vvv")), cli.config);
+    }
+}
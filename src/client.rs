@@ -0,0 +1,135 @@
+//! Authenticated connection to Traduora.
+//!
+//! Requests are sent with the connection of [`transport`](crate::transport) to the upstream
+//! Traduora paths, unless the configuration overrides the path of an endpoint.
+
+use anyhow::{Context, Result};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use traduora::api::{locales::LocaleCode, translations::EditTranslation, AccessToken, TermId};
+
+use crate::compat::{Flavor, Project, ProjectLocale, Term, Translation};
+use crate::config::AppConfig;
+use crate::transport::Api;
+
+pub struct Client {
+    api: Api,
+    flavor: Flavor,
+    config: AppConfig,
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Client").finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Client that works on the configured project and locale, logged in with the access token.
+    /// Payloads are taken out of the responses as the server of the `flavor` sends them.
+    pub fn new(api: Api, token: AccessToken, flavor: Flavor, config: AppConfig) -> Self {
+        Self {
+            api: api.authenticate(token),
+            flavor,
+            config,
+        }
+    }
+
+    /// Get the configuration of the project and locale the client works on.
+    pub fn config(&self) -> &AppConfig {
+        &self.config
+    }
+
+    fn project_path(&self) -> String {
+        format!("api/v1/projects/{}", self.config.project_id())
+    }
+
+    fn terms_path(&self) -> String {
+        let config = &self.config;
+        config
+            .endpoints()
+            .terms_path(config)
+            .unwrap_or_else(|| format!("{}/terms", self.project_path()))
+    }
+
+    fn translations_path(&self) -> String {
+        self.translations_path_of(&self.config)
+    }
+
+    fn translations_path_of(&self, config: &AppConfig) -> String {
+        config
+            .endpoints()
+            .translations_path(config)
+            .unwrap_or_else(|| {
+                format!(
+                    "{}/translations/{}",
+                    self.project_path(),
+                    config.locale().value()
+                )
+            })
+    }
+
+    /// Sends a request and deserializes the payload into the lenient model `T`.
+    fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<T> {
+        let response = self.api.request(method.clone(), path, body)?;
+        self.flavor
+            .payload(response)
+            .with_context(|| format!("Unexpected response of {} {}", method, path))
+    }
+
+    pub fn project(&self) -> Result<Project> {
+        self.request(Method::GET, &self.project_path(), None)
+    }
+
+    /// All projects the account has access to.
+    pub fn projects(&self) -> Result<Vec<Project>> {
+        self.request(Method::GET, "api/v1/projects", None)
+    }
+
+    pub fn locales(&self) -> Result<Vec<ProjectLocale>> {
+        let path = format!("{}/translations", self.project_path());
+        self.request(Method::GET, &path, None)
+    }
+
+    pub fn terms(&self) -> Result<Vec<Term>> {
+        self.request(Method::GET, &self.terms_path(), None)
+    }
+
+    pub fn translations(&self) -> Result<Vec<Translation>> {
+        self.request(Method::GET, &self.translations_path(), None)
+    }
+
+    /// Translations of another locale of the project.
+    pub fn translations_in(&self, locale: &LocaleCode) -> Result<Vec<Translation>> {
+        let config = &self.config;
+        let other = config.with_remote_project(config.project_id().value(), locale.value());
+        self.request(Method::GET, &self.translations_path_of(&other), None)
+    }
+
+    pub fn create_term(&self, term: String) -> Result<Term> {
+        let body = serde_json::json!({ "value": term });
+        self.request(Method::POST, &self.terms_path(), Some(body))
+    }
+
+    pub fn delete_term(&self, term_id: TermId) -> Result<()> {
+        let path = format!("{}/{}", self.terms_path().trim_end_matches('/'), term_id);
+        self.request(Method::DELETE, &path, None)
+    }
+
+    pub fn edit_translation(&self, term_id: TermId, value: String) -> Result<Translation> {
+        let config = &self.config;
+        let endpoint = EditTranslation::new(
+            config.project_id().clone(),
+            config.locale().clone(),
+            term_id,
+            value,
+        );
+        let body = serde_json::to_value(&endpoint)?;
+        self.request(Method::PATCH, &self.translations_path(), Some(body))
+    }
+}
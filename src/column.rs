@@ -0,0 +1,118 @@
+//! Columns with a user adjustable width.
+//!
+//! The width is read from the environment, so every row of a list lines up
+//! with the header whose [`Divider`] changes the width.
+
+use druid::widget::prelude::*;
+use druid::{Cursor, Data, Key, Point, WidgetExt, WidgetPod};
+
+/// Narrowest width a column can be dragged to.
+const MIN_WIDTH: f64 = 50.;
+
+/// Wraps a widget and gives it the width stored under `key`.
+pub struct Column<T> {
+    key: Key<f64>,
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+}
+
+impl<T: Data> Column<T> {
+    pub fn new(key: Key<f64>, child: impl Widget<T> + 'static) -> Self {
+        Self {
+            key,
+            child: WidgetPod::new(child.boxed()),
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Column<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        self.child.event(ctx, event, data, env);
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        self.child.lifecycle(ctx, event, data, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        if ctx.env_key_changed(&self.key) {
+            ctx.request_layout();
+        }
+        self.child.update(ctx, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let width = env.get(&self.key).max(MIN_WIDTH);
+        let bc = BoxConstraints::new(
+            Size::new(width, bc.min().height),
+            Size::new(width, bc.max().height),
+        );
+        let size = self.child.layout(ctx, &bc, data, env);
+        self.child.set_origin(ctx, data, env, Point::ORIGIN);
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.child.paint(ctx, data, env);
+    }
+}
+
+/// Handle that changes a column width when it is dragged horizontally.
+#[derive(Default)]
+pub struct Divider {
+    /// Mouse position and column width when the drag started.
+    drag_start: Option<(f64, f64)>,
+}
+
+impl Divider {
+    pub const WIDTH: f64 = 9.;
+}
+
+impl Widget<f64> for Divider {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut f64, _env: &Env) {
+        match event {
+            Event::MouseDown(mouse) => {
+                self.drag_start = Some((mouse.window_pos.x, *data));
+                ctx.set_active(true);
+            }
+            Event::MouseMove(mouse) => {
+                if ctx.is_hot() || ctx.is_active() {
+                    ctx.set_cursor(&Cursor::ResizeLeftRight);
+                }
+                if let (true, Some((x, width))) = (ctx.is_active(), self.drag_start) {
+                    *data = (width + mouse.window_pos.x - x).max(MIN_WIDTH);
+                }
+            }
+            Event::MouseUp(_) => {
+                self.drag_start = None;
+                ctx.set_active(false);
+            }
+            _ => {}
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _: &f64, _: &Env) {
+        if let LifeCycle::HotChanged(_) = event {
+            ctx.request_paint();
+        }
+    }
+
+    fn update(&mut self, _: &mut UpdateCtx, _: &f64, _: &f64, _: &Env) {}
+
+    fn layout(&mut self, _: &mut LayoutCtx, bc: &BoxConstraints, _: &f64, env: &Env) -> Size {
+        bc.constrain(Size::new(
+            Self::WIDTH,
+            env.get(druid::theme::BASIC_WIDGET_HEIGHT),
+        ))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, _: &f64, env: &Env) {
+        let color = if ctx.is_hot() || ctx.is_active() {
+            env.get(druid::theme::FOREGROUND_LIGHT)
+        } else {
+            env.get(druid::theme::BORDER_DARK)
+        };
+        let size = ctx.size();
+        let line = druid::kurbo::Line::new((size.width / 2., 0.), (size.width / 2., size.height));
+        ctx.stroke(line, &color, 1.);
+    }
+}
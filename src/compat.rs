@@ -0,0 +1,254 @@
+//! Compatibility layer for Traduora versions and forks whose API differs
+//! slightly from what the `traduora` crate expects.
+//!
+//! Instead of the strict default models, responses are deserialized into
+//! lenient models that only contain the fields this tool needs. Known
+//! alternative field names are accepted as aliases and the payload may
+//! or may not be wrapped in a `data` object.
+
+use anyhow::{Context, Result};
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize};
+use traduora::api::{locales::LocaleCode, TermId};
+
+use crate::transport::Api;
+
+/// Marker that is attached to errors caused by the server rejecting
+/// the authentication, e.g. because the password expired or the token was revoked.
+#[derive(Debug)]
+pub struct Unauthorized;
+
+impl std::fmt::Display for Unauthorized {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Traduora rejected the authentication.")
+    }
+}
+
+/// Returns `true` if the error was caused by the server rejecting the authentication.
+pub fn is_unauthorized(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<Unauthorized>().is_some()
+}
+
+/// Marks errors of requests that conflict with the state of the server, e.g. creating a term
+/// that exists already.
+#[derive(Debug)]
+pub struct Conflict;
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Traduora reported a conflict.")
+    }
+}
+
+pub fn is_conflict(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<Conflict>().is_some()
+}
+
+fn is_unauthorized_response(status: StatusCode, text: &str) -> bool {
+    fn has_code(obj: &serde_json::Value) -> bool {
+        obj.get("code").and_then(serde_json::Value::as_str) == Some("Unauthorized")
+            || obj.get("statusCode").and_then(serde_json::Value::as_u64) == Some(401)
+    }
+
+    status == StatusCode::UNAUTHORIZED
+        || text.trim() == "Unauthorized"
+        || serde_json::from_str::<serde_json::Value>(text)
+            .map(|obj| has_code(&obj) || obj.get("error").map_or(false, has_code))
+            .unwrap_or(false)
+}
+
+/// Error for a request that Traduora answered with a failure status. It is marked with
+/// [`Unauthorized`] if the server rejected the authentication and with [`Conflict`] for `409`.
+pub fn response_error(status: StatusCode, text: &str, request: &str) -> anyhow::Error {
+    let err = anyhow::anyhow!("Traduora returned {} for {}: {}", status, request, text);
+    if is_unauthorized_response(status, text) {
+        err.context(Unauthorized)
+    } else if status == StatusCode::CONFLICT {
+        err.context(Conflict)
+    } else {
+        err
+    }
+}
+
+/// Known shapes of the Traduora API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flavor {
+    /// Upstream Traduora wraps every payload in a `data` object.
+    Wrapped,
+    /// Some forks return the payload without the `data` wrapper.
+    Bare,
+}
+
+impl std::fmt::Display for Flavor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Flavor::Wrapped => write!(f, "Traduora (wrapped responses)"),
+            Flavor::Bare => write!(f, "Traduora fork (bare responses)"),
+        }
+    }
+}
+
+impl Flavor {
+    /// Takes the payload out of a response of a server of this flavor. Empty responses are never
+    /// wrapped.
+    pub fn payload<T: DeserializeOwned>(self, response: serde_json::Value) -> Result<T> {
+        let payload = match (self, response) {
+            (Flavor::Wrapped, serde_json::Value::Object(mut obj)) => obj
+                .remove("data")
+                .context("Response of Traduora is not wrapped in a `data` object.")?,
+            (_, response) => response,
+        };
+        serde_json::from_value(payload).context("Failed to parse payload of the response.")
+    }
+}
+
+/// Payload that may or may not be wrapped in a `data` object, for responses that are received
+/// before the [`Flavor`] is known.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum Envelope<T> {
+    Wrapped { data: T },
+    Bare(T),
+}
+
+impl<T> Envelope<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            Envelope::Wrapped { data } | Envelope::Bare(data) => data,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Term {
+    pub id: TermId,
+    #[serde(alias = "term", alias = "key")]
+    pub value: String,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    #[serde(alias = "dates", default)]
+    pub date: Option<AccessDates>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Label {
+    #[serde(alias = "label", alias = "name")]
+    pub value: String,
+}
+
+/// Timestamps are kept as sent by the server because they are only displayed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccessDates {
+    #[serde(alias = "createdAt", default)]
+    pub created: Option<String>,
+    #[serde(alias = "modifiedAt", alias = "updatedAt", default)]
+    pub modified: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Translation {
+    #[serde(alias = "term_id")]
+    pub term_id: TermId,
+    #[serde(alias = "translation", default)]
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Project {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Locale {
+    pub code: LocaleCode,
+    #[serde(default)]
+    pub language: String,
+    #[serde(default)]
+    pub region: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectLocale {
+    pub locale: Locale,
+}
+
+/// Probes the server to find out which API flavor it speaks, so the [`Client`] knows whether
+/// payloads are wrapped.
+///
+/// [`Client`]: crate::client::Client
+pub fn detect(client: &Api) -> Result<Flavor> {
+    let response: serde_json::Value = client
+        .request(reqwest::Method::GET, "api/v1/auth/providers", None)
+        .context("Failed to query Traduora instance.")?;
+
+    let flavor = match response {
+        serde_json::Value::Object(obj) if obj.contains_key("data") => Flavor::Wrapped,
+        serde_json::Value::Array(_) => Flavor::Bare,
+        other => anyhow::bail!("Unrecognized response from Traduora instance: {}", other),
+    };
+    log::info!("Detected API flavor: {}", flavor);
+    Ok(flavor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_wrapped_and_bare_payloads() {
+        let wrapped = r#"{ "data": [{ "termId": "1", "value": "Hello", "labels": [] }] }"#;
+        let bare = r#"[{ "term_id": "1", "translation": "Hello" }]"#;
+
+        let wrapped: Envelope<Vec<Translation>> = serde_json::from_str(wrapped).unwrap();
+        let bare: Envelope<Vec<Translation>> = serde_json::from_str(bare).unwrap();
+        let (wrapped, bare) = (wrapped.into_inner(), bare.into_inner());
+
+        assert_eq!(wrapped[0].term_id, bare[0].term_id);
+        assert_eq!(wrapped[0].value, bare[0].value);
+    }
+
+    #[test]
+    fn unwrap_payload_by_flavor() {
+        let wrapped = serde_json::json!({ "data": { "data": "nested" } });
+        let payload: serde_json::Value = Flavor::Wrapped.payload(wrapped.clone()).unwrap();
+        assert_eq!(serde_json::json!({ "data": "nested" }), payload);
+        let payload: serde_json::Value = Flavor::Bare.payload(wrapped).unwrap();
+        assert_eq!(serde_json::json!({ "data": { "data": "nested" } }), payload);
+
+        let bare = serde_json::json!({ "id": "1", "value": "title" });
+        assert!(Flavor::Wrapped.payload::<Term>(bare.clone()).is_err());
+        assert_eq!("title", Flavor::Bare.payload::<Term>(bare).unwrap().value);
+
+        Flavor::Wrapped
+            .payload::<()>(serde_json::Value::Null)
+            .unwrap();
+    }
+
+    #[test]
+    fn tolerate_missing_optional_fields() {
+        let locale = r#"{ "data": { "locale": { "code": "de_DE" } } }"#;
+        let locale: Envelope<ProjectLocale> = serde_json::from_str(locale).unwrap();
+        let locale = locale.into_inner().locale;
+        assert_eq!(locale.code.value(), "de_DE");
+        assert!(locale.language.is_empty());
+    }
+
+    #[test]
+    fn detect_unauthorized_through_context() {
+        let body = r#"{ "error": { "code": "Unauthorized", "message": "Token expired" } }"#;
+        let err = response_error(StatusCode::BAD_REQUEST, body, "DELETE terms");
+        let err = Err::<(), _>(err)
+            .context("Failed to delete term.")
+            .unwrap_err();
+        assert!(is_unauthorized(&err));
+
+        let err = response_error(StatusCode::CONFLICT, "Term already exists", "POST terms");
+        let err = Err::<(), _>(err)
+            .context("Failed to create term.")
+            .unwrap_err();
+        assert!(!is_unauthorized(&err));
+    }
+}
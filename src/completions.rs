@@ -0,0 +1,314 @@
+//! Tab completion scripts for the commands and options of the command line.
+
+use std::str::FromStr;
+
+/// Commands with a short description for the shells that show one. The hidden bench command is left out.
+pub const COMMANDS: &[(&str, &str)] = &[
+    ("gui", "Show the differences in a window"),
+    ("diff", "Print the operations an update would perform"),
+    ("apply", "Apply all differences to Traduora"),
+    (
+        "check",
+        "Fail if the local translations and Traduora differ",
+    ),
+    (
+        "terminal",
+        "Show the differences interactively in the terminal",
+    ),
+    ("list-projects", "Print the projects of the account"),
+    ("list-locales", "Print the locales of the project"),
+    ("pick", "Choose a project and locale on Traduora"),
+    ("export", "Write the translations on Traduora to a file"),
+    ("preflight", "Run the pre-flight checks"),
+    (
+        "store-credentials",
+        "Store the password or client secret in the keyring",
+    ),
+    ("encrypt-secret", "Encrypt a secret for the config file"),
+    ("config", "Validate the config file"),
+    ("schema", "Write the JSON schema of the config file"),
+    ("completions", "Print a completion script for a shell"),
+    ("help", "Print the help"),
+];
+
+/// What follows an option on the command line.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    None,
+    Text,
+    File,
+}
+
+pub struct Flag {
+    pub short: Option<char>,
+    pub long: &'static str,
+    pub value: Value,
+    pub description: &'static str,
+}
+
+const fn flag(
+    short: Option<char>,
+    long: &'static str,
+    value: Value,
+    description: &'static str,
+) -> Flag {
+    Flag {
+        short,
+        long,
+        value,
+        description,
+    }
+}
+
+pub const FLAGS: &[Flag] = &[
+    flag(Some('c'), "config", Value::File, "Config file to use"),
+    flag(Some('p'), "project", Value::Text, "Project to work on"),
+    flag(Some('l'), "locale", Value::Text, "Locale to update"),
+    flag(
+        Some('t'),
+        "translation-file",
+        Value::File,
+        "Translation file to use, - for stdin",
+    ),
+    flag(None, "host", Value::Text, "Traduora instance to use"),
+    flag(None, "project-id", Value::Text, "Project id to use"),
+    flag(
+        None,
+        "revision",
+        Value::Text,
+        "Git revision for the sanity checks",
+    ),
+    flag(None, "read-only", Value::None, "Only show the differences"),
+    flag(
+        None,
+        "only",
+        Value::Text,
+        "Only compare terms matching the pattern",
+    ),
+    flag(
+        None,
+        "exclude",
+        Value::Text,
+        "Skip terms matching the pattern",
+    ),
+    flag(None, "report", Value::File, "Write a report of the changes"),
+    flag(
+        Some('o'),
+        "output",
+        Value::File,
+        "File that export writes to",
+    ),
+    flag(
+        None,
+        "ping",
+        Value::None,
+        "Also check the login to Traduora",
+    ),
+    flag(Some('v'), "verbose", Value::None, "Log more details"),
+    flag(Some('q'), "quiet", Value::None, "Do not log anything"),
+    flag(Some('h'), "help", Value::None, "Print the help"),
+    flag(Some('V'), "version", Value::None, "Print the version"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+impl FromStr for Shell {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            "powershell" | "pwsh" => Ok(Shell::Powershell),
+            _ => anyhow::bail!(
+                "Unknown shell {:?}, expected bash, zsh, fish or powershell.",
+                s
+            ),
+        }
+    }
+}
+
+const SHELLS: &str = "bash zsh fish powershell";
+
+fn names(f: &Flag) -> Vec<String> {
+    f.short
+        .map(|s| format!("-{}", s))
+        .into_iter()
+        .chain(std::iter::once(format!("--{}", f.long)))
+        .collect()
+}
+
+fn bash() -> String {
+    let commands: Vec<_> = COMMANDS.iter().map(|(name, _)| *name).collect();
+    let flags: Vec<_> = FLAGS.iter().flat_map(names).collect();
+    let files: Vec<_> = FLAGS
+        .iter()
+        .filter(|f| f.value == Value::File)
+        .flat_map(names)
+        .collect();
+    let texts: Vec<_> = FLAGS
+        .iter()
+        .filter(|f| f.value == Value::Text)
+        .flat_map(names)
+        .collect();
+    format!(
+        r#"_traduora_update() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    case "$prev" in
+        {files})
+            COMPREPLY=($(compgen -f -- "$cur"))
+            return;;
+        {texts})
+            COMPREPLY=()
+            return;;
+        completions)
+            COMPREPLY=($(compgen -W "{shells}" -- "$cur"))
+            return;;
+        config)
+            COMPREPLY=($(compgen -W "validate" -- "$cur"))
+            return;;
+    esac
+    if [[ "$cur" == -* ]]; then
+        COMPREPLY=($(compgen -W "{flags}" -- "$cur"))
+    else
+        COMPREPLY=($(compgen -W "{commands}" -- "$cur") $(compgen -f -- "$cur"))
+    fi
+}}
+complete -F _traduora_update traduora-update
+"#,
+        files = files.join("|"),
+        texts = texts.join("|"),
+        shells = SHELLS,
+        flags = flags.join(" "),
+        commands = commands.join(" "),
+    )
+}
+
+fn zsh() -> String {
+    let mut script =
+        String::from("#compdef traduora-update\n\n_traduora_update() {\n    _arguments \\\n");
+    for f in FLAGS {
+        let names = names(f);
+        let spec = match names.as_slice() {
+            [name] => name.clone(),
+            names => format!("{{{}}}", names.join(",")),
+        };
+        let repeat = if matches!(f.long, "verbose" | "quiet" | "only" | "exclude") {
+            "'*'"
+        } else {
+            ""
+        };
+        let value = match f.value {
+            Value::None => "",
+            Value::Text => ":value: ",
+            Value::File => ":file:_files",
+        };
+        script.push_str(&format!(
+            "        {}{}'[{}]{}' \\\n",
+            repeat, spec, f.description, value
+        ));
+    }
+    let commands: Vec<_> = COMMANDS
+        .iter()
+        .map(|(name, description)| format!("{}\\:\"{}\"", name, description))
+        .collect();
+    script.push_str(&format!(
+        "        '1:command:(({}))' \\\n        '*:file:_files'\n}}\n\n_traduora_update \"$@\"\n",
+        commands.join(" ")
+    ));
+    script
+}
+
+fn fish() -> String {
+    let mut script = String::from("complete -c traduora-update -f\n");
+    for (name, description) in COMMANDS {
+        script.push_str(&format!(
+            "complete -c traduora-update -n __fish_use_subcommand -a {} -d '{}'\n",
+            name, description
+        ));
+    }
+    for f in FLAGS {
+        let short = f.short.map(|s| format!(" -s {}", s)).unwrap_or_default();
+        let value = match f.value {
+            Value::None => "",
+            Value::Text => " -x",
+            Value::File => " -r -F",
+        };
+        script.push_str(&format!(
+            "complete -c traduora-update{} -l {}{} -d '{}'\n",
+            short, f.long, value, f.description
+        ));
+    }
+    script.push_str(&format!(
+        "complete -c traduora-update -n '__fish_seen_subcommand_from completions' -a '{}'\n\
+         complete -c traduora-update -n '__fish_seen_subcommand_from config' -a validate\n",
+        SHELLS
+    ));
+    script
+}
+
+fn powershell() -> String {
+    let mut entries = String::new();
+    for (name, description) in COMMANDS {
+        entries.push_str(&format!("        @('{}', '{}')\n", name, description));
+    }
+    for f in FLAGS {
+        for name in names(f) {
+            entries.push_str(&format!("        @('{}', '{}')\n", name, f.description));
+        }
+    }
+    format!(
+        r#"Register-ArgumentCompleter -Native -CommandName traduora-update -ScriptBlock {{
+    param($wordToComplete, $commandAst, $cursorPosition)
+    $entries = @(
+{entries}    )
+    $entries | Where-Object {{ $_[0] -like "$wordToComplete*" }} | ForEach-Object {{
+        [System.Management.Automation.CompletionResult]::new($_[0], $_[0], 'ParameterValue', $_[1])
+    }}
+}}
+"#,
+        entries = entries
+    )
+}
+
+/// Completion script for `shell`, to be sourced by the shell.
+pub fn script(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => bash(),
+        Shell::Zsh => zsh(),
+        Shell::Fish => fish(),
+        Shell::Powershell => powershell(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complete_commands_and_options() {
+        let bash = script(Shell::Bash);
+        assert!(bash.contains("-c|--config|-t|--translation-file|--report|-o|--output)"));
+        assert!(bash.contains("\"gui diff apply check"));
+
+        let zsh = script(Shell::Zsh);
+        assert!(zsh.contains("{-c,--config}'[Config file to use]:file:_files' \\\n"));
+        assert!(zsh.contains("'*'{-v,--verbose}'[Log more details]' \\\n"));
+        assert!(zsh.contains("--only'[Only compare terms matching the pattern]:value: ' \\\n"));
+
+        let fish = script(Shell::Fish);
+        assert!(fish.contains(
+            "complete -c traduora-update -s c -l config -r -F -d 'Config file to use'\n"
+        ));
+
+        assert_eq!(Shell::Powershell, "pwsh".parse().unwrap());
+        assert!("cmd".parse::<Shell>().is_err());
+    }
+}
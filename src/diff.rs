@@ -0,0 +1,169 @@
+//! Word-level diff between two translations.
+//!
+//! Texts are split into words and the whitespace between them, so a diff never
+//! cuts a word in half. The longest common subsequence of both token lists is
+//! kept, everything else is reported as deleted from the old or inserted into
+//! the new text.
+
+use druid::text::{Attribute, RichText};
+use druid::{ArcStr, Color, FontStyle};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Equal,
+    Delete,
+    Insert,
+}
+
+impl<'a> Change<'a> {
+    fn text(&self) -> &'a str {
+        match *self {
+            Change::Equal(t) | Change::Delete(t) | Change::Insert(t) => t,
+        }
+    }
+}
+
+/// Splits the text into alternating runs of whitespace and non-whitespace.
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut last_whitespace = None;
+    for (i, c) in text.char_indices() {
+        let whitespace = c.is_whitespace();
+        if last_whitespace.map_or(false, |last| last != whitespace) {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+        last_whitespace = Some(whitespace);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// Computes the word-level changes that turn `old` into `new`.
+///
+/// Adjacent changes of the same kind are merged.
+pub fn diff<'a>(old: &'a str, new: &'a str) -> Vec<Change<'a>> {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let (n, m) = (old_tokens.len(), new_tokens.len());
+
+    // lcs[i][j] is the length of the longest common subsequence of old_tokens[i..] and new_tokens[j..]
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_tokens[i] == new_tokens[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    // Runs of the same kind are contiguous in their source text and returned as one slice.
+    let mut runs: Vec<(Kind, usize, usize)> = Vec::new();
+    let mut push = |kind, start, len| match runs.last_mut() {
+        Some((last, _, end)) if *last == kind => *end += len,
+        _ => runs.push((kind, start, start + len)),
+    };
+    let (mut i, mut j) = (0, 0);
+    let (mut old_pos, mut new_pos) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_tokens[i] == new_tokens[j] {
+            push(Kind::Equal, old_pos, old_tokens[i].len());
+            old_pos += old_tokens[i].len();
+            new_pos += new_tokens[j].len();
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            push(Kind::Delete, old_pos, old_tokens[i].len());
+            old_pos += old_tokens[i].len();
+            i += 1;
+        } else {
+            push(Kind::Insert, new_pos, new_tokens[j].len());
+            new_pos += new_tokens[j].len();
+            j += 1;
+        }
+    }
+
+    runs.into_iter()
+        .map(|(kind, start, end)| match kind {
+            Kind::Equal => Change::Equal(&old[start..end]),
+            Kind::Delete => Change::Delete(&old[start..end]),
+            Kind::Insert => Change::Insert(&new[start..end]),
+        })
+        .collect()
+}
+
+/// Renders the changes from `old` to `new` as a single text with deletions in red and
+/// insertions in green.
+pub fn highlight(old: &str, new: &str) -> RichText {
+    let changes = diff(old, new);
+    let text: String = changes.iter().map(Change::text).collect();
+    let mut rich = RichText::new(ArcStr::from(text));
+    let mut start = 0;
+    for change in changes {
+        let range = start..start + change.text().len();
+        start = range.end;
+        match change {
+            Change::Equal(_) => {}
+            Change::Delete(_) => {
+                rich.add_attribute(
+                    range.clone(),
+                    Attribute::text_color(Color::rgb8(0xe0, 0x4f, 0x4f)),
+                );
+                rich.add_attribute(range, Attribute::style(FontStyle::Italic));
+            }
+            Change::Insert(_) => {
+                rich.add_attribute(
+                    range.clone(),
+                    Attribute::text_color(Color::rgb8(0x4f, 0xb8, 0x4f)),
+                );
+                rich.add_attribute(range, Attribute::underline(true));
+            }
+        }
+    }
+    rich
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_word_change() {
+        let changes = diff("Save the file now", "Save the document now");
+        assert_eq!(
+            vec![
+                Change::Equal("Save the "),
+                Change::Delete("file"),
+                Change::Insert("document"),
+                Change::Equal(" now"),
+            ],
+            changes
+        );
+    }
+
+    #[test]
+    fn insertion_and_deletion_at_ends() {
+        assert_eq!(
+            vec![Change::Insert("Hello "), Change::Equal("world")],
+            diff("world", "Hello world")
+        );
+        assert_eq!(
+            vec![Change::Equal("Hello"), Change::Delete(" world")],
+            diff("Hello world", "Hello")
+        );
+        assert_eq!(vec![Change::Insert("new")], diff("", "new"));
+        assert!(diff("", "").is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+//! Listing of the operations an update would perform, without performing them.
+
+use std::fmt;
+
+use crate::loader::{Modification, Translation};
+
+/// The operations needed to bring Traduora up to date, in the order of the translations.
+pub struct Plan<'a>(pub &'a [Translation]);
+
+impl fmt::Display for Plan<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (mut created, mut updated, mut deleted) = (0, 0, 0);
+        for t in self.0 {
+            match &t.modification {
+                Modification::Added => {
+                    created += 1;
+                    writeln!(f, "[CREATE] {:?}: {:?}", t.term, t.translation)?;
+                }
+                Modification::Updated(_, previous) => {
+                    updated += 1;
+                    writeln!(
+                        f,
+                        "[UPDATE] {:?}: {:?} -> {:?}",
+                        t.term, previous, t.translation
+                    )?;
+                }
+                Modification::Removed(_) => {
+                    deleted += 1;
+                    writeln!(f, "[DELETE] {:?}: {:?}", t.term, t.translation)?;
+                }
+            }
+        }
+        writeln!(
+            f,
+            "{} created, {} updated, {} deleted. Nothing was sent to Traduora.",
+            created, updated, deleted
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_planned_operations() {
+        let translations = [
+            Translation::added("new".into(), "Neu".into()),
+            Translation::updated("changed".into(), "Neu".into(), "1".into(), "Alt".into()),
+            Translation::removed("gone".into(), "Weg".into(), "2".into()),
+        ];
+        assert_eq!(
+            "[CREATE] \"new\": \"Neu\"\n\
+             [UPDATE] \"changed\": \"Alt\" -> \"Neu\"\n\
+             [DELETE] \"gone\": \"Weg\"\n\
+             1 created, 1 updated, 1 deleted. Nothing was sent to Traduora.\n",
+            Plan(&translations).to_string()
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Exit codes of the modes without a window, so scripts can branch on the result.
+
+use traduora::{auth::Authenticated, ApiError, Client, Traduora};
+
+use crate::updater::Error as UpdateError;
+
+/// Whether `cause` is the error of a request sent with the `traduora` crate.
+/// The crate does not export the type, so it is named through its client.
+fn is_request_error<C: Client>(cause: &(dyn std::error::Error + 'static)) -> bool {
+    cause.is::<ApiError<C::Error>>()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// Traduora is up to date with the local translations.
+    NoChanges = 0,
+    /// Any error that fits none of the other codes, e.g. the project is locked by someone else.
+    Failure = 1,
+    /// Changes were applied or, in a dry run or check, would be applied.
+    Changes = 2,
+    /// Some of the changes could not be applied.
+    PartialFailure = 3,
+    /// The configuration or the local translation file is invalid.
+    Config = 4,
+    /// Traduora could not be reached or rejected the login.
+    Network = 5,
+}
+
+impl ExitCode {
+    /// Returns [`Network`] if the error was caused by talking to Traduora, `otherwise` if not.
+    ///
+    /// [`Network`]: ExitCode::Network
+    pub fn classify(error: &anyhow::Error, otherwise: ExitCode) -> ExitCode {
+        let from_network = crate::compat::is_unauthorized(error)
+            || error.chain().any(|cause| {
+                cause.is::<reqwest::Error>()
+                    || cause.is::<traduora::TraduoraError>()
+                    || is_request_error::<Traduora<Authenticated>>(cause)
+            });
+        if from_network {
+            ExitCode::Network
+        } else {
+            otherwise
+        }
+    }
+
+    pub fn of_update(error: &UpdateError) -> ExitCode {
+        match error {
+            UpdateError::ReadOnly => ExitCode::Failure,
+            UpdateError::ClientCreation(_) => ExitCode::Network,
+            UpdateError::Lock(e) => Self::classify(e, ExitCode::Failure),
+            UpdateError::Update(..) | UpdateError::Cancelled { .. } => ExitCode::PartialFailure,
+        }
+    }
+
+    /// Combines the codes of several runs, e.g. one per locale. Errors take precedence over
+    /// changes and the first error wins.
+    pub fn and(self, other: ExitCode) -> ExitCode {
+        match self {
+            ExitCode::NoChanges => other,
+            ExitCode::Changes if other == ExitCode::NoChanges => self,
+            ExitCode::Changes => other,
+            _ => self,
+        }
+    }
+
+    pub fn exit(self) -> ! {
+        std::process::exit(self as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn classify_errors() {
+        let local = anyhow::anyhow!("Invalid JSON.");
+        assert_eq!(
+            ExitCode::Config,
+            ExitCode::classify(&local, ExitCode::Config)
+        );
+
+        let rejected = anyhow::anyhow!("Token expired.")
+            .context(crate::compat::Unauthorized)
+            .context("Failed to load terms.");
+        assert_eq!(
+            ExitCode::Network,
+            ExitCode::classify(&rejected, ExitCode::Config)
+        );
+
+        let locked: anyhow::Result<()> =
+            Err(anyhow::anyhow!("Project is locked.")).context("Failed to lock.");
+        assert_eq!(
+            ExitCode::Failure,
+            ExitCode::of_update(&UpdateError::Lock(locked.unwrap_err()))
+        );
+    }
+
+    #[test]
+    fn combine_codes() {
+        assert_eq!(
+            ExitCode::Changes,
+            ExitCode::NoChanges.and(ExitCode::Changes)
+        );
+        assert_eq!(
+            ExitCode::Changes,
+            ExitCode::Changes.and(ExitCode::NoChanges)
+        );
+        assert_eq!(ExitCode::Network, ExitCode::Changes.and(ExitCode::Network));
+        assert_eq!(ExitCode::Config, ExitCode::Config.and(ExitCode::Network));
+    }
+}
@@ -0,0 +1,104 @@
+//! A header with a button that shows or hides the content below it.
+//!
+//! Unlike the namespace groups of the term lists, the expanded state is kept in the
+//! widget, so it can be used for data that is only displayed.
+
+use druid::widget::{prelude::*, Button};
+use druid::{Point, WidgetExt, WidgetPod};
+
+pub struct Expander<T> {
+    header: WidgetPod<T, Box<dyn Widget<T>>>,
+    body: WidgetPod<T, Box<dyn Widget<T>>>,
+    toggle: WidgetPod<bool, Box<dyn Widget<bool>>>,
+    expanded: bool,
+}
+
+impl<T: Data> Expander<T> {
+    pub fn new(header: impl Widget<T> + 'static, body: impl Widget<T> + 'static) -> Self {
+        let toggle =
+            Button::new(|expanded: &bool, _: &_| if *expanded { "▾" } else { "▸" }.to_string())
+                .on_click(|_, expanded: &mut bool, _| *expanded = !*expanded);
+        Self {
+            header: WidgetPod::new(header).boxed(),
+            body: WidgetPod::new(body.padding((20., 0., 0., 0.))).boxed(),
+            toggle: WidgetPod::new(toggle).boxed(),
+            expanded: false,
+        }
+    }
+
+    /// Shows the content until the button is clicked.
+    pub fn expanded(mut self) -> Self {
+        self.expanded = true;
+        self
+    }
+}
+
+impl<T: Data> Widget<T> for Expander<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        let expanded = self.expanded;
+        self.toggle.event(ctx, event, &mut self.expanded, env);
+        if expanded != self.expanded {
+            // The state is not part of the data, so changing it does not cause an update by itself.
+            ctx.request_update();
+            ctx.request_layout();
+        }
+        self.header.event(ctx, event, data, env);
+        if self.expanded || event.should_propagate_to_hidden() {
+            self.body.event(ctx, event, data, env);
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        self.toggle.lifecycle(ctx, event, &self.expanded, env);
+        self.header.lifecycle(ctx, event, data, env);
+        if self.expanded || event.should_propagate_to_hidden() {
+            self.body.lifecycle(ctx, event, data, env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        self.toggle.update(ctx, &self.expanded, env);
+        self.header.update(ctx, data, env);
+        if self.expanded {
+            self.body.update(ctx, data, env);
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let loose = bc.loosen();
+        let toggle = self.toggle.layout(ctx, &loose, &self.expanded, env);
+        let header_bc = loose.shrink((toggle.width, 0.));
+        let header = self.header.layout(ctx, &header_bc, data, env);
+        let header_height = toggle.height.max(header.height);
+        self.toggle.set_origin(
+            ctx,
+            &self.expanded,
+            env,
+            Point::new(0., (header_height - toggle.height) / 2.),
+        );
+        self.header.set_origin(
+            ctx,
+            data,
+            env,
+            Point::new(toggle.width, (header_height - header.height) / 2.),
+        );
+
+        let mut size = Size::new(toggle.width + header.width, header_height);
+        if self.expanded {
+            let body_bc = BoxConstraints::new(Size::ZERO, Size::new(bc.max().width, f64::INFINITY));
+            let body = self.body.layout(ctx, &body_bc, data, env);
+            self.body
+                .set_origin(ctx, data, env, Point::new(0., header_height));
+            size = Size::new(size.width.max(body.width), header_height + body.height);
+        }
+        bc.constrain(size)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.toggle.paint(ctx, &self.expanded, env);
+        self.header.paint(ctx, data, env);
+        if self.expanded {
+            self.body.paint(ctx, data, env);
+        }
+    }
+}
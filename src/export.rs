@@ -0,0 +1,93 @@
+//! Export of pending changes for review before uploading.
+//!
+//! The format is chosen by the file extension: `.csv` writes comma-separated
+//! values, everything else writes a JSON array.
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::loader::{Modification, Translation};
+
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    term: &'a str,
+    translation: &'a str,
+    modification: &'static str,
+}
+
+impl<'a> From<&'a Translation> for Record<'a> {
+    fn from(t: &'a Translation) -> Self {
+        Self {
+            term: &t.term,
+            translation: &t.translation,
+            modification: match t.modification {
+                Modification::Added => "added",
+                Modification::Removed(_) => "removed",
+                Modification::Updated(..) => "updated",
+            },
+        }
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+fn to_csv(records: &[Record]) -> String {
+    let mut csv = String::from("term,translation,modification\n");
+    for r in records {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            csv_field(r.term),
+            csv_field(r.translation),
+            r.modification
+        ));
+    }
+    csv
+}
+
+/// Writes the translations to `path` and returns the number of written entries.
+pub fn write(path: impl AsRef<Path>, translations: &[Translation]) -> Result<usize> {
+    let path = path.as_ref();
+    let records: Vec<Record> = translations.iter().map(Record::from).collect();
+
+    let is_csv = path
+        .extension()
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("csv"));
+    let data = if is_csv {
+        to_csv(&records)
+    } else {
+        serde_json::to_string_pretty(&records)?
+    };
+
+    crate::atomic::write(path, data)?;
+    log::info!("Exported {} entries to {}.", records.len(), path.display());
+    Ok(records.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_csv_fields() {
+        let translations = [
+            Translation::added("greeting".into(), "Hello, \"world\"".into()),
+            Translation::removed("farewell".into(), "Bye".into(), "id".into()),
+        ];
+        let records: Vec<Record> = translations.iter().map(Record::from).collect();
+
+        assert_eq!(
+            "term,translation,modification\n\
+            greeting,\"Hello, \"\"world\"\"\",added\n\
+            farewell,Bye,removed\n",
+            to_csv(&records)
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Size and position of the main window, persisted across runs.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use druid::widget::{prelude::*, Controller};
+use druid::{Point, Size};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Geometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Geometry {
+    pub fn position(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+/// Latest geometry of the main window, saved once the window is closed.
+static CURRENT: Lazy<Mutex<Option<Geometry>>> = Lazy::new(Mutex::default);
+
+fn state_file() -> Result<PathBuf> {
+    let dir = dirs::config_dir().context("Failed to determine config directory.")?;
+    Ok(dir.join("traduora-update").join("window.json"))
+}
+
+/// Loads the geometry the main window had when it was last closed.
+pub fn load() -> Option<Geometry> {
+    let fun = || -> Result<Geometry> {
+        let path = state_file()?;
+        let data = std::fs::read(&path)
+            .with_context(|| format!("Failed to read window state {}", path.display()))?;
+        serde_json::from_slice(&data)
+            .with_context(|| format!("Failed to parse window state {}", path.display()))
+    };
+    fun()
+        .map_err(|e| log::info!("Using default window geometry: {:?}", e))
+        .ok()
+}
+
+/// Writes the last tracked geometry of the main window.
+pub fn store() -> Result<()> {
+    let geometry = match *CURRENT.lock().unwrap() {
+        Some(geometry) => geometry,
+        None => return Ok(()),
+    };
+    let path = state_file()?;
+    let fun = || -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        crate::atomic::write(&path, serde_json::to_vec(&geometry)?)
+    };
+    fun().with_context(|| format!("Failed to write window state {}", path.display()))
+}
+
+/// Keeps track of the geometry of the window that contains the widget.
+///
+/// Moving a window does not produce an event, so the position is refreshed
+/// whenever the window is resized or the mouse moves over it.
+pub struct Tracker;
+
+impl<T, W: Widget<T>> Controller<T, W> for Tracker {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        let size = match event {
+            Event::WindowSize(size) => Some(*size),
+            Event::MouseMove(_) => None,
+            _ => return child.event(ctx, event, data, env),
+        };
+        let mut current = CURRENT.lock().unwrap();
+        let size = size
+            .or_else(|| current.map(|g| g.size()))
+            .unwrap_or_else(|| ctx.window().get_size());
+        let position = ctx.window().get_position();
+        *current = Some(Geometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        });
+        drop(current);
+        child.event(ctx, event, data, env);
+    }
+}
@@ -0,0 +1,376 @@
+//! Syntax check of ICU MessageFormat messages, e.g. `{count, plural, one {# item} other {# items}}`.
+//!
+//! Only the syntax is checked, not whether the arguments or plural categories suit the locale.
+//! Apostrophes quote like in ICU: `'{'` is a literal brace and `''` a literal apostrophe.
+
+use std::fmt;
+
+/// Plural categories of CLDR, the keywords a plural variant may be selected by.
+const PLURAL_CATEGORIES: [&str; 6] = ["zero", "one", "two", "few", "many", "other"];
+
+/// Types of arguments that are formatted without variants.
+const SIMPLE_TYPES: [&str; 6] = ["number", "date", "time", "spellout", "ordinal", "duration"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// An argument or variant is not closed with `}`.
+    UnclosedArgument,
+    /// A `}` closes no argument.
+    UnmatchedBrace,
+    /// The argument has no name or an invalid one, e.g. `{}` or `{{name}}`.
+    InvalidArgumentName,
+    /// Something else than `,` or `}` follows the name or type of an argument.
+    ExpectedCommaOrBrace,
+    UnknownType(String),
+    /// A plural variant is selected by neither a plural category nor an exact value like `=0`.
+    InvalidSelector(String),
+    DuplicateSelector(String),
+    /// The selector is not followed by a message in braces.
+    MissingVariant(String),
+    /// The `offset:` of a plural argument is not followed by a number.
+    InvalidOffset,
+    /// A plural or select argument has no `other` variant to fall back to.
+    MissingOther,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// Index of the character where the problem was found.
+    pub position: usize,
+    pub problem: Problem,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::UnclosedArgument => write!(f, "Argument is not closed with }}"),
+            Problem::UnmatchedBrace => write!(f, "}} closes no argument"),
+            Problem::InvalidArgumentName => write!(f, "Argument has no valid name"),
+            Problem::ExpectedCommaOrBrace => write!(f, "Expected , or }}"),
+            Problem::UnknownType(kind) => write!(f, "Unknown argument type {:?}", kind),
+            Problem::InvalidSelector(selector) => write!(f, "Invalid selector {:?}", selector),
+            Problem::DuplicateSelector(selector) => {
+                write!(f, "Selector {:?} is used more than once", selector)
+            }
+            Problem::MissingVariant(selector) => {
+                write!(
+                    f,
+                    "Selector {:?} is not followed by a message in braces",
+                    selector
+                )
+            }
+            Problem::InvalidOffset => write!(f, "offset: is not followed by a number"),
+            Problem::MissingOther => write!(f, "Plural or select argument has no other variant"),
+        }
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at character {}.", self.problem, self.position + 1)
+    }
+}
+
+/// Checks the syntax of the message and returns the first problem.
+pub fn validate(message: &str) -> Result<(), SyntaxError> {
+    let mut parser = Parser {
+        chars: message.chars().collect(),
+        pos: 0,
+    };
+    parser.message(false)?;
+    match parser.peek() {
+        Some(_) => Err(parser.error(Problem::UnmatchedBrace)),
+        None => Ok(()),
+    }
+}
+
+/// Names of arguments are lenient: besides ICU identifiers, the dotted and dashed names of
+/// other placeholder syntaxes like `{user.name}` are accepted.
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | ':' | '-')
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn error(&self, problem: Problem) -> SyntaxError {
+        self.error_at(self.pos, problem)
+    }
+
+    fn error_at(&self, position: usize, problem: Problem) -> SyntaxError {
+        SyntaxError { position, problem }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().map_or(false, char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while self.peek().map_or(false, &predicate) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// Reads text and arguments up to a `}` that closes none of them or up to the end.
+    fn message(&mut self, in_plural: bool) -> Result<(), SyntaxError> {
+        while let Some(c) = self.peek() {
+            match c {
+                '{' => self.argument()?,
+                '}' => return Ok(()),
+                '\'' => self.apostrophe(in_plural),
+                _ => self.pos += 1,
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips an apostrophe and, if it starts a quote, everything up to the closing one. A quote
+    /// that is not closed runs to the end like in ICU.
+    fn apostrophe(&mut self, in_plural: bool) {
+        self.pos += 1;
+        match self.peek() {
+            Some('\'') => self.pos += 1,
+            Some('{' | '}' | '|') => self.skip_quote(),
+            Some('#') if in_plural => self.skip_quote(),
+            _ => {}
+        }
+    }
+
+    fn skip_quote(&mut self) {
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == '\'' {
+                if self.peek() == Some('\'') {
+                    self.pos += 1;
+                } else {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn argument(&mut self) -> Result<(), SyntaxError> {
+        let start = self.pos;
+        let unclosed = |parser: &Self| parser.error_at(start, Problem::UnclosedArgument);
+        self.pos += 1;
+        self.skip_whitespace();
+        let name_start = self.pos;
+        let name = self.take_while(is_name_char);
+        let is_number = name.starts_with(|c: char| c.is_ascii_digit());
+        if name.is_empty() || (is_number && !name.chars().all(|c| c.is_ascii_digit())) {
+            return Err(match self.peek() {
+                None => unclosed(self),
+                Some(_) => self.error_at(name_start, Problem::InvalidArgumentName),
+            });
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some('}') => {
+                self.pos += 1;
+                return Ok(());
+            }
+            Some(',') => self.pos += 1,
+            Some(_) => return Err(self.error(Problem::ExpectedCommaOrBrace)),
+            None => return Err(unclosed(self)),
+        }
+
+        self.skip_whitespace();
+        let type_start = self.pos;
+        let kind = self.take_while(is_name_char);
+        self.skip_whitespace();
+        if self.peek().is_none() {
+            return Err(unclosed(self));
+        }
+        match kind.as_str() {
+            "plural" | "selectordinal" | "select" => {
+                match self.peek() {
+                    Some(',') => self.pos += 1,
+                    Some('}') => return Err(self.error_at(start, Problem::MissingOther)),
+                    _ => return Err(self.error(Problem::ExpectedCommaOrBrace)),
+                }
+                self.variants(start, kind == "select")?;
+            }
+            kind if SIMPLE_TYPES.contains(&kind) => {
+                if self.peek() == Some(',') {
+                    self.pos += 1;
+                    self.style();
+                }
+            }
+            _ => return Err(self.error_at(type_start, Problem::UnknownType(kind))),
+        }
+        match self.peek() {
+            Some('}') => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(_) => Err(self.error(Problem::ExpectedCommaOrBrace)),
+            None => Err(unclosed(self)),
+        }
+    }
+
+    /// Skips the style of a simple argument, e.g. `::currency/EUR` or a date pattern.
+    fn style(&mut self) {
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            match c {
+                '\'' => {
+                    self.apostrophe(false);
+                    continue;
+                }
+                '{' => depth += 1,
+                '}' if depth == 0 => return,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Reads the variants of a plural or select argument up to its closing brace.
+    fn variants(&mut self, start: usize, select: bool) -> Result<(), SyntaxError> {
+        self.skip_whitespace();
+        if !select && self.chars[self.pos..].starts_with(&['o', 'f', 'f', 's', 'e', 't', ':']) {
+            self.pos += "offset:".len();
+            self.skip_whitespace();
+            if self.take_while(|c| c.is_ascii_digit()).is_empty() {
+                return Err(self.error(Problem::InvalidOffset));
+            }
+        }
+
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let selector_start = self.pos;
+            let selector = match self.peek() {
+                None | Some('}') => break,
+                Some('=') => {
+                    self.pos += 1;
+                    let value = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                    format!("={}", value)
+                }
+                Some(_) => self.take_while(is_name_char),
+            };
+            let valid = if select {
+                !selector.is_empty()
+            } else {
+                (selector.len() > 1 && selector.starts_with('='))
+                    || PLURAL_CATEGORIES.contains(&selector.as_str())
+            };
+            if !valid {
+                let selector = if selector.is_empty() {
+                    self.peek().map(String::from).unwrap_or_default()
+                } else {
+                    selector
+                };
+                let problem = Problem::InvalidSelector(selector);
+                return Err(self.error_at(selector_start, problem));
+            }
+            if selectors.contains(&selector) {
+                let problem = Problem::DuplicateSelector(selector);
+                return Err(self.error_at(selector_start, problem));
+            }
+
+            self.skip_whitespace();
+            if self.peek() != Some('{') {
+                return Err(self.error(Problem::MissingVariant(selector)));
+            }
+            let variant_start = self.pos;
+            self.pos += 1;
+            self.message(!select)?;
+            if self.peek() != Some('}') {
+                return Err(self.error_at(variant_start, Problem::UnclosedArgument));
+            }
+            self.pos += 1;
+            selectors.push(selector);
+        }
+        if !selectors.iter().any(|s| s == "other") {
+            return Err(self.error_at(start, Problem::MissingOther));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(message: &str) -> Option<(usize, Problem)> {
+        validate(message).err().map(|e| (e.position, e.problem))
+    }
+
+    #[test]
+    fn accept_valid_messages() {
+        for message in [
+            "",
+            "Plain text with 'apostrophes' and # signs",
+            "Hello {name}, you are {user.age, number} years old",
+            "Due on {due, date, short} at {due, time, ::jmm}",
+            "Costs {price, number, ::currency/EUR}",
+            "{count, plural, =0 {No items} one {# item} other {# items}}",
+            "{count, plural, offset:1 =0 {Nobody} other {You and # others}}",
+            "{place, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}",
+            "{gender, select, female {She has {count, plural, one {# cat} other {# cats}}} \
+             other {They have cats}}",
+            "Literal '{braces}' and it''s fine, '#' too",
+            "{count, plural, other {'#' is # and '{'}}",
+        ] {
+            assert_eq!(None, problem(message), "{}", message);
+        }
+    }
+
+    #[test]
+    fn report_syntax_errors() {
+        assert_eq!(Some((6, Problem::UnclosedArgument)), problem("Hello {name"));
+        assert_eq!(Some((6, Problem::UnmatchedBrace)), problem("Hello }"));
+        assert_eq!(Some((1, Problem::InvalidArgumentName)), problem("{{name}}"));
+        assert_eq!(Some((2, Problem::InvalidArgumentName)), problem("{ }"));
+        assert_eq!(
+            Some((6, Problem::ExpectedCommaOrBrace)),
+            problem("{user name}")
+        );
+        assert_eq!(
+            Some((4, Problem::UnknownType("plurl".into()))),
+            problem("{n, plurl, other {x}}")
+        );
+        assert_eq!(
+            Some((0, Problem::MissingOther)),
+            problem("{n, plural, one {x}}")
+        );
+        assert_eq!(Some((0, Problem::MissingOther)), problem("{n, plural}"));
+        assert_eq!(
+            Some((12, Problem::InvalidSelector("single".into()))),
+            problem("{n, plural, single {x} other {y}}")
+        );
+        assert_eq!(
+            Some((19, Problem::DuplicateSelector("one".into()))),
+            problem("{n, plural, one {} one {} other {}}")
+        );
+        assert_eq!(
+            Some((26, Problem::MissingVariant("other".into()))),
+            problem("{n, select, male {} other x}")
+        );
+        assert_eq!(
+            Some((0, Problem::UnclosedArgument)),
+            problem("{n, plural, other {# items}")
+        );
+        assert_eq!(
+            Some((20, Problem::InvalidOffset)),
+            problem("{n, plural, offset: {}}")
+        );
+        assert_eq!(
+            "Argument is not closed with } at character 7.",
+            validate("Hello {name").unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! The parts of JSON5 that people use when writing JSON by hand: comments, trailing commas,
+//! strings in single quotes and keys without quotes.
+//!
+//! Instead of a parser of its own, the text is turned into JSON that `serde_json` reads. Line
+//! breaks are kept, so the line numbers of errors point to the original text, and the columns are
+//! mapped back with the places where the JSON is shifted against the text.
+
+use anyhow::Result;
+
+/// Lines longer than this are cut around the error in snippets, e.g. in minified files.
+const MAX_SNIPPET_WIDTH: usize = 80;
+
+/// JSON converted from a JSON5 text.
+pub struct Json {
+    pub json: String,
+    /// Positions in the JSON and the text from which on both are shifted against each other by
+    /// the difference, in increasing order.
+    shifts: Vec<(usize, usize)>,
+}
+
+impl Json {
+    /// Position in the text of the position in the JSON.
+    fn original_position(&self, pos: usize) -> usize {
+        let index = self.shifts.partition_point(|&(json, _)| json <= pos);
+        match index.checked_sub(1) {
+            Some(i) => {
+                let (json, text) = self.shifts[i];
+                text + (pos - json)
+            }
+            None => pos,
+        }
+    }
+
+    /// Describes an error of `serde_json` with its line and column in the original text and a
+    /// snippet of the line, instead of a position in the converted JSON.
+    pub fn describe(&self, text: &str, error: &serde_json::Error) -> String {
+        let message = error.to_string();
+        let suffix = format!(" at line {} column {}", error.line(), error.column());
+        let message = message.strip_suffix(&suffix).unwrap_or(&message);
+        if error.line() == 0 {
+            return message.to_owned();
+        }
+        let line_start: usize = self
+            .json
+            .split_inclusive('\n')
+            .take(error.line() - 1)
+            .map(str::len)
+            .sum();
+        let pos = (line_start + error.column().saturating_sub(1)).min(self.json.len());
+        let mut pos = self.original_position(pos).min(text.len());
+        while !text.is_char_boundary(pos) {
+            pos -= 1;
+        }
+        describe_position(text, pos, message)
+    }
+}
+
+/// The message with the line and column of the position in the text and a snippet of the line
+/// that points at the column.
+pub fn describe_position(text: &str, pos: usize, message: &str) -> String {
+    let line_start = text[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = text[pos..].find('\n').map_or(text.len(), |i| pos + i);
+    let line_number = text[..line_start].matches('\n').count() + 1;
+    let column = text[line_start..pos].chars().count();
+    let line: Vec<char> = text[line_start..line_end].trim_end().chars().collect();
+
+    let (mut from, mut to) = (0, line.len());
+    if line.len() > MAX_SNIPPET_WIDTH {
+        from = column.saturating_sub(MAX_SNIPPET_WIDTH / 2);
+        to = (from + MAX_SNIPPET_WIDTH).min(line.len());
+        from = to.saturating_sub(MAX_SNIPPET_WIDTH);
+    }
+    let mut snippet: String = line[from..to].iter().collect();
+    // Tabs stay tabs below the line, so the marker is aligned however wide they are shown.
+    let mut marker: String = line[from..column.min(to)]
+        .iter()
+        .map(|&c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    if from > 0 {
+        snippet.insert(0, '…');
+        marker.insert(0, ' ');
+    }
+    if to < line.len() {
+        snippet.push('…');
+    }
+    let gutter = " ".repeat(line_number.to_string().len());
+    format!(
+        "{} at line {}, column {}:\n {} | {}\n {} | {}^",
+        message,
+        line_number,
+        column + 1,
+        line_number,
+        snippet,
+        gutter,
+        marker
+    )
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Converts JSON5 to JSON. Comments become spaces.
+pub fn to_json(text: &str) -> Result<String> {
+    convert(text).map(|converted| converted.json)
+}
+
+/// Converts JSON5 to JSON like [`to_json`] and keeps what is needed to find the positions of
+/// errors in the text.
+pub fn convert(text: &str) -> Result<Json> {
+    let mut json = String::with_capacity(text.len());
+    let mut shifts = Vec::new();
+    // Position of the last comma in `json` while nothing but whitespace and comments follow it.
+    let mut comma = None;
+    let mut chars = text.chars();
+    let mut pos = 0;
+    while let Some(c) = chars.next() {
+        let start = pos;
+        match c {
+            '/' if matches!(chars.clone().next(), Some('/' | '*')) => {
+                let block = chars.next() == Some('*');
+                json.push_str("  ");
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' && !block {
+                        json.push('\n');
+                        break;
+                    }
+                    json.push(if c == '\n' { '\n' } else { ' ' });
+                    if block && previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '"' | '\'' => {
+                comma = None;
+                json.push('"');
+                // Line breaks of line continuations, which follow the string to keep the lines.
+                let mut continued = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' | 'u')) => {
+                                json.push('\\');
+                                json.push(c);
+                            }
+                            Some('x') => json.push_str("\\u00"),
+                            Some('0') => json.push_str("\\u0000"),
+                            Some('\'') => json.push('\''),
+                            // A line continuation is not part of the string.
+                            Some('\n') => continued.push('\n'),
+                            Some('\r') => {
+                                continued.push('\r');
+                                if chars.clone().next() == Some('\n') {
+                                    chars.next();
+                                    continued.push('\n');
+                                }
+                            }
+                            Some('\u{2028}' | '\u{2029}') => {}
+                            // Any other character stands for itself.
+                            Some(c) => json.push(c),
+                            None => anyhow::bail!(describe_position(
+                                text,
+                                start,
+                                "String is not closed"
+                            )),
+                        },
+                        Some('"') if c == '\'' => json.push_str("\\\""),
+                        Some(end) if end == c => break,
+                        Some(c) => json.push(c),
+                        None => {
+                            anyhow::bail!(describe_position(text, start, "String is not closed"))
+                        }
+                    }
+                }
+                json.push('"');
+                json.push_str(&continued);
+            }
+            ',' => {
+                comma = Some(json.len());
+                json.push(',');
+            }
+            '}' | ']' => {
+                if let Some(comma) = comma.take() {
+                    json.replace_range(comma..comma + 1, " ");
+                }
+                json.push(c);
+            }
+            c if c.is_whitespace() => json.push(c),
+            c if is_identifier_start(c) => {
+                comma = None;
+                let mut identifier = c.to_string();
+                while let Some(c) = chars.clone().next().filter(|&c| is_identifier_char(c)) {
+                    identifier.push(c);
+                    chars.next();
+                }
+                let is_key = chars.clone().find(|c| !c.is_whitespace()) == Some(':');
+                if is_key {
+                    json.push_str(&serde_json::to_string(&identifier)?);
+                } else {
+                    json.push_str(&identifier);
+                }
+            }
+            c => {
+                comma = None;
+                json.push(c);
+            }
+        }
+        pos = text.len() - chars.as_str().len();
+        let shift = json.len() as isize - pos as isize;
+        let last_shift = shifts.last().map_or(0, |&(json, text): &(usize, usize)| {
+            json as isize - text as isize
+        });
+        if shift != last_shift {
+            shifts.push((json.len(), pos));
+        }
+    }
+    Ok(Json { json, shifts })
+}
+
+/// Replaces the comments with spaces byte by byte, so positions in the result are the same as
+/// in the text.
+pub fn blank_comments(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let blank = |c: char, result: &mut String| {
+        if c == '\n' {
+            result.push('\n');
+        } else {
+            result.extend(std::iter::repeat(' ').take(c.len_utf8()));
+        }
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if matches!(chars.peek(), Some('/' | '*')) => {
+                let block = chars.next() == Some('*');
+                result.push_str("  ");
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' && !block {
+                        result.push('\n');
+                        break;
+                    }
+                    blank(c, &mut result);
+                    if block && previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '"' | '\'' => {
+                result.push(c);
+                while let Some(next) = chars.next() {
+                    result.push(next);
+                    if next == '\\' {
+                        result.extend(chars.next());
+                    } else if next == c {
+                        break;
+                    }
+                }
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Reads a string literal in single or double quotes.
+pub fn unquote(literal: &str) -> Result<String> {
+    Ok(serde_json::from_str(&to_json(literal)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_to_json() {
+        let text = "{\n\
+                    \x20 // comment with 'quote\n\
+                    \x20 unquoted: 'single \"quoted\" it\\'s',\n\
+                    \x20 $key_2: \"double // no comment\", /* block\n\
+                    \x20 comment */ 'list': [1, true, null,],\n\
+                    \x20 \"hex\": '\\x41',\n\
+                    }";
+        let json = to_json(text).unwrap();
+        assert_eq!(text.lines().count(), json.lines().count());
+        assert_eq!(
+            serde_json::json!({
+                "unquoted": "single \"quoted\" it's",
+                "$key_2": "double // no comment",
+                "list": [1, true, null],
+                "hex": "A",
+            }),
+            serde_json::from_str::<serde_json::Value>(&json).unwrap()
+        );
+        assert!(to_json("{ 'open: 1 }").is_err());
+    }
+
+    #[test]
+    fn drop_line_continuations() {
+        let text = "{\n  a: 'one \\\ntwo \\\r\nthree \\\u{2028}four',\n  b: \"five \\\rsix\",\n}";
+        let json = to_json(text).unwrap();
+        assert_eq!(text.lines().count(), json.lines().count());
+        assert_eq!(
+            serde_json::json!({ "a": "one two three four", "b": "five six" }),
+            serde_json::from_str::<serde_json::Value>(&json).unwrap()
+        );
+    }
+
+    #[test]
+    fn describe_errors_in_the_text() {
+        let text = "{\n  // a comment\n  key: 'value',\n  \"other\" 'value'\n}";
+        let converted = convert(text).unwrap();
+        let error = serde_json::from_str::<serde_json::Value>(&converted.json).unwrap_err();
+        assert_eq!(
+            "expected `:` at line 4, column 11:\n 4 |   \"other\" 'value'\n   |           ^",
+            converted.describe(text, &error)
+        );
+        assert_eq!(
+            "String is not closed at line 1, column 3:\n 1 | { 'open: 1 }\n   |   ^",
+            convert("{ 'open: 1 }").err().unwrap().to_string()
+        );
+        let long = format!("{{{}\"a\" 1}}", " ".repeat(100));
+        let converted = convert(&long).unwrap();
+        let error = serde_json::from_str::<serde_json::Value>(&converted.json).unwrap_err();
+        let description = converted.describe(&long, &error);
+        assert!(description.starts_with("expected `:` at line 1, column 106:\n 1 | …"));
+        assert!(description.ends_with(&format!("\n   | {}^", " ".repeat(79))));
+    }
+
+    #[test]
+    fn blank_comments_in_place() {
+        let text = "{ 'a//b': \"ü\", // über\n /* * */ b: 1 }";
+        let blanked = blank_comments(text);
+        assert_eq!(text.len(), blanked.len());
+        assert_eq!("{ 'a//b': \"ü\",         \n         b: 1 }", blanked);
+        assert_eq!("it's \"quoted\"", unquote("'it\\'s \"quoted\"'").unwrap());
+    }
+}
@@ -0,0 +1,134 @@
+//! Secrets in the keyring of the operating system, so the config file does not have to
+//! contain the password or client secret.
+//!
+//! The command line tools of the platform are used: `secret-tool` of the Secret Service on
+//! Linux, `security` of the Keychain on macOS and `cmdkey` of the Credential Manager on
+//! Windows, which is read directly because `cmdkey` does not reveal secrets. Entries are
+//! identified by the name given as `keyring` in the config file together with the mail or
+//! client id.
+
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+
+/// Reads the secret of `account` from the keyring entry `service`.
+pub fn get(service: &str, account: &str) -> Result<String> {
+    match read(service, account)? {
+        Some(secret) if !secret.is_empty() => Ok(secret),
+        _ => anyhow::bail!(
+            "No secret for {:?} in keyring entry {:?}. Store it with traduora-update store-credentials.",
+            account,
+            service
+        ),
+    }
+}
+
+#[cfg(not(windows))]
+fn read(service: &str, account: &str) -> Result<Option<String>> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("security");
+        command.args(["find-generic-password", "-w", "-s", service, "-a", account]);
+        command
+    } else if cfg!(unix) {
+        let mut command = Command::new("secret-tool");
+        command.args(["lookup", "service", service, "account", account]);
+        command
+    } else {
+        anyhow::bail!("The keyring is not supported on this platform.");
+    };
+    let output = command
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("Failed to run {:?}", command))?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let secret = String::from_utf8(output.stdout).context("Secret in keyring is not UTF-8.")?;
+    Ok(Some(secret.trim_end_matches(&['\r', '\n'][..]).to_owned()))
+}
+
+/// Name of the generic credential in the Windows Credential Manager.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn target(service: &str, account: &str) -> String {
+    format!("{}:{}", service, account)
+}
+
+#[cfg(windows)]
+fn read(service: &str, account: &str) -> Result<Option<String>> {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::shared::winerror::ERROR_NOT_FOUND;
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::wincred::{CredFree, CredReadW, CRED_TYPE_GENERIC, PCREDENTIALW};
+
+    let target: Vec<u16> = std::ffi::OsStr::new(&target(service, account))
+        .encode_wide()
+        .chain(Some(0))
+        .collect();
+    let mut credential: PCREDENTIALW = std::ptr::null_mut();
+    // SAFETY: The target is null-terminated and the credential is freed below.
+    if unsafe { CredReadW(target.as_ptr(), CRED_TYPE_GENERIC, 0, &mut credential) } == 0 {
+        return match unsafe { GetLastError() } {
+            ERROR_NOT_FOUND => Ok(None),
+            code => Err(std::io::Error::from_raw_os_error(code as i32))
+                .context("Failed to read from the Credential Manager."),
+        };
+    }
+    // SAFETY: The blob of a found credential holds `CredentialBlobSize` bytes.
+    let blob = unsafe {
+        let blob = std::slice::from_raw_parts(
+            (*credential).CredentialBlob,
+            (*credential).CredentialBlobSize as usize,
+        )
+        .to_vec();
+        CredFree(credential.cast());
+        blob
+    };
+    // `cmdkey` stores the secret as UTF-16.
+    let utf16: Vec<u16> = blob
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    let secret = String::from_utf16(&utf16).context("Secret in keyring is not UTF-16.")?;
+    Ok(Some(secret))
+}
+
+/// Stores a secret for `account` in the keyring entry `service`. The platform tool asks
+/// for the secret on the terminal, so it does not show up in the process list.
+pub fn store(service: &str, account: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("security");
+        // Without a value after -w, the secret is prompted for.
+        command.args([
+            "add-generic-password",
+            "-U",
+            "-s",
+            service,
+            "-a",
+            account,
+            "-w",
+        ]);
+        command
+    } else if cfg!(unix) {
+        let mut command = Command::new("secret-tool");
+        command
+            .arg("store")
+            .arg(format!("--label=Traduora-Update {}", service))
+            .args(["service", service, "account", account]);
+        command
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmdkey");
+        // Without a value after /pass, the secret is prompted for.
+        command
+            .arg(format!("/generic:{}", target(service, account)))
+            .arg(format!("/user:{}", account))
+            .arg("/pass");
+        command
+    } else {
+        anyhow::bail!("The keyring is not supported on this platform.");
+    };
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to run {:?}", command))?;
+    anyhow::ensure!(status.success(), "{:?} failed with {}.", command, status);
+    Ok(())
+}
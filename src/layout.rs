@@ -1,18 +1,38 @@
-use std::sync::Arc;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 
 use druid::widget::{
-    Button, Checkbox, Controller, Either, Flex, Label, LineBreaking, List, ProgressBar, Scroll,
-    Spinner, Tabs, TabsTransition,
+    Button, Controller, CrossAxisAlignment, Either, Flex, Label, LineBreaking, List, Painter,
+    ProgressBar, RawLabel, Scroll, SizedBox, Spinner, TextBox, ViewSwitcher,
+};
+use druid::{commands, FileDialogOptions, FileInfo, FileSpec};
+use druid::{
+    im, theme, AppDelegate, ArcStr, Event, EventCtx, ExtEventSink, HotKey, KbKey, Key, LensExt,
+    LifeCycle, LifeCycleCtx, LocalizedString, MenuDesc, MenuItem, RenderContext, Selector,
+    SingleUse, SysMods, Target, WidgetId,
 };
-use druid::{im, theme, AppDelegate, ExtEventSink, LensExt, Selector, SingleUse, Target};
 use druid::{Data, Lens};
 use druid::{Env, Widget, WidgetExt};
 use itertools::Itertools;
 use traduora::api::TermId;
 
-use crate::loader::{Modification, Translation};
+use crate::cli::Cli;
+use crate::column::{Column, Divider};
+use crate::config::{AppConfig, LoginConfig};
+use crate::expander::Expander;
+use crate::lazy::Lazy;
+use crate::loader::{Destination, LoadedData, Modification, TermMetadata, Totals, Translation};
 use crate::modal_host::ModalHost;
-use crate::updater::{Error as UpdateError, UpdateResult};
+use crate::picker::Picker;
+use crate::strings;
+use crate::tooltip::Tooltip;
+use crate::tri_state::{checkbox, Selection, TriStateCheckbox};
+use crate::updater::{Error as UpdateError, Failure, Operation, Progress, Step, UpdateResult};
+use crate::virtual_list::VirtualList;
+use crate::warnings::Warning;
+use crate::wizard::{self, Wizard};
 
 trait LensExtExt<A: ?Sized, B: ?Sized>: LensExt<A, B> {
     fn read_only<Get, C>(self, get: Get) -> druid::lens::Then<Self, ReadOnly<Get>, B>
@@ -57,18 +77,23 @@ where
 
 #[derive(Data, Debug, Clone, Lens)]
 pub struct TabData<T: Clone> {
-    pub select_all_active: bool,
     pub entries: im::Vector<ModificationEntry<T>>,
+    pub term_column_width: f64,
+    /// Namespaces that are expanded in the grouped view.
+    pub expanded: im::HashSet<String>,
 }
 
+const DEFAULT_TERM_COLUMN_WIDTH: f64 = 250.;
+
 impl<T> Default for TabData<T>
 where
     T: Clone,
 {
     fn default() -> Self {
         Self {
-            select_all_active: true,
             entries: im::Vector::default(),
+            term_column_width: DEFAULT_TERM_COLUMN_WIDTH,
+            expanded: im::HashSet::default(),
         }
     }
 }
@@ -79,31 +104,77 @@ where
 {
     fn from(m: im::Vector<ModificationEntry<T>>) -> Self {
         Self {
-            select_all_active: true,
             entries: m,
+            term_column_width: DEFAULT_TERM_COLUMN_WIDTH,
+            expanded: im::HashSet::default(),
+        }
+    }
+}
+
+/// State of a running update as shown in the progress popup.
+#[derive(Data, Lens, Debug, Clone, Default)]
+struct ProgressState {
+    fraction: f64,
+    /// Description of the item that is currently uploaded.
+    current: String,
+    /// Descriptions of the finished items, newest first.
+    log: im::Vector<String>,
+    /// Shared with the update thread, which stops once this is set.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ProgressState {
+    fn advance(&mut self, progress: &Progress) {
+        self.fraction = progress.fraction();
+        match progress.step {
+            Step::Started => self.current = (strings::get().progress)(progress),
+            Step::Succeeded | Step::Failed => {
+                self.log.push_front((strings::get().progress)(progress))
+            }
         }
     }
 }
 
 #[derive(Data, Debug, Clone)]
 enum Popup {
-    Progressing(f64),
+    Confirming,
+    Progressing(ProgressState),
+    Reauthenticating(ProgressState),
     Finished(Arc<UpdateResult>),
 }
 
+#[derive(Data, Debug, Clone, Copy, PartialEq, Eq)]
+enum PopupKind {
+    Confirming,
+    Progressing,
+    Reauthenticating,
+    Finished,
+}
+
 impl Popup {
-    fn as_progressing(&self) -> Option<f64> {
+    fn kind(&self) -> PopupKind {
+        match self {
+            Self::Confirming => PopupKind::Confirming,
+            Self::Progressing(_) => PopupKind::Progressing,
+            Self::Reauthenticating(_) => PopupKind::Reauthenticating,
+            Self::Finished(_) => PopupKind::Finished,
+        }
+    }
+
+    fn as_progressing(&self) -> Option<&ProgressState> {
         match self {
-            Self::Progressing(v) => Some(*v),
+            Self::Progressing(v) | Self::Reauthenticating(v) => Some(v),
             _ => None,
         }
     }
 
-    /// Returns `true` if the popup is [`Finished`].
-    ///
-    /// [`Finished`]: Popup::Finished
-    fn is_finished(&self) -> bool {
-        matches!(self, Self::Finished(..))
+    /// Translations that failed in the finished update and can be retried.
+    fn retriable(&self) -> &[Translation] {
+        match self.as_finished().map(|r| r.as_ref()) {
+            Some(Err(UpdateError::Update(_, failed))) => failed,
+            Some(Err(UpdateError::Cancelled { remaining, .. })) => remaining,
+            _ => &[],
+        }
     }
 
     fn as_finished(&self) -> Option<&Arc<UpdateResult>> {
@@ -117,7 +188,53 @@ impl Popup {
 
 impl Default for Popup {
     fn default() -> Self {
-        Self::Progressing(0.0)
+        Self::Progressing(ProgressState::default())
+    }
+}
+
+/// Login data that is requested from the user when Traduora rejects
+/// the authentication during an update.
+#[derive(Data, Debug, Clone, Lens, Default)]
+pub struct LoginPrompt {
+    client_credentials: bool,
+    user: String,
+    secret: String,
+    reply: Option<Arc<mpsc::Sender<Option<LoginConfig>>>>,
+}
+
+impl LoginPrompt {
+    fn new(reply: mpsc::Sender<Option<LoginConfig>>, login: &LoginConfig) -> Self {
+        Self {
+            client_credentials: login.is_client(),
+            user: login.user().to_owned(),
+            secret: String::new(),
+            reply: Some(Arc::new(reply)),
+        }
+    }
+
+    fn login(&self) -> LoginConfig {
+        if self.client_credentials {
+            LoginConfig::ClientCredentials {
+                client_id: self.user.clone(),
+                client_secret: self.secret.clone(),
+            }
+        } else {
+            LoginConfig::Password {
+                mail: self.user.clone(),
+                password: self.secret.clone(),
+            }
+        }
+    }
+
+    /// Sends the entered login data (or `None` if the user gave up) to the waiting update thread.
+    fn answer(&mut self, accepted: bool) {
+        let login = accepted.then(|| self.login());
+        if let Some(reply) = self.reply.take() {
+            if reply.send(login).is_err() {
+                log::error!("Update thread stopped waiting for new login data.");
+            }
+        }
+        self.secret.clear();
     }
 }
 
@@ -126,10 +243,104 @@ pub struct AppState {
     pub added: TabData<Added>,
     pub removed: TabData<Removed>,
     pub updated: TabData<Updated>,
+    /// Terms that need no update. Only shown for reference.
+    pub unchanged: TabData<Unchanged>,
+    tab: TabKind,
     popup: Popup,
+    login_prompt: LoginPrompt,
+    offline: bool,
+    /// Translation files with uncommitted changes, empty if there are none.
+    uncommitted: String,
+    destination: String,
+    show_term_ids: bool,
+    grouped: bool,
+    filter: String,
+    only_warnings: bool,
+    /// Text typed to confirm that terms are deleted.
+    deletion_confirmation: String,
+    /// Outcome of the last action that does not open a popup, e.g. an export.
+    status: String,
+    reloading: bool,
+    #[data(same_fn = "PartialEq::eq")]
+    totals: Totals,
+    /// Set instead of the loaded data if the application failed to start.
+    startup: Option<Startup>,
+    /// Set instead of the loaded data while the setup wizard creates the config file.
+    wizard: Option<Wizard>,
+    /// Projects and locales on Traduora to switch to, shown in a separate window.
+    picker: Picker,
+    /// Configuration of the project and locale that is shown. Only missing if the startup failed.
+    #[lens(ignore)]
+    config: Option<Arc<AppConfig>>,
+}
+
+#[derive(Data, Debug, Clone, Copy, PartialEq, Eq)]
+enum TabKind {
+    Removed,
+    Added,
+    Updated,
+    Unchanged,
+}
+
+impl Default for TabKind {
+    fn default() -> Self {
+        Self::Removed
+    }
+}
+
+/// State of the screen that is shown if the application failed to start.
+#[derive(Data, Debug, Clone, Lens)]
+pub struct Startup {
+    error: AppStateError,
+    /// Configuration loaded before the failure. It cannot be replaced then.
+    config: Option<Arc<AppConfig>>,
+    config_file: Option<Arc<PathBuf>>,
+    retrying: bool,
+    /// Passphrase being entered if the configuration contains encrypted secrets.
+    passphrase: Option<String>,
+}
+
+impl Startup {
+    fn new(
+        error: anyhow::Error,
+        config: Option<Arc<AppConfig>>,
+        config_file: Option<Arc<PathBuf>>,
+    ) -> Self {
+        let locked = config
+            .as_deref()
+            .map_or(false, crate::secret::needs_passphrase);
+        Self {
+            error: error.into(),
+            config,
+            config_file,
+            retrying: false,
+            passphrase: locked.then(String::new),
+        }
+    }
 }
 
 impl AppState {
+    fn config(&self) -> &AppConfig {
+        self.config
+            .as_deref()
+            .expect("Application started successfully.")
+    }
+
+    /// The configuration, unless it could not be loaded at startup.
+    pub fn try_config(&self) -> Option<&AppConfig> {
+        self.config.as_deref()
+    }
+
+    /// Whether terms cannot be updated because Traduora is unreachable or must not be changed.
+    fn update_disabled(&self) -> bool {
+        self.offline || self.config().read_only()
+    }
+
+    /// Whether the startup screen or the setup wizard is shown instead of the loaded data.
+    fn starting(&self) -> bool {
+        self.startup.is_some() || self.wizard.is_some()
+    }
+
     fn extract_translations(&self) -> Vec<Translation> {
         fn extract<'a, T, I, F>(elements: I, construct: F) -> impl Iterator<Item = Translation> + 'a
         where
@@ -149,37 +360,142 @@ impl AppState {
             Translation::removed(term, translation, r.0)
         });
         let updated = extract(&self.updated.entries, |term, translation, u| {
-            Translation::updated(term, translation, u.0)
+            Translation::updated(term, translation, u.0, u.1)
         });
         added.chain(removed).chain(updated).collect()
     }
 
-    pub fn build(translations: impl IntoIterator<Item = Translation>) -> Self {
+    /// Deleting terms cannot be undone, so it must be confirmed by typing the keyword.
+    fn deletion_confirmed(&self, _: &Env) -> bool {
+        !self.removed.entries.iter().any(|e| e.active)
+            || self.deletion_confirmation.trim() == strings::get().deletion_keyword
+    }
+
+    fn active_tab(&mut self) -> &mut dyn Navigate {
+        match self.tab {
+            TabKind::Removed => &mut self.removed,
+            TabKind::Added => &mut self.added,
+            TabKind::Updated => &mut self.updated,
+            TabKind::Unchanged => &mut self.unchanged,
+        }
+    }
+
+    /// Replaces the loaded data but keeps the view settings and popups.
+    fn reload(&mut self, data: LoadedData) {
+        let mut state = Self::build(
+            self.config
+                .clone()
+                .expect("Application started successfully."),
+            data,
+        );
+        state.popup = std::mem::take(&mut self.popup);
+        state.picker = std::mem::take(&mut self.picker);
+        state.login_prompt = std::mem::take(&mut self.login_prompt);
+        state.show_term_ids = self.show_term_ids;
+        state.tab = self.tab;
+        state.filter = std::mem::take(&mut self.filter);
+        state.grouped = self.grouped;
+        state.only_warnings = self.only_warnings;
+        state.added.term_column_width = self.added.term_column_width;
+        state.removed.term_column_width = self.removed.term_column_width;
+        state.updated.term_column_width = self.updated.term_column_width;
+        state.unchanged.term_column_width = self.unchanged.term_column_width;
+        state.added.expanded = std::mem::take(&mut self.added.expanded);
+        state.removed.expanded = std::mem::take(&mut self.removed.expanded);
+        state.updated.expanded = std::mem::take(&mut self.updated.expanded);
+        state.unchanged.expanded = std::mem::take(&mut self.unchanged.expanded);
+        *self = state;
+    }
+
+    pub fn startup_failed(error: anyhow::Error, config: Option<Arc<AppConfig>>) -> Self {
+        Self {
+            startup: Some(Startup::new(error, config, None)),
+            ..Self::default()
+        }
+    }
+
+    /// Shows the setup wizard because there is no config file yet.
+    pub fn first_run() -> Self {
+        Self {
+            wizard: Some(Wizard::default()),
+            ..Self::default()
+        }
+    }
+
+    pub fn build(config: Arc<AppConfig>, data: LoadedData) -> Self {
         fn new<T: Clone>() -> im::Vector<ModificationEntry<T>> {
             im::Vector::<ModificationEntry<T>>::new()
         }
-        let (added, removed, updated) = translations.into_iter().fold(
+        let metadata = data.metadata;
+        let select = config.default_select();
+        let (added, removed, updated) = data.translations.into_iter().fold(
             (new::<Added>(), new::<Removed>(), new::<Updated>()),
             |(mut added, mut removed, mut updated), t| {
                 match t.modification {
                     Modification::Removed(id) => {
-                        removed.push_back(ModificationEntry::removed(t.term, t.translation, id));
+                        let metadata = metadata.get(&id);
+                        removed.push_back(
+                            ModificationEntry::removed(
+                                t.term,
+                                t.translation,
+                                id.clone(),
+                                select.removed,
+                            )
+                            .with_metadata(metadata),
+                        );
                     }
                     Modification::Added => {
-                        added.push_back(ModificationEntry::added(t.term, t.translation));
+                        added.push_back(
+                            ModificationEntry::added(t.term, t.translation, select.added)
+                                .with_icu_messages(config.icu_messages()),
+                        );
                     }
-                    Modification::Updated(id) => {
-                        updated.push_back(ModificationEntry::updated(t.term, t.translation, id));
+                    Modification::Updated(id, previous) => {
+                        let metadata = metadata.get(&id);
+                        updated.push_back(
+                            ModificationEntry::updated(
+                                t.term,
+                                t.translation,
+                                id.clone(),
+                                previous,
+                                select.updated,
+                            )
+                            .with_metadata(metadata)
+                            .with_icu_messages(config.icu_messages()),
+                        );
                     }
                 }
                 (added, removed, updated)
             },
         );
 
+        let unchanged: im::Vector<_> = data
+            .unchanged
+            .into_iter()
+            .map(|u| {
+                let metadata = metadata.get(&u.term_id);
+                ModificationEntry::unchanged(u.term, u.translation, u.term_id.clone())
+                    .with_metadata(metadata)
+            })
+            .collect();
+
         Self {
             added: added.into(),
             removed: removed.into(),
             updated: updated.into(),
+            unchanged: unchanged.into(),
+            offline: data.offline,
+            uncommitted: data
+                .uncommitted
+                .iter()
+                .map(|path| path.display().to_string())
+                .join(", "),
+            totals: data.totals,
+            destination: data
+                .destination
+                .unwrap_or_else(|| Destination::unresolved(&config))
+                .to_string(),
+            config: Some(config),
             ..Self::default()
         }
     }
@@ -191,54 +507,296 @@ pub struct ModificationEntry<T> {
     pub term: String,
     pub translation: String,
     pub modification: T,
+    /// Information about the term on Traduora, if it exists there.
+    pub metadata: Option<Arc<TermMetadata>>,
+    /// Whether the keyboard cursor is on this entry.
+    pub cursor: bool,
+    /// Whether the translation is checked for syntax errors of ICU MessageFormat.
+    icu_messages: bool,
+    /// Warnings about the translation, checked again whenever it is edited.
+    #[data(ignore)]
+    #[lens(ignore)]
+    warnings: Arc<Vec<Warning>>,
 }
 
 impl ModificationEntry<Updated> {
-    pub fn updated(term: String, translation: String, id: TermId) -> Self {
+    pub fn updated(
+        term: String,
+        translation: String,
+        id: TermId,
+        previous: String,
+        active: bool,
+    ) -> Self {
         Self {
-            active: true,
+            active,
             term,
-            modification: Updated(id),
+            modification: Updated(id, previous),
             translation,
+            metadata: None,
+            cursor: false,
+            icu_messages: false,
+            warnings: Arc::default(),
         }
+        .checked()
     }
 }
 
 impl ModificationEntry<Removed> {
-    pub fn removed(term: String, translation: String, id: TermId) -> Self {
+    pub fn removed(term: String, translation: String, id: TermId, active: bool) -> Self {
         Self {
-            active: true,
+            active,
             term,
             modification: Removed(id),
             translation,
+            metadata: None,
+            cursor: false,
+            icu_messages: false,
+            warnings: Arc::default(),
         }
     }
 }
 
 impl ModificationEntry<Added> {
-    pub fn added(term: String, translation: String) -> Self {
+    pub fn added(term: String, translation: String, active: bool) -> Self {
         Self {
-            active: true,
+            active,
             term,
             modification: Added,
             translation,
+            metadata: None,
+            cursor: false,
+            icu_messages: false,
+            warnings: Arc::default(),
+        }
+        .checked()
+    }
+}
+
+impl ModificationEntry<Unchanged> {
+    pub fn unchanged(term: String, translation: String, id: TermId) -> Self {
+        Self {
+            active: false,
+            term,
+            modification: Unchanged(id),
+            translation,
+            metadata: None,
+            cursor: false,
+            icu_messages: false,
+            warnings: Arc::default(),
+        }
+    }
+}
+
+impl<T> ModificationEntry<T> {
+    fn with_metadata(mut self, metadata: Option<&TermMetadata>) -> Self {
+        self.metadata = metadata.cloned().map(Arc::new);
+        self
+    }
+
+    /// Case-insensitive substring match on term or translation.
+    fn matches(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+        self.term.to_lowercase().contains(&filter)
+            || self.translation.to_lowercase().contains(&filter)
+    }
+}
+
+impl<T: EntryKind> ModificationEntry<T> {
+    fn with_icu_messages(mut self, icu_messages: bool) -> Self {
+        if self.icu_messages != icu_messages {
+            self.icu_messages = icu_messages;
+            self.check();
+        }
+        self
+    }
+
+    fn checked(mut self) -> Self {
+        self.check();
+        self
+    }
+
+    /// Looks for reasons why the translation that is uploaded is probably wrong.
+    fn check(&mut self) {
+        let mut warnings = Vec::new();
+        if T::EDITABLE {
+            warnings = crate::warnings::check(self.modification.previous(), &self.translation);
+            if self.icu_messages {
+                warnings.extend(crate::warnings::check_message(&self.translation));
+            }
+        }
+        self.warnings = Arc::new(warnings);
+    }
+
+    fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Whether the entry passes the filter and the warning filter from the environment.
+    fn is_shown(&self, env: &Env) -> bool {
+        self.passes(&env.get(FILTER), env.get(ONLY_WARNINGS))
+    }
+
+    fn passes(&self, filter: &str, only_warnings: bool) -> bool {
+        self.matches(filter) && !(only_warnings && self.warnings().is_empty())
+    }
+}
+
+/// Keyboard operations on the entries of a tab that pass the filter.
+trait Navigate {
+    /// Moves the keyboard cursor to the next or previous entry, wrapping around at the ends.
+    fn move_cursor(&mut self, forward: bool, filter: &str, only_warnings: bool);
+
+    fn toggle_at_cursor(&mut self);
+
+    /// Selects all entries unless all are selected already, in which case all are deselected.
+    fn toggle_all(&mut self, filter: &str, only_warnings: bool);
+}
+
+impl<T: Clone + EntryKind> Navigate for TabData<T> {
+    fn move_cursor(&mut self, forward: bool, filter: &str, only_warnings: bool) {
+        let shown: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| self.entries[i].passes(filter, only_warnings))
+            .collect();
+        let current = self.entries.iter().position(|e| e.cursor);
+        let next = match (current, forward) {
+            (Some(c), true) => shown.iter().find(|&&i| i > c).or_else(|| shown.first()),
+            (Some(c), false) => shown
+                .iter()
+                .rev()
+                .find(|&&i| i < c)
+                .or_else(|| shown.last()),
+            (None, true) => shown.first(),
+            (None, false) => shown.last(),
+        };
+        if let Some(&next) = next {
+            if let Some(c) = current {
+                self.entries[c].cursor = false;
+            }
+            self.entries[next].cursor = true;
+        }
+    }
+
+    fn toggle_at_cursor(&mut self) {
+        if !T::SELECTABLE {
+            return;
+        }
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.cursor) {
+            entry.active = !entry.active;
         }
     }
+
+    fn toggle_all(&mut self, filter: &str, only_warnings: bool) {
+        if !T::SELECTABLE {
+            return;
+        }
+        let shown = || {
+            self.entries
+                .iter()
+                .filter(|e| e.passes(filter, only_warnings))
+        };
+        let active = !shown().all(|e| e.active);
+        for entry in self
+            .entries
+            .iter_mut()
+            .filter(|e| e.passes(filter, only_warnings))
+        {
+            entry.active = active;
+        }
+    }
+}
+
+/// Failures of a finished update that belong to the same operation.
+#[derive(Clone, Debug, Data, Lens)]
+struct FailureGroup {
+    title: String,
+    failures: im::Vector<FailureDetails>,
+}
+
+#[derive(Clone, Debug, Data)]
+struct FailureDetails {
+    summary: String,
+    /// The error with all its causes.
+    details: String,
+}
+
+impl FailureGroup {
+    fn group(failures: &[Failure]) -> im::Vector<Self> {
+        [
+            Some(Operation::Create),
+            Some(Operation::Update),
+            Some(Operation::Delete),
+            None,
+        ]
+        .iter()
+        .filter_map(|&operation| {
+            let failures: im::Vector<_> = failures
+                .iter()
+                .filter(|f| f.operation == operation)
+                .map(|f| FailureDetails {
+                    summary: format!("{} ==> {}: {}", f.term, f.translation, f.error),
+                    details: format!("{:?}", f.error),
+                })
+                .collect();
+            (!failures.is_empty()).then(|| Self {
+                title: (strings::get().failed_terms)(operation, failures.len()),
+                failures,
+            })
+        })
+        .collect()
+    }
+}
+
+/// Properties that depend on the kind of modification.
+pub(crate) trait EntryKind {
+    /// Whether the translation can be edited before it is uploaded.
+    const EDITABLE: bool;
+
+    /// Whether the entry can be selected for the update.
+    const SELECTABLE: bool = true;
+
+    /// Id of the term on Traduora, if the term already exists there.
+    fn term_id(&self) -> Option<&TermId>;
+
+    /// Translation that is currently on Traduora, if it is replaced.
+    fn previous(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl EntryKind for Removed {
+    const EDITABLE: bool = false;
+
+    fn term_id(&self) -> Option<&TermId> {
+        Some(&self.0)
+    }
 }
 
-trait DisplayString {
-    fn display_string(&self) -> String;
+impl EntryKind for Updated {
+    const EDITABLE: bool = true;
+
+    fn term_id(&self) -> Option<&TermId> {
+        Some(&self.0)
+    }
+
+    fn previous(&self) -> Option<&str> {
+        Some(&self.1)
+    }
 }
 
-impl<T> DisplayString for ModificationEntry<T> {
-    fn display_string(&self) -> String {
-        format!("{} ==> {}", self.term, self.translation)
+impl EntryKind for Added {
+    const EDITABLE: bool = true;
+
+    fn term_id(&self) -> Option<&TermId> {
+        None
     }
 }
 
-impl DisplayString for (String, String, anyhow::Error) {
-    fn display_string(&self) -> String {
-        format!("{} ==> {}: {:?}", self.0, self.1, self.2)
+impl EntryKind for Unchanged {
+    const EDITABLE: bool = false;
+    const SELECTABLE: bool = false;
+
+    fn term_id(&self) -> Option<&TermId> {
+        Some(&self.0)
     }
 }
 
@@ -251,205 +809,1466 @@ impl Data for Removed {
     }
 }
 
+/// Id of the term and its current translation on Traduora.
 #[derive(Clone, Debug)]
-pub struct Updated(pub TermId);
+pub struct Updated(pub TermId, pub String);
 
 impl Data for Updated {
     fn same(&self, other: &Self) -> bool {
-        self.0 == other.0
+        self.0 == other.0 && self.1 == other.1
     }
 }
 
 #[derive(Clone, Debug, Data)]
 pub struct Added;
 
-struct OmniSelector;
+/// Id of a term that is identical locally and on Traduora.
+#[derive(Clone, Debug)]
+pub struct Unchanged(pub TermId);
 
-impl<T, W> Controller<TabData<T>, W> for OmniSelector
-where
-    T: Clone,
-    W: Widget<TabData<T>>,
-{
-    fn event(
-        &mut self,
-        child: &mut W,
-        ctx: &mut druid::EventCtx,
-        event: &druid::Event,
-        data: &mut TabData<T>,
-        env: &Env,
-    ) {
-        let old_value = data.select_all_active;
-        child.event(ctx, event, data, env);
-        if old_value == data.select_all_active {
-            return;
-        }
-        for entry in data.entries.iter_mut() {
-            entry.active = data.select_all_active;
-        }
+impl Data for Unchanged {
+    fn same(&self, other: &Self) -> bool {
+        self.0 == other.0
     }
 }
 
-fn build_item<T>() -> impl Widget<ModificationEntry<T>>
-where
-    T: druid::Data,
-    ModificationEntry<T>: DisplayString,
-{
-    Flex::row()
-        .with_child(Checkbox::new("").lens(ModificationEntry::<T>::active))
-        .with_child(Label::new(|item: &ModificationEntry<T>, _env: &_| {
-            item.display_string()
-        }))
-}
-
-fn build_list<T>() -> impl Widget<TabData<T>>
-where
-    T: druid::Data,
-    ModificationEntry<T>: DisplayString,
-{
-    Flex::column()
-        .with_child(
-            Checkbox::new(|is_active: &bool, _env: &_| {
-                if *is_active {
-                    "Deselect all"
-                } else {
-                    "Select all"
-                }
-                .into()
-            })
-            .lens(TabData::<T>::select_all_active)
-            .controller(OmniSelector)
-            .align_left(),
-        )
-        .with_default_spacer()
-        .with_flex_child(
-            Scroll::new(List::new(build_item).with_spacing(5.))
-                .vertical()
-                .expand_width()
-                .lens(TabData::<T>::entries),
-            1.,
-        )
+/// Entries whose terms share the namespace, i.e. everything before the last dot.
+#[derive(Data, Debug, Clone, Lens)]
+pub struct Group<T: Clone> {
+    namespace: String,
+    expanded: bool,
+    entries: im::Vector<ModificationEntry<T>>,
+    /// Positions of the entries in [`TabData::entries`].
+    #[data(ignore)]
+    indices: Arc<[usize]>,
 }
 
-pub fn build_ui() -> impl Widget<AppState> {
-    let main_view = Flex::column()
-        .with_flex_child(
-            Tabs::new()
-                .with_transition(TabsTransition::Instant)
-                .with_tab("Removed", build_list().lens(AppState::removed))
-                .with_tab("Added", build_list().lens(AppState::added))
-                .with_tab("Updated", build_list().lens(AppState::updated)),
-            10.,
-        )
-        .with_child(Button::new("Update terms").padding(10.).on_click(
-            |ctx, data: &mut AppState, _env| {
-                data.popup = Popup::default();
-                let cmd = ModalHost::make_modal_command(build_popup);
-                ctx.submit_command(cmd);
-                wrapped_run(ctx.get_external_handle(), data);
-            },
-        ));
+impl<T: Clone> Group<T> {
+    fn all_active(&self) -> bool {
+        self.entries.iter().all(|e| e.active)
+    }
 
-    ModalHost::new(main_view)
+    fn set_all_active(&mut self, active: bool) {
+        if self.all_active() != active {
+            for entry in self.entries.iter_mut() {
+                entry.active = active;
+            }
+        }
+    }
 }
 
-fn build_popup() -> impl Widget<AppState> {
-    let progressing = Flex::column()
-        .with_child(Label::new("Uploading terms."))
-        .with_default_spacer()
-        .with_child(Spinner::new())
-        .with_default_spacer()
-        .with_child(ProgressBar::new())
-        .padding(16.0)
-        .background(theme::BACKGROUND_DARK)
-        .lens(AppState::popup.read_only(|p: &Popup| p.as_progressing().unwrap_or(0.)));
+fn namespace(term: &str) -> &str {
+    term.rsplit_once('.').map_or("", |(namespace, _)| namespace)
+}
 
-    let finished = Flex::column()
-        .with_child(Label::new("Finished uploading terms."))
-        .with_default_spacer()
-        .with_flex_child(
-            Scroll::new(
-                Label::new(|data: &Arc<UpdateResult>, _: &_| match data.as_ref() {
-                    Ok(_) => "No error occurred.".into(),
-                    Err(UpdateError::ClientCreation(e)) => format!("{}", e),
-                    Err(UpdateError::Update(errs)) => {
-                        errs.iter().map(DisplayString::display_string).join("\n")
-                    }
-                })
-                .with_line_break_mode(LineBreaking::WordWrap),
-            ),
-            1.,
-        )
-        .with_default_spacer()
-        .with_child(Button::new("Ok").on_click(|ctx, _, _| {
-            ctx.submit_command(ModalHost::DISMISS_MODAL);
-        }))
-        .padding(16.0)
-        .background(theme::BACKGROUND_DARK)
-        .lens(
-            AppState::popup
-                .read_only(|p: &Popup| p.as_finished().cloned().unwrap_or_else(|| Ok(()).into())),
-        );
+/// Row of the grouped view, either the header of a group or one of its entries.
+#[derive(Data, Debug, Clone, Lens)]
+struct Row<T: Clone> {
+    group: Group<T>,
+    /// Position of the entry in the group, `None` for the header.
+    entry: Option<usize>,
+}
 
-    Either::new(
-        |data: &AppState, _| data.popup.is_finished(),
-        finished,
-        progressing,
-    )
+impl<T: Clone + EntryKind> Row<T> {
+    fn is_shown(&self, env: &Env) -> bool {
+        match self.entry {
+            None => self.group.entries.iter().any(|e| e.is_shown(env)),
+            Some(i) => self.group.expanded && self.group.entries[i].is_shown(env),
+        }
+    }
 }
 
-fn wrapped_run(sink: ExtEventSink, data: &AppState) {
-    let translations = data.extract_translations();
+/// Lens from a row to its entry. Hidden widgets may still ask a header for it, which then leads
+/// to the first entry of the group.
+struct RowEntry;
 
-    std::thread::spawn(move || {
-        let result = crate::updater::run(translations, |current, max| {
-            let current = current as f64;
-            let max = max.max(1) as f64;
-            let percentage = current / max;
-            log::debug!("Sending update progress command: {} of {}", current, max);
-            sink.submit_command(UPDATE_PROGRESS, percentage, Target::Auto)
-                .expect("Failed to submit update progress command.");
-        });
-        log::info!("Sending finished update command: {:#?}", result);
-        sink.submit_command(UPDATE_FINISHED, SingleUse::new(result), Target::Auto)
-            .expect("Failed to submit update finished command.");
-    });
+impl<T: Clone> Lens<Row<T>, ModificationEntry<T>> for RowEntry {
+    fn with<V, F: FnOnce(&ModificationEntry<T>) -> V>(&self, row: &Row<T>, f: F) -> V {
+        f(&row.group.entries[row.entry.unwrap_or(0)])
+    }
+
+    fn with_mut<V, F: FnOnce(&mut ModificationEntry<T>) -> V>(&self, row: &mut Row<T>, f: F) -> V {
+        f(&mut row.group.entries[row.entry.unwrap_or(0)])
+    }
 }
 
-const UPDATE_PROGRESS: Selector<f64> =
-    Selector::new("me.erik-hennig.traduora-update.update-progress");
+/// Rows of the grouped view and the state of the tab they were built from.
+struct GroupedRows<T: Clone> {
+    entries: im::Vector<ModificationEntry<T>>,
+    expanded: im::HashSet<String>,
+    rows: im::Vector<Row<T>>,
+}
 
-const UPDATE_FINISHED: Selector<SingleUse<UpdateResult>> =
-    Selector::new("me.erik-hennig.traduora-update.update-finished");
+/// Lens that groups the entries of a tab by namespace into the rows of the grouped view and
+/// writes changes back to the entries. The rows are only built again if the entries were changed
+/// elsewhere, e.g. by selecting all of them.
+struct Grouped<T: Clone> {
+    cache: RefCell<Option<GroupedRows<T>>>,
+}
 
-pub struct Delegate;
+impl<T: Clone> Grouped<T> {
+    fn new() -> Self {
+        Self {
+            cache: RefCell::new(None),
+        }
+    }
 
-impl AppDelegate<AppState> for Delegate {
-    fn command(
-        &mut self,
-        _: &mut druid::DelegateCtx,
-        _: Target,
-        cmd: &druid::Command,
-        data: &mut AppState,
-        _: &Env,
-    ) -> druid::Handled {
-        log::debug!("Received command {:?}.", cmd);
-        if let Some(progress) = cmd.get(UPDATE_PROGRESS) {
-            data.popup = Popup::Progressing(*progress);
-            druid::Handled::Yes
-        } else if let Some(result) = cmd.get(UPDATE_FINISHED).and_then(SingleUse::take) {
-            let load_result = crate::loader::load_data();
-            log::info!(
-                "Finished refreshing data. Error (if any): {:?}.",
-                load_result.as_ref().err()
-            );
-            *data = AppState::build(load_result.unwrap_or_default());
-            data.popup = Popup::Finished(result.into());
-            druid::Handled::Yes
-        } else {
-            druid::Handled::No
+    fn groups(data: &TabData<T>) -> im::Vector<Group<T>> {
+        let mut indices = std::collections::BTreeMap::<&str, Vec<usize>>::new();
+        for (i, entry) in data.entries.iter().enumerate() {
+            indices.entry(namespace(&entry.term)).or_default().push(i);
         }
+        indices
+            .into_iter()
+            .map(|(namespace, indices)| Group {
+                namespace: namespace.to_owned(),
+                expanded: data.expanded.contains(namespace),
+                entries: indices.iter().map(|&i| data.entries[i].clone()).collect(),
+                indices: indices.into(),
+            })
+            .collect()
     }
-}
+
+    /// The rows of the tab, which are taken from the cache unless the tab changed.
+    fn rows(&self, data: &TabData<T>) -> im::Vector<Row<T>> {
+        let mut cache = self.cache.borrow_mut();
+        if let Some(cached) = &*cache {
+            if cached.entries.ptr_eq(&data.entries) && cached.expanded == data.expanded {
+                return cached.rows.clone();
+            }
+        }
+        let mut rows = im::Vector::new();
+        for group in Self::groups(data) {
+            rows.push_back(Row {
+                group: group.clone(),
+                entry: None,
+            });
+            for i in 0..group.entries.len() {
+                rows.push_back(Row {
+                    group: group.clone(),
+                    entry: Some(i),
+                });
+            }
+        }
+        *cache = Some(GroupedRows {
+            entries: data.entries.clone(),
+            expanded: data.expanded.clone(),
+            rows: rows.clone(),
+        });
+        rows
+    }
+}
+
+impl<T: Data> Lens<TabData<T>, im::Vector<Row<T>>> for Grouped<T> {
+    fn with<V, F: FnOnce(&im::Vector<Row<T>>) -> V>(&self, data: &TabData<T>, f: F) -> V {
+        f(&self.rows(data))
+    }
+
+    fn with_mut<V, F: FnOnce(&mut im::Vector<Row<T>>) -> V>(
+        &self,
+        data: &mut TabData<T>,
+        f: F,
+    ) -> V {
+        let before = self.rows(data);
+        let mut rows = before.clone();
+        let result = f(&mut rows);
+        if rows.ptr_eq(&before) {
+            return result;
+        }
+
+        // Positions of the headers of the groups that changed.
+        let mut changed = Vec::new();
+        for (i, (row, old)) in rows.iter().zip(before.iter()).enumerate() {
+            if row.same(old) {
+                continue;
+            }
+            let (group, old) = (&row.group, &old.group);
+            if group.expanded && !old.expanded {
+                data.expanded.insert(group.namespace.clone());
+            } else if !group.expanded && old.expanded {
+                data.expanded.remove(&group.namespace);
+            }
+            for ((&index, entry), old) in group.indices.iter().zip(&group.entries).zip(&old.entries)
+            {
+                if !entry.same(old) {
+                    data.entries.set(index, entry.clone());
+                }
+            }
+            let header = i - row.entry.map_or(0, |j| j + 1);
+            if changed.last() != Some(&header) {
+                changed.push(header);
+            }
+        }
+
+        // Every row holds its group, so all rows of a changed group get the new one.
+        for header in changed {
+            let old = &rows[header].group;
+            let group = Group {
+                namespace: old.namespace.clone(),
+                expanded: data.expanded.contains(&old.namespace),
+                entries: old
+                    .indices
+                    .iter()
+                    .map(|&i| data.entries[i].clone())
+                    .collect(),
+                indices: old.indices.clone(),
+            };
+            for i in header..=header + group.entries.len() {
+                rows[i].group = group.clone();
+            }
+        }
+        *self.cache.borrow_mut() = Some(GroupedRows {
+            entries: data.entries.clone(),
+            expanded: data.expanded.clone(),
+            rows,
+        });
+        result
+    }
+}
+
+/// Checkbox that shows and sets the selection of all entries matching the filter.
+///
+/// While a filter is active, the label names the number of matching entries so it is
+/// clear that only those are changed.
+struct SelectAll {
+    checkbox: TriStateCheckbox,
+    /// Selection of the matching entries as of the last update.
+    selection: Selection,
+    /// Number of matching entries as of the last update.
+    matching: usize,
+}
+
+/// Number of entries that match the filter, passed to the label of [`SelectAll`].
+const MATCHING_ENTRIES: Key<u64> = Key::new("me.erik-hennig.traduora-update.matching-entries");
+
+impl SelectAll {
+    fn new() -> Self {
+        Self {
+            checkbox: TriStateCheckbox::new(|selection: &Selection, env: &Env| {
+                let strings = strings::get();
+                let matching = env.get(MATCHING_ENTRIES) as usize;
+                let unfiltered = env.get(FILTER).is_empty() && !env.get(ONLY_WARNINGS);
+                match (unfiltered, selection) {
+                    (true, Selection::All) => strings.deselect_all.into(),
+                    (true, Selection::None | Selection::Some) => strings.select_all.into(),
+                    (false, Selection::All) => (strings.deselect_matching)(matching),
+                    (false, Selection::None | Selection::Some) => {
+                        (strings.select_matching)(matching)
+                    }
+                }
+            }),
+            selection: Selection::None,
+            matching: 0,
+        }
+    }
+
+    fn refresh<T: Clone + EntryKind>(&mut self, data: &TabData<T>, env: &Env) {
+        let matching: Vec<bool> = data
+            .entries
+            .iter()
+            .filter(|e| e.is_shown(env))
+            .map(|e| e.active)
+            .collect();
+        self.matching = matching.len();
+        self.selection = Selection::of(matching.into_iter());
+    }
+
+    fn env(&self, env: &Env) -> Env {
+        env.clone().adding(MATCHING_ENTRIES, self.matching as u64)
+    }
+}
+
+impl<T: Clone + EntryKind> Widget<TabData<T>> for SelectAll {
+    fn event(
+        &mut self,
+        ctx: &mut druid::EventCtx,
+        event: &druid::Event,
+        data: &mut TabData<T>,
+        env: &Env,
+    ) {
+        let mut selection = self.selection;
+        self.checkbox
+            .event(ctx, event, &mut selection, &self.env(env));
+        if selection == self.selection {
+            return;
+        }
+        let active = selection == Selection::All;
+        for entry in data.entries.iter_mut().filter(|e| e.is_shown(env)) {
+            entry.active = active;
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut druid::LifeCycleCtx,
+        event: &druid::LifeCycle,
+        data: &TabData<T>,
+        env: &Env,
+    ) {
+        if let druid::LifeCycle::WidgetAdded = event {
+            self.refresh(data, env);
+        }
+        self.checkbox
+            .lifecycle(ctx, event, &self.selection, &self.env(env));
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut druid::UpdateCtx,
+        _old_data: &TabData<T>,
+        data: &TabData<T>,
+        env: &Env,
+    ) {
+        let old = self.selection;
+        self.refresh(data, env);
+        self.checkbox
+            .update(ctx, &old, &self.selection, &self.env(env));
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut druid::LayoutCtx,
+        bc: &druid::BoxConstraints,
+        _data: &TabData<T>,
+        env: &Env,
+    ) -> druid::Size {
+        self.checkbox
+            .layout(ctx, bc, &self.selection, &self.env(env))
+    }
+
+    fn paint(&mut self, ctx: &mut druid::PaintCtx, _data: &TabData<T>, env: &Env) {
+        self.checkbox.paint(ctx, &self.selection, &self.env(env));
+    }
+}
+
+/// Whether the term ids should be shown next to the terms.
+const SHOW_TERM_IDS: Key<bool> = Key::new("me.erik-hennig.traduora-update.show-term-ids");
+
+/// Whether the entries are grouped by namespace.
+const GROUP_BY_NAMESPACE: Key<bool> = Key::new("me.erik-hennig.traduora-update.group-by-namespace");
+
+/// Width of the term column, adjusted by dragging the divider in the header.
+const TERM_COLUMN_WIDTH: Key<f64> = Key::new("me.erik-hennig.traduora-update.term-column-width");
+
+/// Width of the checkbox column. Checkboxes grow with the theme and the configured ui scale.
+fn checkbox_column_width(config: &AppConfig) -> f64 {
+    (crate::palette::checkbox_size(config.theme()) + 12.) * config.ui_scale()
+}
+
+/// Only entries whose term or translation contains this text are shown.
+const FILTER: Key<ArcStr> = Key::new("me.erik-hennig.traduora-update.filter");
+
+/// Whether only entries with warnings are shown.
+const ONLY_WARNINGS: Key<bool> = Key::new("me.erik-hennig.traduora-update.only-warnings");
+
+/// Term to look up in the web interface of Traduora.
+const OPEN_IN_TRADUORA: Selector<String> =
+    Selector::new("me.erik-hennig.traduora-update.open-in-traduora");
+
+fn build_traduora_link<T>() -> impl Widget<ModificationEntry<T>>
+where
+    T: druid::Data + EntryKind,
+{
+    let link = Button::new("↗").on_click(|ctx, item: &mut ModificationEntry<T>, _| {
+        ctx.submit_command(OPEN_IN_TRADUORA.with(item.term.clone()));
+    });
+    let link = Tooltip::new(link, |_: &ModificationEntry<T>, _: &_| {
+        Some(strings::get().open_in_traduora.to_string())
+    });
+    Either::new(
+        |item: &ModificationEntry<T>, _: &_| item.modification.term_id().is_some(),
+        link.padding((5., 0., 0., 0.)),
+        SizedBox::empty(),
+    )
+}
+
+fn build_warning_badge<T>() -> impl Widget<ModificationEntry<T>>
+where
+    T: druid::Data + EntryKind,
+{
+    let badge = Label::new("⚠").with_text_color(druid::Color::rgb8(0xff, 0xc1, 0x07));
+    let badge = Tooltip::new(badge, |item: &ModificationEntry<T>, _: &_| {
+        Some(
+            item.warnings()
+                .iter()
+                .map(strings::get().warning)
+                .join("\n"),
+        )
+    });
+    Either::new(
+        |item: &ModificationEntry<T>, _: &_| !item.warnings().is_empty(),
+        badge.padding((5., 0., 0., 0.)),
+        SizedBox::empty(),
+    )
+}
+
+/// Lens to the translation of an entry that checks it for warnings again when it is edited.
+struct CheckedTranslation;
+
+impl<T: EntryKind> Lens<ModificationEntry<T>, String> for CheckedTranslation {
+    fn with<V, F: FnOnce(&String) -> V>(&self, data: &ModificationEntry<T>, f: F) -> V {
+        f(&data.translation)
+    }
+
+    fn with_mut<V, F: FnOnce(&mut String) -> V>(&self, data: &mut ModificationEntry<T>, f: F) -> V {
+        let before = data.translation.clone();
+        let result = f(&mut data.translation);
+        if data.translation != before {
+            data.check();
+        }
+        result
+    }
+}
+
+fn build_item<T>(checkbox_width: f64) -> impl Widget<ModificationEntry<T>>
+where
+    T: druid::Data + EntryKind,
+{
+    let term =
+        Label::new(
+            |item: &ModificationEntry<T>, env: &Env| match item.modification.term_id() {
+                Some(id) if env.get(SHOW_TERM_IDS) => format!("[{}] {}", id, item.term),
+                _ => item.term.clone(),
+            },
+        )
+        .with_line_break_mode(LineBreaking::WordWrap);
+    let term = Tooltip::new(term, |item: &ModificationEntry<T>, _: &_| {
+        let id = item.modification.term_id()?;
+        let metadata = item.metadata.as_deref().cloned().unwrap_or_default();
+        Some((strings::get().term_metadata)(id, &metadata))
+    });
+
+    let translation = if T::EDITABLE {
+        TextBox::new()
+            .expand_width()
+            .lens(CheckedTranslation)
+            .boxed()
+    } else {
+        Label::new(|item: &ModificationEntry<T>, _env: &_| item.translation.clone())
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .boxed()
+    };
+
+    let previous = Either::new(
+        |item: &ModificationEntry<T>, _: &_| item.modification.previous().is_some(),
+        Flex::row()
+            .cross_axis_alignment(CrossAxisAlignment::Start)
+            .with_child(
+                Label::new(strings::get().changes_to_traduora)
+                    .with_text_color(theme::PLACEHOLDER_COLOR),
+            )
+            .with_spacer(5.)
+            .with_flex_child(
+                RawLabel::new()
+                    .with_line_break_mode(LineBreaking::WordWrap)
+                    .with_text_color(theme::PLACEHOLDER_COLOR)
+                    .lens(
+                        druid::lens::Identity.read_only(|item: &ModificationEntry<T>| {
+                            crate::diff::highlight(
+                                item.modification.previous().unwrap_or_default(),
+                                &item.translation,
+                            )
+                        }),
+                    )
+                    .expand_width(),
+                1.,
+            ),
+        SizedBox::empty(),
+    );
+
+    let checkbox = if T::SELECTABLE {
+        checkbox("")
+            .lens(ModificationEntry::<T>::active)
+            .fix_width(checkbox_width)
+            .boxed()
+    } else {
+        SizedBox::empty().width(checkbox_width).boxed()
+    };
+
+    let row = Flex::row()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(checkbox)
+        .with_child(Column::new(TERM_COLUMN_WIDTH, term))
+        .with_spacer(Divider::WIDTH)
+        .with_flex_child(
+            Flex::column()
+                .cross_axis_alignment(CrossAxisAlignment::Start)
+                .with_child(translation)
+                .with_child(previous),
+            1.,
+        )
+        .with_child(build_warning_badge())
+        .with_child(build_traduora_link())
+        .padding((0., 2.5))
+        .background(Painter::new(|ctx, item: &ModificationEntry<T>, env| {
+            if item.cursor {
+                let rect = ctx.size().to_rect().inset(-1.);
+                ctx.stroke(rect, &env.get(theme::PRIMARY_LIGHT), 1.);
+            }
+        }));
+
+    Either::new(
+        |item: &ModificationEntry<T>, env: &Env| item.is_shown(env),
+        row,
+        SizedBox::empty(),
+    )
+}
+
+fn build_group_header<T>() -> impl Widget<Group<T>>
+where
+    T: druid::Data + EntryKind,
+{
+    let checkbox = if T::SELECTABLE {
+        checkbox("")
+            .lens(druid::lens::Map::new(
+                Group::<T>::all_active,
+                Group::<T>::set_all_active,
+            ))
+            .boxed()
+    } else {
+        SizedBox::empty().boxed()
+    };
+
+    let header = Flex::row()
+        .with_child(
+            Button::new(|group: &Group<T>, _: &_| {
+                if group.expanded { "▾" } else { "▸" }.to_string()
+            })
+            .on_click(|_, group: &mut Group<T>, _| group.expanded = !group.expanded),
+        )
+        .with_child(checkbox)
+        .with_child(Label::new(|group: &Group<T>, _: &_| {
+            let namespace = match group.namespace.as_str() {
+                "" => strings::get().no_namespace,
+                namespace => namespace,
+            };
+            format!("{} ({})", namespace, group.entries.len())
+        }));
+
+    header
+}
+
+fn build_row<T>(checkbox_width: f64) -> impl Widget<Row<T>>
+where
+    T: druid::Data + EntryKind,
+{
+    Either::new(
+        |row: &Row<T>, _: &_| row.entry.is_none(),
+        build_group_header().lens(Row::<T>::group),
+        build_item(checkbox_width)
+            .padding((20., 0., 0., 0.))
+            .lens(RowEntry),
+    )
+}
+
+fn build_list<T>(checkbox_width: f64) -> impl Widget<TabData<T>>
+where
+    T: druid::Data + EntryKind,
+{
+    let select_all = if T::SELECTABLE {
+        SelectAll::new().align_left().boxed()
+    } else {
+        SizedBox::empty().boxed()
+    };
+
+    Flex::column()
+        .with_child(select_all)
+        .with_default_spacer()
+        .with_child(
+            Flex::row()
+                .with_spacer(checkbox_width)
+                .with_child(Column::new(
+                    TERM_COLUMN_WIDTH,
+                    Label::new(strings::get().term),
+                ))
+                .with_child(Divider::default().lens(TabData::<T>::term_column_width))
+                .with_child(Label::new(strings::get().translation)),
+        )
+        .with_flex_child(
+            Either::new(
+                |_: &TabData<T>, env: &Env| env.get(GROUP_BY_NAMESPACE),
+                Scroll::new(
+                    VirtualList::new(move || build_row(checkbox_width))
+                        .with_filter(|row: &Row<T>, env: &Env| row.is_shown(env)),
+                )
+                .vertical()
+                .expand_width()
+                .lens(Grouped::new()),
+                Scroll::new(
+                    VirtualList::new(move || build_item(checkbox_width))
+                        .with_filter(|item: &ModificationEntry<T>, env: &Env| item.is_shown(env)),
+                )
+                .vertical()
+                .expand_width()
+                .lens(TabData::<T>::entries),
+            ),
+            1.,
+        )
+        .env_scope(|env, data: &TabData<T>| env.set(TERM_COLUMN_WIDTH, data.term_column_width))
+}
+
+fn build_offline_banner() -> impl Widget<AppState> {
+    Either::new(
+        |data: &AppState, _| data.offline,
+        Label::new(strings::get().offline_banner)
+            .with_text_color(druid::Color::rgb8(0xff, 0xc1, 0x07))
+            .padding(5.)
+            .expand_width(),
+        Flex::column(),
+    )
+}
+
+fn build_uncommitted_banner() -> impl Widget<AppState> {
+    Either::new(
+        |data: &AppState, _| !data.uncommitted.is_empty(),
+        Label::new(|data: &AppState, _: &_| (strings::get().uncommitted_banner)(&data.uncommitted))
+            .with_text_color(druid::Color::rgb8(0xff, 0xc1, 0x07))
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .padding(5.)
+            .expand_width(),
+        Flex::column(),
+    )
+}
+
+fn build_statistics() -> impl Widget<AppState> {
+    Label::new(|data: &AppState, _: &_| {
+        fn count<T: Clone>(tab: &TabData<T>) -> (usize, usize) {
+            let selected = tab.entries.iter().filter(|e| e.active).count();
+            (selected, tab.entries.len())
+        }
+        (strings::get().statistics)(
+            count(&data.removed),
+            count(&data.added),
+            count(&data.updated),
+            &data.totals,
+        )
+    })
+    .with_line_break_mode(LineBreaking::WordWrap)
+    .padding((5., 2.))
+    .expand_width()
+}
+
+fn build_status_bar() -> impl Widget<AppState> {
+    // Computed on every change because the locale can be switched in the menu.
+    let text = |data: &AppState, _: &Env| {
+        let config = data.config();
+        let revision = match config.revision() {
+            "" => strings::get().no_revision,
+            revision => revision,
+        };
+        (strings::get().status_bar)(
+            &config.server_url(),
+            &config.project_id().to_string(),
+            &config.locale().to_string(),
+            &config.translation_file().display().to_string(),
+            revision,
+        )
+    };
+
+    Label::new(text)
+        .with_line_break_mode(LineBreaking::Clip)
+        .padding((5., 2.))
+        .expand_width()
+        .background(theme::BACKGROUND_DARK)
+}
+
+fn open_confirmation(ctx: &mut EventCtx, data: &mut AppState) {
+    data.popup = Popup::Confirming;
+    data.deletion_confirmation.clear();
+    ctx.submit_command(ModalHost::make_modal_command(build_popup));
+}
+
+/// The filter text box, so it can be focused with a shortcut.
+const FILTER_BOX: WidgetId = WidgetId::reserved(1);
+
+/// Keyboard shortcuts of the main view.
+///
+/// The main view takes the keyboard focus unless a text box has it. Escape returns the
+/// focus from a text box to the main view.
+struct Shortcuts;
+
+impl<W: Widget<AppState>> Controller<AppState, W> for Shortcuts {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut AppState,
+        env: &Env,
+    ) {
+        let key = match event {
+            Event::WindowConnected => {
+                ctx.request_focus();
+                return child.event(ctx, event, data, env);
+            }
+            Event::KeyDown(key) => key,
+            _ => return child.event(ctx, event, data, env),
+        };
+
+        if HotKey::new(SysMods::Cmd, "f").matches(key) {
+            ctx.set_focus(FILTER_BOX);
+        } else if HotKey::new(SysMods::Cmd, KbKey::Enter).matches(key) {
+            if !data.update_disabled() {
+                open_confirmation(ctx, data);
+            }
+        } else if !ctx.is_focused() {
+            if HotKey::new(None, KbKey::Escape).matches(key) {
+                ctx.request_focus();
+            } else {
+                return child.event(ctx, event, data, env);
+            }
+        } else {
+            let (filter, only_warnings) = (data.filter.clone(), data.only_warnings);
+            let tab = data.active_tab();
+            if HotKey::new(SysMods::Cmd, "a").matches(key) {
+                tab.toggle_all(&filter, only_warnings);
+            } else if HotKey::new(None, " ").matches(key) {
+                tab.toggle_at_cursor();
+            } else if HotKey::new(None, KbKey::Tab).matches(key)
+                || HotKey::new(None, KbKey::ArrowDown).matches(key)
+            {
+                tab.move_cursor(true, &filter, only_warnings);
+            } else if HotKey::new(SysMods::Shift, KbKey::Tab).matches(key)
+                || HotKey::new(None, KbKey::ArrowUp).matches(key)
+            {
+                tab.move_cursor(false, &filter, only_warnings);
+            } else {
+                return child.event(ctx, event, data, env);
+            }
+        }
+        ctx.set_handled();
+    }
+
+    fn lifecycle(
+        &mut self,
+        child: &mut W,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &AppState,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            ctx.register_for_focus();
+        }
+        child.lifecycle(ctx, event, data, env);
+    }
+}
+
+fn build_tab_bar() -> impl Widget<AppState> {
+    let tab = |kind: TabKind, title: &'static str| {
+        Label::new(title)
+            .padding((10., 5.))
+            .background(Painter::new(move |ctx, data: &AppState, env| {
+                if data.tab == kind {
+                    let rect = ctx.size().to_rect();
+                    ctx.fill(rect, &env.get(theme::BACKGROUND_LIGHT));
+                }
+            }))
+            .on_click(move |_, data: &mut AppState, _| data.tab = kind)
+    };
+    Flex::row()
+        .with_child(tab(TabKind::Removed, strings::get().removed))
+        .with_child(tab(TabKind::Added, strings::get().added))
+        .with_child(tab(TabKind::Updated, strings::get().updated))
+        .with_child(tab(TabKind::Unchanged, strings::get().unchanged))
+}
+
+fn build_tabs(checkbox_width: f64) -> impl Widget<AppState> {
+    // Hidden tabs are never painted, so each list is only built once its tab is opened.
+    let tab = |kind: TabKind, body: Box<dyn Widget<AppState>>, other: Box<dyn Widget<AppState>>| {
+        Either::new(move |data: &AppState, _: &_| data.tab == kind, body, other).boxed()
+    };
+    let body = tab(
+        TabKind::Removed,
+        Lazy::new(move || build_list(checkbox_width).lens(AppState::removed)).boxed(),
+        tab(
+            TabKind::Added,
+            Lazy::new(move || build_list(checkbox_width).lens(AppState::added)).boxed(),
+            tab(
+                TabKind::Updated,
+                Lazy::new(move || build_list(checkbox_width).lens(AppState::updated)).boxed(),
+                Lazy::new(move || build_list(checkbox_width).lens(AppState::unchanged)).boxed(),
+            ),
+        ),
+    );
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(build_tab_bar())
+        .with_flex_child(body.padding(5.).border(theme::BORDER_DARK, 0.5), 1.)
+}
+
+pub fn build_ui(config: &AppConfig) -> impl Widget<AppState> {
+    let update_button = Button::new(strings::get().update_terms)
+        .padding(10.)
+        .on_click(|ctx, data: &mut AppState, _env| open_confirmation(ctx, data));
+
+    let reload_button = Either::new(
+        |data: &AppState, _| data.reloading,
+        Label::new(strings::get().reloading).padding(10.),
+        Button::new(strings::get().reload)
+            .padding(10.)
+            .on_click(|ctx, data: &mut AppState, _| start_reload(ctx.get_external_handle(), data)),
+    );
+
+    let export_button = Button::new(strings::get().export)
+        .padding(10.)
+        .on_click(|ctx, _, _| {
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![
+                    FileSpec::new("CSV", &["csv"]),
+                    FileSpec::new("JSON", &["json"]),
+                ])
+                .default_type(FileSpec::new("CSV", &["csv"]))
+                .default_name("pending-changes.csv");
+            ctx.submit_command(commands::SHOW_SAVE_PANEL.with(options));
+        });
+
+    let main_view = Flex::column()
+        .with_child(build_offline_banner())
+        .with_child(build_uncommitted_banner())
+        .with_child(
+            Flex::row()
+                .with_flex_child(
+                    TextBox::new()
+                        .with_placeholder(strings::get().filter_placeholder)
+                        .with_id(FILTER_BOX)
+                        .expand_width()
+                        .lens(AppState::filter),
+                    1.,
+                )
+                .with_default_spacer()
+                .with_child(checkbox(strings::get().group_by_namespace).lens(AppState::grouped))
+                .with_default_spacer()
+                .with_child(checkbox(strings::get().show_term_ids).lens(AppState::show_term_ids))
+                .with_default_spacer()
+                .with_child(checkbox(strings::get().only_warnings).lens(AppState::only_warnings))
+                .padding(5.),
+        )
+        .with_flex_child(
+            build_tabs(checkbox_column_width(config)).env_scope(|env, data: &AppState| {
+                env.set(SHOW_TERM_IDS, data.show_term_ids);
+                env.set(GROUP_BY_NAMESPACE, data.grouped);
+                env.set(FILTER, data.filter.as_str());
+                env.set(ONLY_WARNINGS, data.only_warnings);
+            }),
+            10.,
+        )
+        .with_child(build_statistics())
+        .with_child(
+            Flex::row()
+                .with_child(Either::new(
+                    |data: &AppState, _| data.update_disabled(),
+                    Label::new(|data: &AppState, _: &_| {
+                        if data.config().read_only() {
+                            strings::get().update_disabled_read_only
+                        } else {
+                            strings::get().update_disabled_offline
+                        }
+                        .to_string()
+                    })
+                    .padding(10.),
+                    update_button,
+                ))
+                .with_child(reload_button)
+                .with_child(export_button)
+                .with_flex_child(
+                    Label::new(|data: &AppState, _: &_| data.status.clone())
+                        .with_line_break_mode(LineBreaking::WordWrap),
+                    1.,
+                ),
+        )
+        .with_child(build_status_bar())
+        .controller(Shortcuts);
+
+    ModalHost::new(main_view)
+        .controller(crate::geometry::Tracker)
+        .controller(crate::notification::Presence)
+}
+
+fn build_popup() -> impl Widget<AppState> {
+    ViewSwitcher::new(
+        |data: &AppState, _| data.popup.kind(),
+        |kind, _, _| match kind {
+            PopupKind::Confirming => build_confirmation().boxed(),
+            PopupKind::Progressing => build_progress().boxed(),
+            PopupKind::Reauthenticating => build_login_prompt().boxed(),
+            PopupKind::Finished => build_result().boxed(),
+        },
+    )
+}
+
+fn build_confirmation() -> impl Widget<AppState> {
+    let deletions = Either::new(
+        |data: &AppState, _: &_| data.removed.entries.iter().any(|e| e.active),
+        Flex::column()
+            .cross_axis_alignment(CrossAxisAlignment::Start)
+            .with_default_spacer()
+            .with_child(Label::new(strings::get().following_deleted))
+            .with_default_spacer()
+            .with_child(
+                Scroll::new(Label::new(|data: &AppState, _: &_| {
+                    data.removed
+                        .entries
+                        .iter()
+                        .filter(|e| e.active)
+                        .map(|e| e.term.as_str())
+                        .join("\n")
+                }))
+                .vertical()
+                .fix_height(150.),
+            )
+            .with_default_spacer()
+            .with_child(Label::new(|data: &AppState, _: &_| {
+                let count = data.removed.entries.iter().filter(|e| e.active).count();
+                (strings::get().confirm_deletion)(count, strings::get().deletion_keyword)
+            }))
+            .with_child(
+                TextBox::new()
+                    .with_placeholder(strings::get().deletion_keyword)
+                    .lens(AppState::deletion_confirmation),
+            ),
+        SizedBox::empty(),
+    );
+
+    let update_button = Either::new(
+        AppState::deletion_confirmed,
+        Button::new(strings::get().update).on_click(|ctx, data: &mut AppState, _| {
+            let progress = ProgressState::default();
+            wrapped_run(
+                ctx.get_external_handle(),
+                data.config
+                    .clone()
+                    .expect("Application started successfully."),
+                data.extract_translations(),
+                progress.cancelled.clone(),
+            );
+            data.popup = Popup::Progressing(progress);
+        }),
+        Label::new(strings::get().update).with_text_color(theme::PLACEHOLDER_COLOR),
+    );
+
+    Flex::column()
+        .with_child(Label::new(|data: &AppState, _: &_| {
+            (strings::get().pushing_to)(&data.destination.to_string())
+        }))
+        .with_default_spacer()
+        .with_child(Label::new(|data: &AppState, _: &_| {
+            fn count<T: Clone>(tab: &TabData<T>) -> usize {
+                tab.entries.iter().filter(|e| e.active).count()
+            }
+            (strings::get().summary)(
+                count(&data.added),
+                count(&data.removed),
+                count(&data.updated),
+            )
+        }))
+        .with_child(deletions)
+        .with_default_spacer()
+        .with_child(
+            Flex::row()
+                .with_child(update_button)
+                .with_default_spacer()
+                .with_child(Button::new(strings::get().cancel).on_click(|ctx, _, _| {
+                    ctx.submit_command(ModalHost::DISMISS_MODAL);
+                })),
+        )
+        .padding(16.0)
+        .background(theme::BACKGROUND_DARK)
+}
+
+fn build_progress() -> impl Widget<AppState> {
+    Flex::column()
+        .with_child(Label::new(strings::get().uploading))
+        .with_default_spacer()
+        .with_child(Spinner::new())
+        .with_default_spacer()
+        .with_child(ProgressBar::new().lens(ProgressState::fraction))
+        .with_default_spacer()
+        .with_child(
+            Label::new(|data: &ProgressState, _: &_| data.current.clone())
+                .with_line_break_mode(LineBreaking::WordWrap),
+        )
+        .with_default_spacer()
+        .with_child(
+            Scroll::new(
+                Label::new(|data: &ProgressState, _: &_| data.log.iter().join("\n"))
+                    .with_text_color(theme::PLACEHOLDER_COLOR),
+            )
+            .vertical()
+            .fix_height(150.),
+        )
+        .with_default_spacer()
+        .with_child(Button::new(strings::get().cancel).on_click(|ctx, _, _| {
+            ctx.submit_command(CANCEL_UPDATE);
+        }))
+        .padding(16.0)
+        .background(theme::BACKGROUND_DARK)
+        .lens(
+            AppState::popup.read_only(|p: &Popup| p.as_progressing().cloned().unwrap_or_default()),
+        )
+}
+
+fn build_login_prompt() -> impl Widget<AppState> {
+    Flex::column()
+        .with_child(Label::new(strings::get().login_rejected))
+        .with_default_spacer()
+        .with_child(
+            Flex::row()
+                .with_child(
+                    Label::new(|p: &LoginPrompt, _: &_| {
+                        if p.client_credentials {
+                            strings::get().client_id
+                        } else {
+                            strings::get().mail
+                        }
+                        .to_string()
+                    })
+                    .fix_width(100.),
+                )
+                .with_child(TextBox::new().fix_width(250.).lens(LoginPrompt::user))
+                .lens(AppState::login_prompt),
+        )
+        .with_default_spacer()
+        .with_child(
+            Flex::row()
+                .with_child(
+                    Label::new(|p: &LoginPrompt, _: &_| {
+                        if p.client_credentials {
+                            strings::get().client_secret
+                        } else {
+                            strings::get().password
+                        }
+                        .to_string()
+                    })
+                    .fix_width(100.),
+                )
+                .with_child(TextBox::new().fix_width(250.).lens(LoginPrompt::secret))
+                .lens(AppState::login_prompt),
+        )
+        .with_default_spacer()
+        .with_child(
+            Flex::row()
+                .with_child(Button::new(strings::get().log_in).on_click(
+                    |_, data: &mut AppState, _| {
+                        data.login_prompt.answer(true);
+                        data.popup = Popup::Progressing(
+                            data.popup.as_progressing().cloned().unwrap_or_default(),
+                        );
+                    },
+                ))
+                .with_default_spacer()
+                .with_child(Button::new(strings::get().skip).on_click(
+                    |_, data: &mut AppState, _| {
+                        data.login_prompt.answer(false);
+                        data.popup = Popup::Progressing(
+                            data.popup.as_progressing().cloned().unwrap_or_default(),
+                        );
+                    },
+                )),
+        )
+        .padding(16.0)
+        .background(theme::BACKGROUND_DARK)
+}
+
+fn result_summary(result: &UpdateResult) -> String {
+    match result {
+        Ok(_) => strings::get().no_error.into(),
+        Err(UpdateError::ReadOnly) => strings::get().update_disabled_read_only.into(),
+        Err(UpdateError::ClientCreation(e)) => format!("{}", e),
+        Err(UpdateError::Lock(e)) => format!("{:#}", e),
+        Err(UpdateError::Update(errs, _)) => (strings::get().update_failed)(errs.len()),
+        Err(UpdateError::Cancelled {
+            applied, remaining, ..
+        }) => (strings::get().cancelled)(*applied, remaining.len()),
+    }
+}
+
+fn build_failure() -> impl Widget<FailureDetails> {
+    Expander::new(
+        Label::new(|f: &FailureDetails, _: &_| f.summary.clone())
+            .with_line_break_mode(LineBreaking::WordWrap),
+        Label::new(|f: &FailureDetails, _: &_| f.details.clone())
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .with_text_color(theme::PLACEHOLDER_COLOR),
+    )
+}
+
+fn build_failure_group() -> impl Widget<FailureGroup> {
+    Expander::new(
+        Label::new(|group: &FailureGroup, _: &_| group.title.clone()),
+        List::new(build_failure).lens(FailureGroup::failures),
+    )
+    .expanded()
+}
+
+fn build_result() -> impl Widget<AppState> {
+    let result = || {
+        AppState::popup
+            .read_only(|p: &Popup| p.as_finished().cloned().unwrap_or_else(|| Ok(()).into()))
+    };
+    Flex::column()
+        .with_child(Label::new(strings::get().finished_uploading))
+        .with_default_spacer()
+        .with_child(
+            Label::new(|data: &Arc<UpdateResult>, _: &_| result_summary(data))
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .lens(result()),
+        )
+        .with_default_spacer()
+        .with_flex_child(
+            Scroll::new(List::new(build_failure_group))
+                .vertical()
+                .lens(
+                    result().read_only(|data: &Arc<UpdateResult>| match data.as_ref() {
+                        Err(UpdateError::Update(errors, _))
+                        | Err(UpdateError::Cancelled { errors, .. }) => FailureGroup::group(errors),
+                        _ => im::Vector::new(),
+                    }),
+                ),
+            1.,
+        )
+        .with_default_spacer()
+        .with_child(
+            Flex::row()
+                .with_child(Button::new(strings::get().ok).on_click(|ctx, _, _| {
+                    ctx.submit_command(ModalHost::DISMISS_MODAL);
+                }))
+                .with_child(Either::new(
+                    |data: &AppState, _: &_| !data.popup.retriable().is_empty(),
+                    Button::new(strings::get().retry_failed)
+                        .on_click(|ctx, data: &mut AppState, _| {
+                            let translations = data.popup.retriable().to_vec();
+                            let progress = ProgressState::default();
+                            wrapped_run(
+                                ctx.get_external_handle(),
+                                data.config
+                                    .clone()
+                                    .expect("Application started successfully."),
+                                translations,
+                                progress.cancelled.clone(),
+                            );
+                            data.popup = Popup::Progressing(progress);
+                        })
+                        .padding((10., 0., 0., 0.)),
+                    SizedBox::empty(),
+                )),
+        )
+        .padding(16.0)
+        .background(theme::BACKGROUND_DARK)
+}
+
+fn wrapped_run(
+    sink: ExtEventSink,
+    config: Arc<AppConfig>,
+    translations: Vec<Translation>,
+    cancelled: Arc<AtomicBool>,
+) {
+    std::thread::spawn(move || {
+        let progress_sink = sink.clone();
+        let reauthenticate_sink = sink.clone();
+        let result = crate::updater::run(
+            &config,
+            translations,
+            move |progress| {
+                log::debug!("Sending update progress command: {:?}", progress);
+                progress_sink
+                    .submit_command(UPDATE_PROGRESS, progress, Target::Auto)
+                    .expect("Failed to submit update progress command.");
+            },
+            move || {
+                let (reply, answer) = mpsc::channel();
+                log::info!("Sending reauthenticate command.");
+                reauthenticate_sink
+                    .submit_command(REAUTHENTICATE, SingleUse::new(reply), Target::Auto)
+                    .expect("Failed to submit reauthenticate command.");
+                answer.recv().ok().flatten()
+            },
+            &cancelled,
+        );
+        log::info!("Sending finished update command: {:#?}", result);
+        sink.submit_command(UPDATE_FINISHED, SingleUse::new(result), Target::Auto)
+            .expect("Failed to submit update finished command.");
+    });
+}
+
+/// Stops the running update after the current term.
+const CANCEL_UPDATE: Selector = Selector::new("me.erik-hennig.traduora-update.cancel-update");
+
+const UPDATE_PROGRESS: Selector<Progress> =
+    Selector::new("me.erik-hennig.traduora-update.update-progress");
+
+const RELOAD_FINISHED: Selector<SingleUse<anyhow::Result<LoadedData>>> =
+    Selector::new("me.erik-hennig.traduora-update.reload-finished");
+
+/// Loads the configuration and the data again after the startup failed.
+const RETRY_STARTUP: Selector = Selector::new("me.erik-hennig.traduora-update.retry-startup");
+
+/// The configuration, if it could be loaded, and the data loaded with it.
+#[allow(clippy::type_complexity)]
+const STARTUP_FINISHED: Selector<SingleUse<(Option<Arc<AppConfig>>, anyhow::Result<LoadedData>)>> =
+    Selector::new("me.erik-hennig.traduora-update.startup-finished");
+
+const UPDATE_FINISHED: Selector<SingleUse<UpdateResult>> =
+    Selector::new("me.erik-hennig.traduora-update.update-finished");
+
+const REAUTHENTICATE: Selector<SingleUse<mpsc::Sender<Option<LoginConfig>>>> =
+    Selector::new("me.erik-hennig.traduora-update.reauthenticate");
+
+/// Loads the data again on a background thread.
+fn start_reload(sink: ExtEventSink, data: &mut AppState) {
+    if data.reloading {
+        return;
+    }
+    data.reloading = true;
+    data.status = strings::get().status_reloading.into();
+
+    let config = data
+        .config
+        .clone()
+        .expect("Application started successfully.");
+    std::thread::spawn(move || {
+        let result = crate::loader::load_data(&config);
+        log::info!("Sending reload finished command.");
+        sink.submit_command(RELOAD_FINISHED, SingleUse::new(result), Target::Auto)
+            .expect("Failed to submit reload finished command.");
+    });
+}
+
+pub struct Delegate {
+    sink: ExtEventSink,
+    /// Command line options to apply when the configuration is loaded again.
+    cli: Arc<Cli>,
+}
+
+impl Delegate {
+    pub fn new(sink: ExtEventSink, cli: Arc<Cli>) -> Self {
+        Self { sink, cli }
+    }
+}
+
+impl Delegate {
+    /// Shows `config` instead of the current project and loads its data.
+    fn switch_project(
+        &self,
+        ctx: &mut druid::DelegateCtx,
+        target: Target,
+        data: &mut AppState,
+        config: AppConfig,
+    ) {
+        data.config = Some(Arc::new(config));
+        start_reload(self.sink.clone(), data);
+        data.status = (strings::get().switching_project)(data.config().project().name());
+        // The locales differ between projects.
+        if let Target::Window(window) = target {
+            ctx.set_menu(build_menu(data.config.as_deref()), window);
+        }
+    }
+}
+
+impl AppDelegate<AppState> for Delegate {
+    fn event(
+        &mut self,
+        _: &mut druid::DelegateCtx,
+        _: druid::WindowId,
+        event: druid::Event,
+        data: &mut AppState,
+        _: &Env,
+    ) -> Option<druid::Event> {
+        match event {
+            druid::Event::KeyDown(ref key)
+                if key.key == druid::keyboard_types::Key::F5 && !data.starting() =>
+            {
+                start_reload(self.sink.clone(), data);
+                None
+            }
+            event => Some(event),
+        }
+    }
+
+    fn command(
+        &mut self,
+        ctx: &mut druid::DelegateCtx,
+        target: Target,
+        cmd: &druid::Command,
+        data: &mut AppState,
+        _: &Env,
+    ) -> druid::Handled {
+        log::debug!("Received command {:?}.", cmd);
+        if cmd.is(CANCEL_UPDATE) {
+            if let Popup::Progressing(state) = &mut data.popup {
+                log::info!("Cancelling update.");
+                state.cancelled.store(true, Ordering::Relaxed);
+                state.current = strings::get().cancelling.into();
+            }
+            druid::Handled::Yes
+        } else if let Some(progress) = cmd.get(UPDATE_PROGRESS) {
+            let mut state = data.popup.as_progressing().cloned().unwrap_or_default();
+            state.advance(progress);
+            data.popup = Popup::Progressing(state);
+            druid::Handled::Yes
+        } else if let Some(reply) = cmd.get(REAUTHENTICATE).and_then(SingleUse::take) {
+            data.login_prompt = LoginPrompt::new(reply, data.config().login());
+            data.popup =
+                Popup::Reauthenticating(data.popup.as_progressing().cloned().unwrap_or_default());
+            druid::Handled::Yes
+        } else if let Some(file) = cmd.get(commands::OPEN_FILE) {
+            if let Some(startup) = data.startup.as_mut() {
+                startup.config_file = Some(Arc::new(file.path().to_owned()));
+            }
+            druid::Handled::Yes
+        } else if let Some((config, result)) = cmd.get(STARTUP_FINISHED).and_then(SingleUse::take) {
+            match (config, result) {
+                (Some(config), Ok(loaded)) => *data = AppState::build(config, loaded),
+                (config, result) => {
+                    let e = result.err().unwrap_or_else(|| {
+                        anyhow::anyhow!("Data was loaded without a configuration.")
+                    });
+                    log::error!("Failed to start: {:?}", e);
+                    *data = AppState {
+                        // After the setup wizard, there was no startup screen before.
+                        startup: Some(Startup::new(
+                            e,
+                            config,
+                            data.startup.take().and_then(|s| s.config_file),
+                        )),
+                        ..AppState::default()
+                    };
+                }
+            }
+            druid::Handled::Yes
+        } else if let Some(path) = cmd.get(wizard::FINISHED) {
+            log::info!("Setup wizard created {}.", path.display());
+            retry_startup(
+                self.sink.clone(),
+                self.cli.clone(),
+                None,
+                Some(path.clone()),
+            );
+            druid::Handled::Yes
+        } else if cmd.is(RETRY_STARTUP) {
+            if let Some(startup) = &data.startup {
+                retry_startup(
+                    self.sink.clone(),
+                    self.cli.clone(),
+                    startup.config.clone(),
+                    startup.config_file.as_deref().cloned(),
+                );
+            }
+            druid::Handled::Yes
+        } else if let Some(term) = cmd.get(OPEN_IN_TRADUORA) {
+            // The web interface cannot show a single term, so the term is copied for its search.
+            druid::Application::global().clipboard().put_string(term);
+            data.status =
+                match crate::browser::open(&crate::browser::translations_url(data.config())) {
+                    Ok(()) => (strings::get().opened_in_traduora)(term),
+                    Err(e) => {
+                        log::error!("Failed to open Traduora: {:?}", e);
+                        (strings::get().open_in_traduora_failed)(&e)
+                    }
+                };
+            druid::Handled::Yes
+        } else if cmd.is(SHOW_LOG) {
+            ctx.new_window(
+                druid::WindowDesc::new(build_log_window)
+                    .title(strings::get().log_title)
+                    .window_size((700., 400.)),
+            );
+            druid::Handled::Yes
+        } else if let Some(&index) = cmd.get(SELECT_LOCALE) {
+            // The running load or update would show its result for the other locale.
+            if data.reloading || data.popup.as_progressing().is_some() || data.starting() {
+                data.status = strings::get().locale_busy.into();
+            } else {
+                data.config = Some(Arc::new(data.config().with_locale(index)));
+                start_reload(self.sink.clone(), data);
+                data.status = (strings::get().switching_locale)(data.config().locale().value());
+            }
+            druid::Handled::Yes
+        } else if let Some(&index) = cmd.get(SELECT_PROJECT) {
+            if data.reloading || data.popup.as_progressing().is_some() || data.starting() {
+                data.status = strings::get().project_busy.into();
+            } else {
+                let config = data.config().with_project(index);
+                self.switch_project(ctx, target, data, config);
+            }
+            druid::Handled::Yes
+        } else if cmd.is(SHOW_PICKER) {
+            if let Target::Window(window) = target {
+                let config = data.config().clone();
+                data.picker.load_projects(self.sink.clone(), config);
+                ctx.new_window(
+                    druid::WindowDesc::new(move || build_picker_window(window))
+                        .title(strings::get().picker_title)
+                        .window_size((600., 450.)),
+                );
+            }
+            druid::Handled::Yes
+        } else if let Some((project_id, locale)) = cmd.get(OPEN_PICKED) {
+            if data.reloading || data.popup.as_progressing().is_some() || data.starting() {
+                data.status = strings::get().project_busy.into();
+            } else {
+                let config = data.config().with_remote_project(project_id, locale);
+                self.switch_project(ctx, target, data, config);
+            }
+            druid::Handled::Yes
+        } else if let Some(file) = cmd.get(OPEN_PROJECT) {
+            data.status = match open_project(file.path()) {
+                Ok(()) => (strings::get().opened_project)(file.path()),
+                Err(e) => {
+                    log::error!("Failed to open project: {:?}", e);
+                    (strings::get().open_project_failed)(&e)
+                }
+            };
+            druid::Handled::Yes
+        } else if let Some(file) = cmd.get(commands::SAVE_FILE_AS) {
+            data.status = match crate::export::write(file.path(), &data.extract_translations()) {
+                Ok(count) => (strings::get().exported)(count, file.path()),
+                Err(e) => {
+                    log::error!("Export failed: {:?}", e);
+                    (strings::get().export_failed)(&e)
+                }
+            };
+            druid::Handled::Yes
+        } else if let Some(result) = cmd.get(RELOAD_FINISHED).and_then(SingleUse::take) {
+            data.reloading = false;
+            match result {
+                Ok(loaded) => {
+                    data.reload(loaded);
+                    data.status = strings::get().status_reloaded.into();
+                }
+                Err(e) => {
+                    log::error!("Failed to reload data: {:?}", e);
+                    data.status = (strings::get().reload_failed)(&e);
+                }
+            }
+            druid::Handled::Yes
+        } else if let Some(result) = cmd.get(UPDATE_FINISHED).and_then(SingleUse::take) {
+            if crate::notification::unattended() {
+                let title = strings::get().finished_uploading;
+                if let Err(e) = crate::notification::send(title, &result_summary(&result)) {
+                    log::warn!("Failed to show notification: {:?}", e);
+                }
+            }
+            data.popup = Popup::Finished(result.into());
+            start_reload(self.sink.clone(), data);
+            druid::Handled::Yes
+        } else {
+            druid::Handled::No
+        }
+    }
+
+    fn window_removed(
+        &mut self,
+        _: druid::WindowId,
+        _: &mut AppState,
+        _: &Env,
+        _: &mut druid::DelegateCtx,
+    ) {
+        if let Err(e) = crate::geometry::store() {
+            log::warn!("Failed to save window geometry: {:?}", e);
+        }
+    }
+}
 
 #[derive(Debug, Clone, Data)]
 pub struct AppStateError(Arc<anyhow::Error>);
@@ -460,14 +2279,337 @@ impl From<anyhow::Error> for AppStateError {
     }
 }
 
-pub fn build_ui_startup_failed() -> impl Widget<AppStateError> {
+#[derive(Data, Debug, Clone, Copy, PartialEq, Eq)]
+enum RootView {
+    Wizard,
+    StartupFailed,
+    Main,
+}
+
+/// Root widget that shows the main view or, if the application failed to start, the error.
+/// Without a config file, the setup wizard is shown first.
+pub fn build_root() -> impl Widget<AppState> {
+    ViewSwitcher::new(
+        |data: &AppState, _| {
+            if data.wizard.is_some() {
+                RootView::Wizard
+            } else if data.startup.is_some() {
+                RootView::StartupFailed
+            } else {
+                RootView::Main
+            }
+        },
+        |view, data: &AppState, _| match view {
+            RootView::Wizard => Scroll::new(wizard::build())
+                .vertical()
+                .lens(WizardLens)
+                .boxed(),
+            RootView::StartupFailed => build_ui_startup_failed().boxed(),
+            RootView::Main => build_ui(data.config()).boxed(),
+        },
+    )
+}
+
+fn config_file_options() -> FileDialogOptions {
+    FileDialogOptions::new()
+        .allowed_types(vec![
+            FileSpec::new("JSON", &["json"]),
+            FileSpec::new("TOML", &["toml"]),
+            FileSpec::new("YAML", &["yaml", "yml"]),
+        ])
+        .default_type(FileSpec::new("JSON", &["json"]))
+}
+
+/// Config file chosen with "File → Open project…".
+const OPEN_PROJECT: Selector<FileInfo> =
+    Selector::new("me.erik-hennig.traduora-update.open-project");
+
+/// Menu bar of the window. The project and locale menus need the configuration.
+pub fn build_menu(config: Option<&AppConfig>) -> MenuDesc<AppState> {
+    let open_project = MenuItem::new(
+        LocalizedString::new("me.erik-hennig.traduora-update.menu-open-project")
+            .with_placeholder(strings::get().open_project),
+        commands::SHOW_OPEN_PANEL.with(config_file_options().accept_command(OPEN_PROJECT)),
+    );
+    let show_log = MenuItem::new(
+        LocalizedString::new("me.erik-hennig.traduora-update.menu-show-log")
+            .with_placeholder(strings::get().show_log),
+        SHOW_LOG,
+    );
+    let mut menu = MenuDesc::empty().append(
+        MenuDesc::new(
+            LocalizedString::new("me.erik-hennig.traduora-update.menu-file")
+                .with_placeholder(strings::get().file_menu),
+        )
+        .append(open_project)
+        .append(show_log),
+    );
+    if let Some(config) = config {
+        menu = menu.append(build_project_menu(config));
+        if config.locales().count() > 1 {
+            menu = menu.append(build_locale_menu(config));
+        }
+    }
+    menu
+}
+
+/// Switches to the project at the index of [`AppConfig::projects`].
+const SELECT_PROJECT: Selector<usize> =
+    Selector::new("me.erik-hennig.traduora-update.select-project");
+
+/// Opens the window to choose a project and locale on Traduora.
+const SHOW_PICKER: Selector = Selector::new("me.erik-hennig.traduora-update.show-picker");
+
+/// Switches to the project id and locale chosen on Traduora.
+const OPEN_PICKED: Selector<(String, String)> =
+    Selector::new("me.erik-hennig.traduora-update.open-picked");
+
+/// Lists the configured projects if there are several, followed by the item to choose one on Traduora.
+fn build_project_menu(config: &AppConfig) -> MenuDesc<AppState> {
+    let mut menu = MenuDesc::new(
+        LocalizedString::new("me.erik-hennig.traduora-update.menu-project")
+            .with_placeholder(strings::get().project_menu),
+    );
+    if config.projects().count() > 1 {
+        menu = config
+            .projects()
+            .enumerate()
+            .fold(menu, |menu, (index, name)| {
+                menu.append(MenuItem::new(
+                    LocalizedString::new("me.erik-hennig.traduora-update.menu-select-project")
+                        .with_placeholder(name.to_string()),
+                    SELECT_PROJECT.with(index),
+                ))
+            })
+            .append_separator();
+    }
+    menu.append(MenuItem::new(
+        LocalizedString::new("me.erik-hennig.traduora-update.menu-choose-on-traduora")
+            .with_placeholder(strings::get().choose_on_traduora),
+        SHOW_PICKER,
+    ))
+}
+
+/// Lets the user choose a project and locale on Traduora, which `parent` then switches to.
+fn build_picker_window(parent: druid::WindowId) -> impl Widget<AppState> {
+    let open = Either::new(
+        |p: &Picker, _: &_| p.chosen().is_some(),
+        Button::new(strings::get().picker_open).on_click(move |ctx, p: &mut Picker, _| {
+            if let Some((project_id, locale)) = p.chosen() {
+                let picked = (project_id.to_string(), locale.to_string());
+                ctx.submit_command(OPEN_PICKED.with(picked).to(parent));
+                ctx.submit_command(commands::CLOSE_WINDOW);
+            }
+        }),
+        Label::new(strings::get().picker_open).with_text_color(theme::PLACEHOLDER_COLOR),
+    );
+    Scroll::new(
+        Flex::column()
+            .cross_axis_alignment(CrossAxisAlignment::Start)
+            .with_child(crate::picker::build())
+            .with_default_spacer()
+            .with_child(open)
+            .padding(10.),
+    )
+    .vertical()
+    .lens(AppState::picker)
+}
+
+/// Switches to the locale at the index of [`AppConfig::locales`].
+const SELECT_LOCALE: Selector<usize> =
+    Selector::new("me.erik-hennig.traduora-update.select-locale");
+
+fn build_locale_menu(config: &AppConfig) -> MenuDesc<AppState> {
+    config.locales().enumerate().fold(
+        MenuDesc::new(
+            LocalizedString::new("me.erik-hennig.traduora-update.menu-locale")
+                .with_placeholder(strings::get().locale_menu),
+        ),
+        |menu, (index, locale)| {
+            menu.append(MenuItem::new(
+                LocalizedString::new("me.erik-hennig.traduora-update.menu-select-locale")
+                    .with_placeholder(locale.to_string()),
+                SELECT_LOCALE.with(index),
+            ))
+        },
+    )
+}
+
+/// Shows the messages logged so far in a separate window.
+const SHOW_LOG: Selector = Selector::new("me.erik-hennig.traduora-update.show-log");
+
+/// The log at the time the window is opened. Opening it again shows newer messages.
+fn build_log_window() -> impl Widget<AppState> {
+    let log = crate::logging::buffered();
+    let log = if log.is_empty() {
+        strings::get().log_empty.to_string()
+    } else {
+        log
+    };
+    Scroll::new(
+        Label::new(log)
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .padding(10.),
+    )
+    .vertical()
+}
+
+/// Opens another window for the project configured in `config_file`.
+///
+/// Every config file runs in a process of its own, so a crash does not take the other windows with it.
+fn open_project(config_file: &Path) -> Result<(), anyhow::Error> {
+    use anyhow::Context;
+    let exe = std::env::current_exe().context("Failed to determine path of the executable.")?;
+    std::process::Command::new(&exe)
+        .arg("--config")
+        .arg(config_file)
+        .spawn()
+        .with_context(|| format!("Failed to start {}", exe.display()))?;
+    Ok(())
+}
+
+fn build_ui_startup_failed() -> impl Widget<AppState> {
+    let choose_config = Either::new(
+        |startup: &Startup, _: &_| startup.config.is_some(),
+        SizedBox::empty(),
+        Button::new(strings::get().choose_config).on_click(|ctx, _, _| {
+            ctx.submit_command(commands::SHOW_OPEN_PANEL.with(config_file_options()));
+        }),
+    );
+
+    let retry = Either::new(
+        |startup: &Startup, _: &_| startup.retrying,
+        Label::new(strings::get().retrying),
+        Button::new(|startup: &Startup, _: &_| {
+            match startup.passphrase {
+                Some(_) => strings::get().unlock,
+                None => strings::get().retry,
+            }
+            .to_string()
+        })
+        .on_click(|ctx, startup: &mut Startup, _| {
+            if let (Some(passphrase), Some(config)) = (&startup.passphrase, &startup.config) {
+                if let Err(e) = crate::secret::unlock(config, passphrase) {
+                    startup.error = e.into();
+                    return;
+                }
+                startup.passphrase = None;
+            }
+            startup.retrying = true;
+            ctx.submit_command(RETRY_STARTUP);
+        }),
+    );
+
+    let passphrase = Either::new(
+        |startup: &Startup, _: &_| startup.passphrase.is_some(),
+        Flex::row()
+            .with_child(Label::new(strings::get().passphrase).fix_width(100.))
+            .with_child(
+                TextBox::new()
+                    .fix_width(250.)
+                    .lens(druid::lens::Map::new(
+                        |p: &Option<String>| p.clone().unwrap_or_default(),
+                        |p: &mut Option<String>, entered| *p = Some(entered),
+                    ))
+                    .lens(Startup::passphrase),
+            )
+            .padding((0., 0., 0., 10.)),
+        SizedBox::empty(),
+    );
+
     Flex::column()
-        .with_child(Label::new(
-            "Failed to start. Please fix the error and restart the application.",
-        ))
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(|startup: &Startup, _: &_| {
+            match startup.passphrase {
+                Some(_) => strings::get().passphrase_required,
+                None => strings::get().startup_failed,
+            }
+            .to_string()
+        }))
         .with_default_spacer()
         .with_flex_child(
-            Label::new(|state: &AppStateError, _: &_| format!("{:?}", state.0)),
+            Scroll::new(
+                Label::new(|startup: &Startup, _: &_| format!("{:?}", startup.error.0))
+                    .with_line_break_mode(LineBreaking::WordWrap),
+            )
+            .vertical(),
             1.,
         )
+        .with_default_spacer()
+        .with_child(Label::new(|startup: &Startup, _: &_| {
+            startup
+                .config_file
+                .as_ref()
+                .map(|file| (strings::get().chosen_config)(file))
+                .unwrap_or_default()
+        }))
+        .with_default_spacer()
+        .with_child(passphrase)
+        .with_child(
+            Flex::row()
+                .with_child(choose_config)
+                .with_default_spacer()
+                .with_child(retry),
+        )
+        .padding(10.)
+        .lens(StartupLens)
+}
+
+/// Lens to the startup state. Must only be used while the startup state is set.
+struct StartupLens;
+
+impl Lens<AppState, Startup> for StartupLens {
+    fn with<V, F: FnOnce(&Startup) -> V>(&self, data: &AppState, f: F) -> V {
+        f(data
+            .startup
+            .as_ref()
+            .expect("Application started successfully."))
+    }
+
+    fn with_mut<V, F: FnOnce(&mut Startup) -> V>(&self, data: &mut AppState, f: F) -> V {
+        f(data
+            .startup
+            .as_mut()
+            .expect("Application started successfully."))
+    }
+}
+
+/// Lens to the setup wizard. Must only be used while the wizard is shown.
+struct WizardLens;
+
+impl Lens<AppState, Wizard> for WizardLens {
+    fn with<V, F: FnOnce(&Wizard) -> V>(&self, data: &AppState, f: F) -> V {
+        f(data.wizard.as_ref().expect("Setup wizard is shown."))
+    }
+
+    fn with_mut<V, F: FnOnce(&mut Wizard) -> V>(&self, data: &mut AppState, f: F) -> V {
+        f(data.wizard.as_mut().expect("Setup wizard is shown."))
+    }
+}
+
+/// Loads the configuration, unless it was loaded already, and the data in the background.
+fn retry_startup(
+    sink: ExtEventSink,
+    cli: Arc<Cli>,
+    config: Option<Arc<AppConfig>>,
+    config_file: Option<PathBuf>,
+) {
+    std::thread::spawn(move || {
+        let config = match (config, config_file) {
+            (Some(config), _) => Ok(config),
+            (None, Some(file)) => crate::config::load_from(file, &cli).map(Arc::new),
+            (None, None) => crate::config::load(&cli).map(Arc::new),
+        };
+        let result = match config {
+            Ok(config) => {
+                let result = crate::preflight::run(&config)
+                    .into_result()
+                    .and_then(|_| crate::loader::load_data(&config));
+                (Some(config), result)
+            }
+            Err(e) => (None, Err(e)),
+        };
+        sink.submit_command(STARTUP_FINISHED, SingleUse::new(result), Target::Auto)
+            .expect("Failed to submit startup finished command.");
+    });
 }
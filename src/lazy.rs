@@ -0,0 +1,91 @@
+//! A widget that builds its child the first time it is painted.
+//!
+//! Hidden tabs are never painted, so wrapping the body of a tab defers
+//! building it until the user opens the tab for the first time.
+
+use druid::widget::prelude::*;
+use druid::{ExtEventSink, Selector, WidgetExt, WidgetPod};
+
+type Builder<T> = Box<dyn FnOnce() -> Box<dyn Widget<T>>>;
+
+pub struct Lazy<T> {
+    builder: Option<Builder<T>>,
+    child: Option<WidgetPod<T, Box<dyn Widget<T>>>>,
+    sink: Option<ExtEventSink>,
+}
+
+impl Lazy<()> {
+    /// Command a lazy widget sends to itself once it needs to build its child.
+    const BUILD: Selector = Selector::new("me.erik-hennig.traduora-update.lazy-build");
+}
+
+impl<T: Data> Lazy<T> {
+    pub fn new<W: Widget<T> + 'static>(f: impl FnOnce() -> W + 'static) -> Self {
+        Self {
+            builder: Some(Box::new(|| f().boxed())),
+            child: None,
+            sink: None,
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Lazy<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if let Event::Command(cmd) = event {
+            if cmd.is(Lazy::BUILD) {
+                if let Some(builder) = self.builder.take() {
+                    log::debug!("Building lazy widget {:?}.", ctx.widget_id());
+                    self.child = Some(WidgetPod::new(builder()));
+                    ctx.children_changed();
+                }
+                ctx.set_handled();
+                return;
+            }
+        }
+        if let Some(child) = self.child.as_mut() {
+            child.event(ctx, event, data, env);
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        if let LifeCycle::WidgetAdded = event {
+            if self.builder.is_some() {
+                self.sink = Some(ctx.get_external_handle());
+            }
+        }
+        if let Some(child) = self.child.as_mut() {
+            child.lifecycle(ctx, event, data, env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        if let Some(child) = self.child.as_mut() {
+            child.update(ctx, data, env);
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        match self.child.as_mut() {
+            Some(child) => {
+                let size = child.layout(ctx, bc, data, env);
+                child.set_layout_rect(ctx, data, env, size.to_rect());
+                size
+            }
+            None => bc.max(),
+        }
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        match self.child.as_mut() {
+            Some(child) => child.paint(ctx, data, env),
+            // The paint context cannot submit commands, so the request goes through the event sink.
+            None => {
+                if let Some(sink) = self.sink.take() {
+                    if let Err(e) = sink.submit_command(Lazy::BUILD, (), ctx.widget_id()) {
+                        log::error!("Failed to request building lazy widget: {:?}", e);
+                    }
+                }
+            }
+        }
+    }
+}
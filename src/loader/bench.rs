@@ -0,0 +1,151 @@
+//! Timing of the load and merge steps on the configured inputs.
+//!
+//! Traduora is only queried once because the network latency would dominate
+//! the measurements. The parse, git and merge steps are repeated.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use super::{data, local};
+use crate::config::AppConfig;
+
+/// Timings of a single step over all iterations.
+#[derive(Debug)]
+pub struct Timings {
+    pub name: &'static str,
+    samples: Vec<Duration>,
+}
+
+impl Timings {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            samples: Vec::new(),
+        }
+    }
+
+    fn measure<T>(&mut self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let start = Instant::now();
+        let result = f()?;
+        self.samples.push(start.elapsed());
+        Ok(result)
+    }
+
+    fn sorted(&self) -> Vec<Duration> {
+        let mut samples = self.samples.clone();
+        samples.sort_unstable();
+        samples
+    }
+
+    pub fn min(&self) -> Duration {
+        self.sorted().first().copied().unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.sorted().last().copied().unwrap_or_default()
+    }
+
+    pub fn median(&self) -> Duration {
+        let samples = self.sorted();
+        samples.get(samples.len() / 2).copied().unwrap_or_default()
+    }
+
+    pub fn mean(&self) -> Duration {
+        match self.samples.len() {
+            0 => Duration::default(),
+            n => self.samples.iter().sum::<Duration>() / n as u32,
+        }
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:<6} n={:<4} min={:>10.3?} median={:>10.3?} mean={:>10.3?} max={:>10.3?}",
+            self.name,
+            self.samples.len(),
+            self.min(),
+            self.median(),
+            self.mean(),
+            self.max()
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub terms: usize,
+    pub steps: Vec<Timings>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} differences found", self.terms)?;
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the parse, git and merge steps `iterations` times and collects their timings.
+pub fn run(config: &AppConfig, iterations: usize) -> Result<Report> {
+    let translation_files = config.translation_files();
+    let revision = config.revision();
+
+    let (remote, _) = data::fetch_remote(config)?;
+
+    let mut parse = Timings::new("parse");
+    let mut git = Timings::new("git");
+    let mut merge = Timings::new("merge");
+    let mut terms = 0;
+
+    for _ in 0..iterations {
+        let local = parse.measure(|| local::load_from_files(config, translation_files))?;
+        let history = if revision.is_empty()
+            || local::is_stdin(config.translation_file())
+            || translation_files.iter().any(local::is_url)
+        {
+            Vec::new()
+        } else {
+            git.measure(|| local::load_from_git_files(config, revision, translation_files))?
+        };
+        let remote = remote.clone();
+        terms = merge
+            .measure(|| {
+                Ok(data::merge(
+                    local,
+                    remote,
+                    history,
+                    config.ignore_terms(),
+                    None,
+                ))
+            })?
+            .len();
+    }
+
+    Ok(Report {
+        terms,
+        steps: vec![parse, git, merge],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timing_statistics() {
+        let mut timings = Timings::new("test");
+        for ms in [3, 1, 2, 10] {
+            timings.samples.push(Duration::from_millis(ms));
+        }
+        assert_eq!(Duration::from_millis(1), timings.min());
+        assert_eq!(Duration::from_millis(10), timings.max());
+        assert_eq!(Duration::from_millis(3), timings.median());
+        assert_eq!(Duration::from_millis(4), timings.mean());
+    }
+}
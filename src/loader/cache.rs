@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use super::remote::Translation;
+use crate::config::AppConfig;
+
+fn cache_file(config: &AppConfig) -> Result<PathBuf> {
+    let dir = dirs::cache_dir().context("Failed to determine cache directory.")?;
+    Ok(dir.join("traduora-update").join(format!(
+        "{}_{}.json",
+        config.project_id(),
+        config.locale()
+    )))
+}
+
+/// Saves the data fetched from Traduora so it can be used if the server becomes unreachable.
+pub fn store(config: &AppConfig, translations: &[Translation]) -> Result<()> {
+    let path = cache_file(config)?;
+    let fun = || -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let data = serde_json::to_vec(translations)?;
+        crate::atomic::write(&path, data)?;
+        Ok(())
+    };
+    fun().with_context(|| format!("Failed to write cache file {}", path.display()))
+}
+
+pub fn exists(config: &AppConfig) -> bool {
+    cache_file(config).map_or(false, |path| path.is_file())
+}
+
+/// Loads the data that was last fetched from Traduora.
+pub fn load(config: &AppConfig) -> Result<Vec<Translation>> {
+    let path = cache_file(config)?;
+    let data = std::fs::read(&path)
+        .with_context(|| format!("Failed to read cache file {}", path.display()))?;
+    serde_json::from_slice(&data)
+        .with_context(|| format!("Failed to parse cache file {}", path.display()))
+}
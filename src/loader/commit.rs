@@ -0,0 +1,113 @@
+//! Commits the translation files after an update, so the repository records exactly what was
+//! uploaded to Traduora.
+//!
+//! Only the translation files go into the commit. Other changes, staged or not, stay as they are.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use git2::Repository;
+
+use super::local::{is_stdin, is_url, path_in_repo};
+
+/// Commits the local translation files on top of `HEAD` of their repository with the message.
+/// Nothing is committed if the files are unchanged.
+pub fn commit_translation_files(files: &[PathBuf], message: &str) -> Result<()> {
+    let files: Vec<_> = files
+        .iter()
+        .filter(|file| !is_stdin(file) && !is_url(file))
+        .collect();
+    let first = match files.first() {
+        Some(first) => first,
+        None => {
+            log::warn!("Not committing because no translation file is stored locally.");
+            return Ok(());
+        }
+    };
+    let repo = Repository::discover(first).context("Failed to discover git repository.")?;
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .context("Failed to find the commit of HEAD.")?;
+
+    let mut index = repo.index()?;
+    let mut tree = git2::build::TreeUpdateBuilder::new();
+    for file in &files {
+        let path = path_in_repo(&repo, file);
+        // Staging applies the clean filters of `.gitattributes`, e.g. to line endings.
+        index
+            .add_path(&path)
+            .with_context(|| format!("Failed to stage {}", file.display()))?;
+        let entry = index
+            .get_path(&path, 0)
+            .with_context(|| format!("Failed to stage {}", file.display()))?;
+        tree.upsert(
+            path.to_string_lossy().as_ref(),
+            entry.id,
+            git2::FileMode::Blob,
+        );
+    }
+    let tree = repo.find_tree(tree.create_updated(&repo, &head.tree()?)?)?;
+    if tree.id() == head.tree_id() {
+        log::info!("Not committing because the translation files are unchanged.");
+        return Ok(());
+    }
+    index.write()?;
+
+    let signature = repo
+        .signature()
+        .context("Failed to find the author of the commit, set user.name and user.email.")?;
+    let id = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &[&head],
+    )?;
+    log::info!("Committed the translation files as {}.", id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn commit_only_translation_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let signature = repo.signature().unwrap();
+        let empty = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "Start", &empty, &[])
+            .unwrap();
+
+        let en = dir.path().join("en.json");
+        fs::write(&en, "{ \"title\": \"Title\" }").unwrap();
+        fs::write(dir.path().join("other.txt"), "not committed").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path("other.txt".as_ref()).unwrap();
+        index.write().unwrap();
+
+        let files = [en, PathBuf::from("-")];
+        commit_translation_files(&files, "Update translations").unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(Some("Update translations"), head.message());
+        let tree = head.tree().unwrap();
+        assert!(tree.get_name("en.json").is_some());
+        assert!(tree.get_name("other.txt").is_none());
+
+        commit_translation_files(&files, "Nothing changed").unwrap();
+        assert_eq!(
+            head.id(),
+            repo.head().unwrap().peel_to_commit().unwrap().id()
+        );
+    }
+}
@@ -1,13 +1,18 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 use anyhow::Result;
 use itertools::{merge_join_by, EitherOrBoth, Itertools};
 use traduora::api::TermId;
 
-use super::{local, remote};
+use super::{cache, local, remote};
+use crate::config::{AppConfig, TermPattern};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Modification {
     Removed(TermId),
-    Updated(TermId),
+    /// Id of the term and its current translation on Traduora.
+    Updated(TermId, String),
     Added,
 }
 
@@ -34,28 +39,63 @@ impl Translation {
             modification: Modification::Removed(term_id),
         }
     }
-    pub fn updated(term: String, translation: String, term_id: TermId) -> Self {
+    pub fn updated(term: String, translation: String, term_id: TermId, previous: String) -> Self {
         Self {
             term,
             translation,
-            modification: Modification::Updated(term_id),
+            modification: Modification::Updated(term_id, previous),
         }
     }
 }
 
-fn merge(
+/// A term whose translation is identical locally and on Traduora.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnchangedTerm {
+    pub term_id: TermId,
+    pub term: String,
+    pub translation: String,
+}
+
+/// Determines the modifications needed to bring Traduora up to date with the local translations.
+///
+/// Terms matching one of the `ignored` patterns are left out entirely.
+/// If `unchanged` is given, terms that need no modification are collected there.
+pub(super) fn merge(
     mut local: Vec<local::Translation>,
     mut remote: Vec<remote::Translation>,
     mut git: Vec<local::Translation>,
+    ignored: &[TermPattern],
+    mut unchanged: Option<&mut Vec<UnchangedTerm>>,
 ) -> Vec<Translation> {
+    if !ignored.is_empty() {
+        let kept = |term: &str| !ignored.iter().any(|p| p.matches(term));
+        local.retain(|l| kept(&l.term));
+        remote.retain(|r| kept(&r.term));
+        git.retain(|g| kept(&g.term));
+    }
     local.sort_unstable_by(local::Translation::cmp_by_term);
     remote.sort_unstable_by(remote::Translation::cmp_by_term);
     git.sort_unstable_by(local::Translation::cmp_by_term);
     merge_join_by(local, remote, |l, r| l.term.cmp(&r.term))
         .filter_map(|e| match e {
-            EitherOrBoth::Both(local, remote) => (local.translation != remote.translation
-                && !local.translation.is_empty())
-            .then(|| Translation::updated(local.term, local.translation, remote.term_id)),
+            EitherOrBoth::Both(local, remote) if local.translation == remote.translation => {
+                if let Some(unchanged) = unchanged.as_mut() {
+                    unchanged.push(UnchangedTerm {
+                        term_id: remote.term_id,
+                        term: remote.term,
+                        translation: remote.translation,
+                    });
+                }
+                None
+            }
+            EitherOrBoth::Both(local, remote) => (!local.translation.is_empty()).then(|| {
+                Translation::updated(
+                    local.term,
+                    local.translation,
+                    remote.term_id,
+                    remote.translation,
+                )
+            }),
             EitherOrBoth::Left(local) => Some(Translation::added(local.term, local.translation)),
             EitherOrBoth::Right(remote) => Some(Translation::removed(
                 remote.term,
@@ -81,7 +121,7 @@ fn merge(
                     | Modification::Added
                     // Translations differ in Traduora and locally but git is same as local -> translation changed elsewhere
                     // Translations differ in Traduora and locally but git is different than local -> translation changed locally
-                    | Modification::Updated(_) => (t.translation != g.translation).then(|| t),
+                    | Modification::Updated(..) => (t.translation != g.translation).then(|| t),
                 },
                 // term does not exist in git but was not removed, git is too old to know term -> no git data to double check with
                 EitherOrBoth::Left(t) => Some(t),
@@ -91,30 +131,135 @@ fn merge(
         .collect()
 }
 
-pub fn load_data() -> Result<Vec<Translation>> {
-    let translation_file = crate::config::get().translation_file();
-    let revision = crate::config::get().revision();
+/// Number of terms in the local file and on Traduora, regardless of any modification.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Totals {
+    pub local_terms: usize,
+    pub remote_terms: usize,
+    /// Terms on Traduora that have a non-empty translation for the locale.
+    pub remote_translated: usize,
+}
+
+impl Totals {
+    /// Share of the terms on Traduora that are translated, in percent.
+    pub fn completion(&self) -> Option<f64> {
+        (self.remote_terms > 0)
+            .then(|| self.remote_translated as f64 * 100. / self.remote_terms as f64)
+    }
+}
 
-    let local = local::load_from_file(translation_file)?;
-    let remote = remote::fetch_from_traduora()?;
+#[derive(Debug, Clone, Default)]
+pub struct LoadedData {
+    pub translations: Vec<Translation>,
+    pub unchanged: Vec<UnchangedTerm>,
+    /// Metadata of every term on Traduora, shown for reference.
+    pub metadata: HashMap<TermId, remote::TermMetadata>,
+    pub totals: Totals,
+    /// Whether Traduora was unreachable and the remote data was taken from the cache instead.
+    pub offline: bool,
+    pub destination: Option<remote::Destination>,
+    /// Translation files with changes that are not committed, which are probably unfinished.
+    pub uncommitted: Vec<PathBuf>,
+}
+
+pub(super) fn fetch_remote(
+    config: &AppConfig,
+) -> Result<(Vec<remote::Translation>, Option<remote::Destination>)> {
+    match remote::fetch_from_traduora(config) {
+        Ok((remote, destination)) => {
+            if let Err(e) = cache::store(config, &remote) {
+                log::warn!("Failed to cache data from Traduora: {:?}", e);
+            }
+            Ok((remote, Some(destination)))
+        }
+        // Only an unreachable server is bridged, errors like a rejected login must be fixed.
+        Err(e) if !crate::transport::is_unreachable(&e) => Err(e),
+        Err(e) => match cache::load(config) {
+            Ok(cached) => {
+                log::warn!(
+                    "Failed to fetch data from Traduora. Falling back to cached data. Error: {:?}",
+                    e
+                );
+                Ok((cached, None))
+            }
+            Err(cache_err) => {
+                log::info!("No usable cached data from Traduora: {:?}", cache_err);
+                Err(e)
+            }
+        },
+    }
+}
+
+pub fn load_data(config: &AppConfig) -> Result<LoadedData> {
+    let translation_file = config.translation_file();
+    let revision = config.revision();
+
+    let local = local::load_from_files(config, config.translation_files())?;
+    let (mut remote, destination) = fetch_remote(config)?;
+    if let Some(i18next) = config.i18next() {
+        // Terms of other namespaces belong to other translation files.
+        remote.retain(|r| i18next.key(&r.term).is_some());
+    }
     let git = if revision.is_empty() {
         Vec::new()
+    } else if local::is_stdin(translation_file) {
+        log::warn!("Skipping sanity checks because the translation file is read from stdin.");
+        Vec::new()
+    } else if config.translation_files().iter().any(local::is_url) {
+        log::warn!("Skipping sanity checks because the translation file is downloaded.");
+        Vec::new()
     } else {
-        local::load_from_git(revision, translation_file)?
+        local::load_from_git_files(config, revision, config.translation_files())?
+    };
+    let metadata = remote
+        .iter()
+        .map(|r| (r.term_id.clone(), r.metadata.clone()))
+        .collect();
+    let totals = Totals {
+        local_terms: local.len(),
+        remote_terms: remote.len(),
+        remote_translated: remote.iter().filter(|r| !r.translation.is_empty()).count(),
     };
-    Ok(merge(local, remote, git))
+    let mut unchanged = Vec::new();
+    Ok(LoadedData {
+        translations: merge(
+            local,
+            remote,
+            git,
+            config.ignore_terms(),
+            Some(&mut unchanged),
+        ),
+        unchanged,
+        metadata,
+        totals,
+        offline: destination.is_none(),
+        destination,
+        uncommitted: local::uncommitted_files(config.translation_files()),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn completion_of_remote_terms() {
+        let totals = Totals {
+            local_terms: 10,
+            remote_terms: 8,
+            remote_translated: 6,
+        };
+        assert_eq!(Some(75.), totals.completion());
+        assert_eq!(None, Totals::default().completion());
+    }
+
     #[test]
     fn do_not_remove_text_for_translation_update() {
         let remote = vec![remote::Translation {
             term_id: "example-id".into(),
             term: "foo.bar.baz".into(),
             translation: "hello world".into(),
+            metadata: Default::default(),
         }];
         let local = vec![local::Translation {
             term: "foo.bar.baz".into(),
@@ -122,7 +267,7 @@ mod tests {
         }];
         let git = Vec::new();
 
-        let result = merge(local, remote, git);
+        let result = merge(local, remote, git, &[], None);
 
         const EXPECTED: &[Translation] = &[];
         assert_eq!(EXPECTED, result);
@@ -140,7 +285,7 @@ mod tests {
             translation: "Hello world!".into(),
         }];
 
-        let result = merge(local, remote, git);
+        let result = merge(local, remote, git, &[], None);
 
         assert_eq!(1, result.len());
         assert_eq!("foo.bar.baz", result[0].term);
@@ -148,12 +293,87 @@ mod tests {
         assert_eq!(Modification::Added, result[0].modification);
     }
 
+    #[test]
+    fn collect_unchanged_terms() {
+        let remote = vec![
+            remote::Translation {
+                term_id: "same-id".into(),
+                term: "foo.same".into(),
+                translation: "hello world".into(),
+                metadata: Default::default(),
+            },
+            remote::Translation {
+                term_id: "changed-id".into(),
+                term: "foo.changed".into(),
+                translation: "hello world".into(),
+                metadata: Default::default(),
+            },
+        ];
+        let local = vec![
+            local::Translation {
+                term: "foo.same".into(),
+                translation: "hello world".into(),
+            },
+            local::Translation {
+                term: "foo.changed".into(),
+                translation: "Hello World!".into(),
+            },
+        ];
+        let mut unchanged = Vec::new();
+
+        let result = merge(local, remote, Vec::new(), &[], Some(&mut unchanged));
+
+        assert_eq!(1, result.len());
+        assert_eq!("foo.changed", result[0].term);
+        assert_eq!(
+            vec![UnchangedTerm {
+                term_id: "same-id".into(),
+                term: "foo.same".into(),
+                translation: "hello world".into(),
+            }],
+            unchanged
+        );
+    }
+
+    #[test]
+    fn drop_ignored_terms() {
+        let remote = vec![remote::Translation {
+            term_id: "vendor-id".into(),
+            term: "vendor.widget.title".into(),
+            translation: "Widget".into(),
+            metadata: Default::default(),
+        }];
+        let local = vec![
+            local::Translation {
+                term: "generated_1234".into(),
+                translation: "Hello".into(),
+            },
+            local::Translation {
+                term: "app.title".into(),
+                translation: "App".into(),
+            },
+        ];
+        let ignored = [
+            TermPattern::try_from("vendor.*".to_string()).unwrap(),
+            TermPattern::try_from(r"/^generated_\d+$/".to_string()).unwrap(),
+        ];
+
+        let result = merge(local, remote, Vec::new(), &ignored, None);
+
+        assert_eq!(
+            vec![Translation::added("app.title".into(), "App".into())],
+            result
+        );
+        assert!(TermPattern::try_from("/(/".to_string()).is_err());
+    }
+
     #[test]
     fn update_translation_text() {
         let remote = vec![remote::Translation {
             term_id: "example-id".into(),
             term: "foo.bar.baz".into(),
             translation: "hello world".into(),
+            metadata: Default::default(),
         }];
         let local = vec![local::Translation {
             term: "foo.bar.baz".into(),
@@ -161,13 +381,13 @@ mod tests {
         }];
         let git = Vec::new();
 
-        let result = merge(local, remote, git);
+        let result = merge(local, remote, git, &[], None);
 
         assert_eq!(1, result.len());
         assert_eq!("foo.bar.baz", result[0].term);
         assert_eq!("It's a me, mario.", result[0].translation);
         assert_eq!(
-            Modification::Updated("example-id".into()),
+            Modification::Updated("example-id".into(), "hello world".into()),
             result[0].modification
         );
     }
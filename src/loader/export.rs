@@ -0,0 +1,53 @@
+//! Writes the translations on Traduora to the local translation files.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use super::{local, remote};
+use crate::config::AppConfig;
+
+/// Writes the terms and translations on Traduora to the translation files at `paths`. With
+/// several files, a term is written to the first file that contains it already and the terms
+/// that none contains to the first file. Returns the number of written terms.
+pub fn export_to_files(config: &AppConfig, paths: &[PathBuf]) -> Result<usize> {
+    if let Some(url) = paths.iter().find(|path| local::is_url(path)) {
+        anyhow::bail!(
+            "Cannot export to {}, translation files are only written to local paths.",
+            url.display()
+        );
+    }
+    let (remote, _) = remote::fetch_from_traduora(config)?;
+    let translations: Vec<_> = remote
+        .into_iter()
+        .filter(|r| config.i18next().map_or(true, |i| i.key(&r.term).is_some()))
+        .map(|r| local::Translation::new(r.term, r.translation))
+        .collect();
+    let count = translations.len();
+
+    let mut existing = Vec::with_capacity(paths.len());
+    for path in paths.iter().skip(1) {
+        let terms: HashSet<_> = if path.exists() {
+            local::load_from_file(config, path)?
+                .into_iter()
+                .map(|t| t.term)
+                .collect()
+        } else {
+            Default::default()
+        };
+        existing.push(terms);
+    }
+    let mut per_file = vec![Vec::new(); paths.len()];
+    for translation in translations {
+        let index = existing
+            .iter()
+            .position(|terms| terms.contains(&translation.term))
+            .map_or(0, |i| i + 1);
+        per_file[index].push(translation);
+    }
+    for (path, translations) in paths.iter().zip(per_file) {
+        local::save_to_file(config, path, &translations)?;
+    }
+    Ok(count)
+}
@@ -0,0 +1,182 @@
+//! The filters that git applies to a file when checking it out, as set up in `.gitattributes`
+//! and the git config. A file read from a git revision gets them as well, so it matches the file
+//! in the working tree, e.g. with CRLF line endings on Windows.
+//!
+//! Line endings (`text`, `eol`, `crlf`, `core.autocrlf` and `core.eol`) and `ident` are applied.
+//! Filter drivers like Git LFS run external commands and are not applied.
+
+use std::path::Path;
+
+use anyhow::Result;
+use git2::{AttrCheckFlags, AttrValue, Blob, Repository};
+
+/// Whether the content is text whose line endings are converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Text {
+    /// Set with `text` or `crlf`.
+    Set,
+    /// Unset with `-text`, `binary` or `-crlf`.
+    Unset,
+    /// Decided by the content with `text=auto` or `core.autocrlf`.
+    Auto,
+    Unspecified,
+}
+
+/// Line endings of the working tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Eol {
+    Lf,
+    Crlf,
+}
+
+/// The content of the blob at `path`, which is relative to the root of the repository, as it is
+/// checked out.
+pub fn smudge(repo: &Repository, path: &Path, blob: &Blob) -> Result<Vec<u8>> {
+    // The values must not be copied, set and unset attributes are told apart by their address.
+    let attribute = |name| -> Result<AttrValue> {
+        Ok(AttrValue::from_string(repo.get_attr(
+            path,
+            name,
+            AttrCheckFlags::default(),
+        )?))
+    };
+    let config = repo.config()?;
+    let autocrlf = match config.get_bool("core.autocrlf") {
+        Ok(autocrlf) => Some(autocrlf.to_string()),
+        Err(_) => config.get_string("core.autocrlf").ok(),
+    };
+    let core_eol = config.get_string("core.eol").ok();
+
+    let mut content = blob.content().to_vec();
+    if attribute("ident")? == AttrValue::True {
+        let id = format!("$Id: {} $", blob.id());
+        content = replace(&content, b"$Id$", id.as_bytes());
+    }
+    let eol = line_endings(
+        attribute("text")?,
+        attribute("eol")?,
+        attribute("crlf")?,
+        autocrlf.as_deref(),
+        core_eol.as_deref(),
+        &content,
+    );
+    if eol == Some(Eol::Crlf) {
+        content = to_crlf(&content);
+    }
+    if let AttrValue::String(driver) = attribute("filter")? {
+        log::warn!(
+            "Filter {:?} of {} is not applied to the file of the git revision.",
+            driver,
+            path.display()
+        );
+    }
+    Ok(content)
+}
+
+/// The line endings the content gets in the working tree, `None` if it is kept as it is.
+fn line_endings(
+    text: AttrValue,
+    eol: AttrValue,
+    crlf: AttrValue,
+    autocrlf: Option<&str>,
+    core_eol: Option<&str>,
+    content: &[u8],
+) -> Option<Eol> {
+    let eol = match eol {
+        AttrValue::String("crlf") => Some(Eol::Crlf),
+        AttrValue::String("lf") => Some(Eol::Lf),
+        _ => None,
+    };
+    let mut state = match (text, crlf) {
+        (AttrValue::True, _) => Text::Set,
+        (AttrValue::False, _) => Text::Unset,
+        (AttrValue::String("auto"), _) => Text::Auto,
+        (AttrValue::Unspecified, AttrValue::True) => Text::Set,
+        (AttrValue::Unspecified, AttrValue::False) => Text::Unset,
+        // `crlf=input` is the old way to write `eol=lf`.
+        (AttrValue::Unspecified, AttrValue::String("input")) => return Some(Eol::Lf),
+        _ if eol.is_some() => Text::Set,
+        _ => Text::Unspecified,
+    };
+    if state == Text::Unspecified && autocrlf.map_or(false, |a| a == "true" || a == "input") {
+        state = Text::Auto;
+    }
+    match state {
+        Text::Unset | Text::Unspecified => return None,
+        // Binary files and files with carriage returns already are left alone.
+        Text::Auto if content.contains(&0) || content.contains(&b'\r') => return None,
+        Text::Set | Text::Auto => {}
+    }
+    let configured = match (autocrlf, core_eol) {
+        (Some("true"), _) => Eol::Crlf,
+        (Some("input"), _) => Eol::Lf,
+        (_, Some("crlf")) => Eol::Crlf,
+        (_, Some("lf")) => Eol::Lf,
+        _ if cfg!(windows) => Eol::Crlf,
+        _ => Eol::Lf,
+    };
+    Some(eol.unwrap_or(configured))
+}
+
+/// Turns line feeds that have no carriage return before them into CRLF.
+fn to_crlf(content: &[u8]) -> Vec<u8> {
+    let mut converted = Vec::with_capacity(content.len() + content.len() / 32);
+    for (i, &byte) in content.iter().enumerate() {
+        if byte == b'\n' && (i == 0 || content[i - 1] != b'\r') {
+            converted.push(b'\r');
+        }
+        converted.push(byte);
+    }
+    converted
+}
+
+fn replace(content: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+    let mut replaced = Vec::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(i) = rest.windows(from.len()).position(|window| window == from) {
+        replaced.extend_from_slice(&rest[..i]);
+        replaced.extend_from_slice(to);
+        rest = &rest[i + from.len()..];
+    }
+    replaced.extend_from_slice(rest);
+    replaced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_line_endings_and_ident() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(
+            dir.path().join(".gitattributes"),
+            "*.json text eol=crlf\n*.properties ident\n*.bin -text\n",
+        )
+        .unwrap();
+        let blob = |content: &[u8]| repo.find_blob(repo.blob(content).unwrap()).unwrap();
+        let smudged = |path: &str, blob: &Blob| smudge(&repo, Path::new(path), blob).unwrap();
+
+        let json = blob(b"{\n  \"a\": \"1\\n2\"\r\n}\n");
+        assert_eq!(
+            b"{\r\n  \"a\": \"1\\n2\"\r\n}\r\n".to_vec(),
+            smudged("i18n/en.json", &json)
+        );
+        let properties = blob(b"# $Id$\na=1\n");
+        assert_eq!(
+            format!("# $Id: {} $\na=1\n", properties.id()).into_bytes(),
+            smudged("messages.properties", &properties)
+        );
+
+        repo.config()
+            .unwrap()
+            .set_str("core.autocrlf", "true")
+            .unwrap();
+        let other = blob(b"a\nb\n");
+        assert_eq!(b"a\r\nb\r\n".to_vec(), smudged("other.txt", &other));
+        assert_eq!(b"a\nb\n".to_vec(), smudged("data.bin", &other));
+        let binary = blob(b"a\0\nb\n");
+        assert_eq!(b"a\0\nb\n".to_vec(), smudged("other.txt", &binary));
+    }
+}
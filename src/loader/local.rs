@@ -1,7 +1,16 @@
-use std::{fs, path::Path};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
 
 use anyhow::{Context, Result};
-use serde::{de::Visitor, Deserialize};
+use encoding_rs::Encoding;
+use once_cell::sync::{Lazy, OnceCell};
+
+use super::{filters, properties, qt, writer};
+use crate::config::{AppConfig, NonStringValues};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Translation {
@@ -19,18 +28,75 @@ impl Translation {
     }
 }
 
-pub fn load_from_file<P>(path: P) -> Result<Vec<Translation>>
+/// Returns `true` if the path is `-`, i.e. the translation file is read from stdin.
+pub fn is_stdin<P>(path: P) -> bool
 where
     P: AsRef<Path>,
 {
+    path.as_ref() == Path::new("-")
+}
+
+/// Returns `true` if the path is an HTTP or HTTPS URL, i.e. the translation file is downloaded.
+pub fn is_url<P>(path: P) -> bool
+where
+    P: AsRef<Path>,
+{
+    path.as_ref().to_str().map_or(false, |path| {
+        path.starts_with("http://") || path.starts_with("https://")
+    })
+}
+
+/// Downloads the translation file with the same connection settings as the requests to Traduora.
+fn download(config: &AppConfig, url: &str) -> Result<Vec<u8>> {
+    let response = crate::transport::client(config)?
+        .get(url)
+        .send()
+        .and_then(reqwest::blocking::Response::error_for_status)
+        .with_context(|| format!("Failed to download {}", url))?;
+    let data = response
+        .bytes()
+        .with_context(|| format!("Failed to download {}", url))?;
+    Ok(data.to_vec())
+}
+
+/// Reads stdin once and keeps the content because the data is loaded again after every update.
+fn read_stdin() -> Result<&'static [u8]> {
+    use std::io::Read;
+    static STDIN: OnceCell<Vec<u8>> = OnceCell::new();
+
+    STDIN
+        .get_or_try_init(|| {
+            let mut data = Vec::new();
+            std::io::stdin().read_to_end(&mut data)?;
+            Ok(data)
+        })
+        .map(Vec::as_slice)
+}
+
+pub fn load_from_file<P>(config: &AppConfig, path: P) -> Result<Vec<Translation>>
+where
+    P: AsRef<Path>,
+{
+    if is_stdin(&path) {
+        let data = read_stdin().context("Failed to read translation file from stdin")?;
+        return parse_file(config, path.as_ref(), data, config.encoding_local())
+            .context("Failed to load translation file from stdin");
+    }
+    if is_url(&path) {
+        let url = path.as_ref().display().to_string();
+        let data = download(config, &url)?;
+        return parse_file(config, path.as_ref(), &data, config.encoding_local())
+            .with_context(|| format!("Failed to load translation file from {}", url));
+    }
+
     let data = fs::read(&path)
         .with_context(|| format!("Failed to open file {}", path.as_ref().display()))?;
 
-    parse(&data, Source::Local)
+    parse_file(config, path.as_ref(), &data, config.encoding_local())
         .with_context(|| format!("Failed to load file {}", path.as_ref().display()))
 }
 
-pub fn load_from_git<P>(revision: &str, path: P) -> Result<Vec<Translation>>
+pub fn load_from_git<P>(config: &AppConfig, revision: &str, path: P) -> Result<Vec<Translation>>
 where
     P: AsRef<Path>,
 {
@@ -38,17 +104,25 @@ where
 
     let fun = || -> Result<Vec<Translation>> {
         let repo = Repository::discover(&path).context("Failed to discover git repository.")?;
+        if config.fetch_revision() {
+            fetch_once(&repo, revision)?;
+        }
 
-        let revision = repo
+        let mut revision = repo
             .revparse_single(revision)
             .with_context(|| format!("Failed to find revision {:?}.", revision))?;
+        if config.merge_base() {
+            revision = merge_base(&repo, &revision)?;
+        }
 
+        let path_in_repo = path_in_repo(&repo, path.as_ref());
         let blob = revision
             .peel_to_tree()?
-            .get_path(path.as_ref())?
+            .get_path(&path_in_repo)?
             .to_object(&repo)?
             .peel_to_blob()?;
-        parse(blob.content(), Source::Git)
+        let content = filters::smudge(&repo, &path_in_repo, &blob)?;
+        parse_file(config, path.as_ref(), &content, config.encoding_git())
     };
 
     fun().with_context(|| {
@@ -60,68 +134,605 @@ where
     })
 }
 
-enum Source {
-    Git,
-    Local,
+/// The local translation files that differ from `HEAD` of their repository or are not committed
+/// at all, e.g. in the middle of editing them. Files outside of a repository are left out.
+pub fn uncommitted_files(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let is_uncommitted = |path: &Path| -> Result<bool> {
+        let repo = git2::Repository::discover(path)?;
+        let status = repo.status_file(&path_in_repo(&repo, path))?;
+        Ok(!status.is_empty() && !status.contains(git2::Status::IGNORED))
+    };
+    paths
+        .iter()
+        .filter(|path| !is_stdin(path) && !is_url(path))
+        .filter(|path| match is_uncommitted(path) {
+            Ok(uncommitted) => uncommitted,
+            Err(e) => {
+                log::debug!("Failed to find git status of {}: {}", path.display(), e);
+                false
+            }
+        })
+        .cloned()
+        .collect()
 }
 
-fn parse(data: &[u8], src: Source) -> Result<Vec<Translation>> {
-    use json_comments::StripComments;
-    let enc = guess_encoding(data, src);
-    let (data, encountered_malformeds) = enc.decode_with_bom_removal(data);
+/// Fetches the remote of the revision like [`fetch`], but only once per repository and run
+/// because the data is loaded again after every update.
+fn fetch_once(repo: &git2::Repository, revision: &str) -> Result<()> {
+    static FETCHED: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(Default::default);
 
-    if encountered_malformeds {
-        log::warn!("Replaced some malformed characters in translation file.");
+    let mut fetched = FETCHED.lock().unwrap_or_else(|e| e.into_inner());
+    if !fetched.contains(repo.path()) {
+        fetch(repo, revision)?;
+        fetched.insert(repo.path().to_owned());
     }
+    Ok(())
+}
+
+/// Fetches the remote that the revision belongs to, like `git fetch` does. Credentials come from
+/// the ssh-agent or the git credential helper.
+fn fetch(repo: &git2::Repository, revision: &str) -> Result<()> {
+    use git2::{Cred, CredentialType};
 
-    let data = StripComments::new(data.as_bytes());
+    let remotes = repo.remotes()?;
+    let names: Vec<_> = remotes.iter().flatten().collect();
+    let name = match remote_of(revision, &names) {
+        Some(name) => name,
+        None => {
+            log::warn!(
+                "Not fetching revision {:?} because there is no remote.",
+                revision
+            );
+            return Ok(());
+        }
+    };
+    let mut remote = repo.find_remote(name)?;
+    let git_config = repo.config()?;
 
-    let result: DeserializationHelper =
-        serde_json::from_reader(data).context("Failed to parse translation file")?;
-    Ok(result.0)
+    // Credentials that are rejected are offered again, so give up after a few attempts.
+    let mut attempts = 0;
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.credentials(|url, username, allowed| {
+        attempts += 1;
+        if attempts > 3 {
+            Err(git2::Error::from_str("Authentication failed."))
+        } else if allowed.contains(CredentialType::SSH_KEY) {
+            Cred::ssh_key_from_agent(username.unwrap_or("git"))
+        } else if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            Cred::credential_helper(&git_config, url, username)
+        } else {
+            Cred::default()
+        }
+    });
+    let mut options = git2::FetchOptions::new();
+    options.remote_callbacks(callbacks);
+    log::info!("Fetching remote {:?} for revision {:?}.", name, revision);
+    remote
+        .fetch(&[] as &[&str], Some(&mut options), None)
+        .with_context(|| format!("Failed to fetch remote {:?}.", name))
 }
 
-fn guess_encoding(data: &[u8], src: Source) -> &'static encoding_rs::Encoding {
-    use encoding_rs::Encoding;
-    let config = crate::config::get();
-    match src {
-        Source::Git => config.encoding_git(),
-        Source::Local => config.encoding_local(),
-    }
-    .unwrap_or_else(|| Encoding::for_bom(data).map_or(encoding_rs::UTF_8, |x| x.0))
+/// The remote whose branch the revision names, e.g. `origin` for `origin/main`, or else `origin`
+/// like `git fetch` without arguments.
+fn remote_of<'a>(revision: &str, remotes: &[&'a str]) -> Option<&'a str> {
+    let branch = revision.strip_prefix("refs/remotes/").unwrap_or(revision);
+    remotes
+        .iter()
+        .copied()
+        .filter(|remote| {
+            branch
+                .strip_prefix(remote)
+                .map_or(false, |rest| rest.starts_with('/'))
+        })
+        .max_by_key(|remote| remote.len())
+        .or_else(|| remotes.iter().copied().find(|&remote| remote == "origin"))
 }
 
-struct DeserializationHelper(Vec<Translation>);
+/// The commit where `HEAD` branched off from the revision, like `git merge-base HEAD <revision>`.
+fn merge_base<'r>(repo: &'r git2::Repository, revision: &git2::Object) -> Result<git2::Object<'r>> {
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .context("Failed to find the commit of HEAD.")?;
+    let base = repo
+        .merge_base(head.id(), revision.peel_to_commit()?.id())
+        .context("HEAD and the revision have no common ancestor.")?;
+    log::debug!("Comparing with merge base {}.", base);
+    Ok(repo.find_object(base, None)?)
+}
 
-impl<'de> Deserialize<'de> for DeserializationHelper {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct TranslationVisitor;
+/// Reads the translation files and merges them, e.g. the files of several feature modules.
+pub fn load_from_files(config: &AppConfig, paths: &[PathBuf]) -> Result<Vec<Translation>> {
+    merge_files(
+        paths
+            .iter()
+            .map(|path| Ok((path.as_path(), load_from_file(config, path)?))),
+    )
+}
 
-        impl<'de> Visitor<'de> for TranslationVisitor {
-            type Value = DeserializationHelper;
+/// Reads the translation files of the git revision and merges them like [`load_from_files`].
+pub fn load_from_git_files(
+    config: &AppConfig,
+    revision: &str,
+    paths: &[PathBuf],
+) -> Result<Vec<Translation>> {
+    merge_files(
+        paths
+            .iter()
+            .map(|path| Ok((path.as_path(), load_from_git(config, revision, path)?))),
+    )
+}
 
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("a map of terms and translations")
+/// Concatenates the translations of the files. A term that is in several files must have the
+/// same translation everywhere, otherwise it is unclear which one Traduora should get.
+fn merge_files<'a>(
+    files: impl IntoIterator<Item = Result<(&'a Path, Vec<Translation>)>>,
+) -> Result<Vec<Translation>> {
+    let mut merged: Vec<Translation> = Vec::new();
+    let mut origins = std::collections::HashMap::new();
+    let mut conflicts = Vec::new();
+    for file in files {
+        let (path, translations) = file?;
+        for translation in translations {
+            match origins.get(&translation.term) {
+                Some(&(origin, i)) if merged[i] == translation => log::warn!(
+                    "Term {:?} is in both {} and {}.",
+                    translation.term,
+                    Path::display(origin),
+                    path.display()
+                ),
+                Some(&(origin, _)) => conflicts.push(format!(
+                    "Term {:?} has different translations in {} and {}.",
+                    translation.term,
+                    Path::display(origin),
+                    path.display()
+                )),
+                None => {
+                    origins.insert(translation.term.clone(), (path, merged.len()));
+                    merged.push(translation);
+                }
             }
+        }
+    }
+    anyhow::ensure!(conflicts.is_empty(), "{}", conflicts.join("\n"));
+    Ok(merged)
+}
 
-            fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
-            where
-                M: serde::de::MapAccess<'de>,
+/// Path of the file relative to the root of the repository, as the trees of git name it.
+/// Paths that cannot be resolved are used as they are.
+pub(super) fn path_in_repo(repo: &git2::Repository, path: &Path) -> PathBuf {
+    let absolute = || -> Option<PathBuf> {
+        // The file may be missing locally, but its directory exists.
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+        let dir = dir.unwrap_or_else(|| Path::new(".")).canonicalize().ok()?;
+        Some(dir.join(path.file_name()?))
+    };
+    let workdir = repo.workdir().and_then(|dir| dir.canonicalize().ok());
+    match (absolute(), workdir) {
+        (Some(absolute), Some(workdir)) => absolute
+            .strip_prefix(workdir)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Writes the translations to the file in the format that is read by [`load_from_file`]. An
+/// existing file keeps its layout, see [`writer`].
+pub fn save_to_file<P>(config: &AppConfig, path: P, translations: &[Translation]) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    anyhow::ensure!(
+        !qt::is_ts(&path),
+        "Writing Qt Linguist files is not supported, export to a JSON file instead."
+    );
+    let existing = if !is_stdin(&path) && path.as_ref().is_file() {
+        let data = fs::read(&path)
+            .with_context(|| format!("Failed to open file {}", path.as_ref().display()))?;
+        Some(data).filter(|data| !data.is_empty())
+    } else {
+        None
+    };
+    let keys: Vec<_>;
+    let (translations, nested) = match config.i18next() {
+        Some(i18next) if !properties::is_properties(&path) => {
+            keys = translations
+                .iter()
+                .filter_map(|t| {
+                    let key = i18next.key(&t.term)?;
+                    Some(Translation::new(key.to_owned(), t.translation.clone()))
+                })
+                .collect();
+            (&keys[..], true)
+        }
+        _ => (translations, config.nested_json()),
+    };
+    let data = match existing {
+        Some(existing) => writer::write(
+            path.as_ref(),
+            &existing,
+            config.encoding_local(),
+            translations,
+            nested,
+            config.non_string_values(),
+            config.key_separator(),
+        )?,
+        None if properties::is_properties(&path) => {
+            properties::serialize(translations).into_bytes()
+        }
+        None => {
+            let encoding = config.encoding_local().unwrap_or(encoding_rs::UTF_8);
+            serialize(translations, encoding, nested, config.key_separator())?
+        }
+    };
+    if is_stdin(&path) {
+        use std::io::Write;
+        return std::io::stdout()
+            .write_all(&data)
+            .context("Failed to write translations to stdout");
+    }
+    crate::atomic::write(path, data)
+}
+
+/// Encodes the translations as JSON object sorted by term, nested at the `separator` of the
+/// terms if `nested` is set. UTF-16 is written with a byte order mark, so the encoding is detected when
+/// reading the file again.
+fn serialize(
+    translations: &[Translation],
+    encoding: &'static encoding_rs::Encoding,
+    nested: bool,
+    separator: &str,
+) -> Result<Vec<u8>> {
+    let object: std::collections::BTreeMap<_, _> = translations
+        .iter()
+        .map(|t| (&t.term, &t.translation))
+        .collect();
+    let json = if nested {
+        serde_json::to_string_pretty(&nest(object, separator)?)
+    } else {
+        serde_json::to_string_pretty(&object)
+    }
+    .context("Failed to serialize translations")?
+        + "\n";
+    writer::encode(&json, encoding, false)
+}
+
+/// Nests the terms at the separator like the JSON-nested export of Traduora does at dots, e.g.
+/// `app.title` becomes `{ "app": { "title": ... } }`.
+fn nest(
+    translations: std::collections::BTreeMap<&String, &String>,
+    separator: &str,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    use serde_json::{Map, Value};
+
+    let mut root = Map::new();
+    for (term, translation) in translations {
+        let mut parts: Vec<_> = term.split(separator).collect();
+        let leaf = parts.pop().unwrap_or_default();
+        let mut object = &mut root;
+        for part in parts {
+            object = match object
+                .entry(part)
+                .or_insert_with(|| Value::Object(Map::new()))
             {
-                let mut data = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                Value::Object(child) => child,
+                _ => anyhow::bail!(
+                    "Term {:?} cannot be nested because a prefix of it is a term as well.",
+                    term
+                ),
+            };
+        }
+        anyhow::ensure!(
+            !object.contains_key(leaf),
+            "Term {:?} cannot be nested because it is the prefix of other terms.",
+            term
+        );
+        object.insert(leaf.to_owned(), Value::String(translation.clone()));
+    }
+    Ok(root)
+}
+
+/// Turns nested objects into terms joined with the separator, the reverse of [`nest`]. Values
+/// that are no strings are handled as configured by `non_strings`.
+fn flatten(
+    prefix: &str,
+    value: serde_json::Value,
+    separator: &str,
+    non_strings: NonStringValues,
+    data: &mut Vec<Translation>,
+) -> Result<()> {
+    use serde_json::Value;
+
+    match (value, non_strings) {
+        (Value::Object(object), _) => {
+            for (key, value) in object {
+                let term = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{}{}{}", prefix, separator, key)
+                };
+                flatten(&term, value, separator, non_strings, data)?;
+            }
+        }
+        (Value::String(translation), _) => {
+            data.push(Translation::new(prefix.to_owned(), translation))
+        }
+        (value @ (Value::Number(_) | Value::Bool(_)), NonStringValues::Coerce) => {
+            data.push(Translation::new(prefix.to_owned(), value.to_string()))
+        }
+        (Value::Null, NonStringValues::Coerce) => {
+            data.push(Translation::new(prefix.to_owned(), String::new()))
+        }
+        (value, NonStringValues::Skip) => log::warn!(
+            "Skipped term {:?} because its translation {} is not a string.",
+            prefix,
+            value
+        ),
+        (value, _) => anyhow::bail!(
+            "Translation of term {:?} is not a string but {}. Set non_string_values to \"coerce\" \
+             or \"skip\" to load the file anyway.",
+            prefix,
+            value
+        ),
+    }
+    Ok(())
+}
+
+/// Formats of the translation files that can be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    Properties,
+    QtLinguist,
+}
+
+impl Format {
+    /// The format named by the extension of the file or else the one its content looks like.
+    /// `.ts` is also the extension of TypeScript, so these files are looked at as well.
+    fn detect(path: &Path, data: &[u8], encoding: Option<&'static Encoding>) -> Self {
+        if properties::is_properties(path) {
+            Format::Properties
+        } else if path.extension().map_or(false, |extension| {
+            extension == "json" || extension == "json5"
+        }) {
+            Format::Json
+        } else {
+            Self::sniff(data, encoding)
+        }
+    }
+
+    /// Guesses the format from the first characters: an object or a comment for JSON, a tag for
+    /// XML and anything else for `key=value` lines.
+    fn sniff(data: &[u8], encoding: Option<&'static Encoding>) -> Self {
+        let encoding = encoding.unwrap_or_else(|| guess_encoding(data));
+        let (text, _) = encoding.decode_with_bom_removal(&data[..data.len().min(1024)]);
+        let text = text.trim_start();
+        if text.starts_with('<') {
+            Format::QtLinguist
+        } else if text.is_empty()
+            || text.starts_with('{')
+            || text.starts_with("//")
+            || text.starts_with("/*")
+        {
+            Format::Json
+        } else {
+            Format::Properties
+        }
+    }
+}
 
-                while let Some((term, translation)) = map.next_entry()? {
-                    data.push(Translation::new(term, translation));
+/// Parses the translation file in the format it is detected as.
+fn parse_file(
+    config: &AppConfig,
+    path: &Path,
+    data: &[u8],
+    encoding: Option<&'static Encoding>,
+) -> Result<Vec<Translation>> {
+    let format = Format::detect(path, data, encoding);
+    log::debug!("Reading {} as {:?}.", path.display(), format);
+    match format {
+        Format::Properties => properties::parse(data, encoding),
+        Format::QtLinguist => qt::parse(data, encoding),
+        Format::Json => {
+            let translations = parse(
+                data,
+                encoding,
+                config.non_string_values(),
+                config.key_separator(),
+            )?;
+            match config.i18next() {
+                Some(i18next) => {
+                    warn_incomplete_plurals(&translations);
+                    Ok(translations
+                        .into_iter()
+                        .map(|t| Translation::new(i18next.term(t.term), t.translation))
+                        .collect())
                 }
+                None => Ok(translations),
+            }
+        }
+    }
+}
+
+/// Plural suffixes of i18next, from the plural categories of the Unicode CLDR.
+const PLURAL_SUFFIXES: [&str; 6] = ["_zero", "_one", "_two", "_few", "_many", "_other"];
+
+/// Warns about plural forms without the `_other` form, which i18next falls back to.
+fn warn_incomplete_plurals(translations: &[Translation]) {
+    let terms: std::collections::HashSet<_> = translations.iter().map(|t| &t.term).collect();
+    let mut bases: Vec<_> = terms
+        .iter()
+        .filter_map(|term| {
+            PLURAL_SUFFIXES
+                .iter()
+                .find_map(|suffix| term.strip_suffix(suffix))
+        })
+        .filter(|base| !terms.contains(&format!("{}_other", base)))
+        .collect();
+    bases.sort_unstable();
+    bases.dedup();
+    for base in bases {
+        log::warn!("Plural forms of {:?} lack the form {}_other.", base, base);
+    }
+}
+
+/// Parses the translations of a JSON or JSON5 file, decoded with the configured `encoding` if
+/// there is one. Nested objects are flattened to terms joined with the separator, so flat and nested files
+/// are read alike.
+fn parse(
+    data: &[u8],
+    encoding: Option<&'static Encoding>,
+    non_strings: NonStringValues,
+    separator: &str,
+) -> Result<Vec<Translation>> {
+    let enc = encoding.unwrap_or_else(|| guess_encoding(data));
+    let (data, encountered_malformeds) = enc.decode_with_bom_removal(data);
 
-                Ok(DeserializationHelper(data))
+    if encountered_malformeds {
+        log::warn!("Replaced some malformed characters in translation file.");
+    }
+
+    // The line numbers stay the same in the plain JSON.
+    let converted = crate::json5::convert(&data).context("Failed to parse translation file")?;
+    let json = &converted.json;
+
+    let value: serde_json::Value = serde_json::from_str(json).map_err(|e| {
+        anyhow::anyhow!(converted.describe(&data, &e)).context("Failed to parse translation file")
+    })?;
+    anyhow::ensure!(
+        value.is_object(),
+        "Translation file must contain an object of terms and translations."
+    );
+    let duplicates = duplicate_terms(json, separator);
+    if !duplicates.is_empty() {
+        let duplicates: Vec<_> = duplicates
+            .iter()
+            .map(|(term, lines)| {
+                let lines: Vec<_> = lines.iter().map(ToString::to_string).collect();
+                format!("  {:?} in lines {}", term, lines.join(", "))
+            })
+            .collect();
+        anyhow::bail!(
+            "Translation file defines terms more than once, only the last definition would be \
+             kept:\n{}",
+            duplicates.join("\n")
+        );
+    }
+    let mut translations = Vec::new();
+    flatten("", value, separator, non_strings, &mut translations)?;
+    Ok(translations)
+}
+
+/// Terms that are defined more than once in valid JSON, each with the lines of its definitions.
+/// The keys of nested objects are joined with the separator like [`flatten`] does, so a nested
+/// term that is also written out flat counts as well.
+fn duplicate_terms(json: &str, separator: &str) -> Vec<(String, Vec<usize>)> {
+    /// An object or array that is being read, with the term prefix of its keys.
+    struct Container {
+        prefix: String,
+        is_object: bool,
+        expects_key: bool,
+        /// Term of the last key, which a nested object continues.
+        last_term: String,
+    }
+
+    let mut definitions: Vec<(String, usize)> = Vec::new();
+    let mut stack: Vec<Container> = Vec::new();
+    let mut line = 1;
+    let mut chars = json.char_indices();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            '"' => {
+                let mut escaped = false;
+                let end = chars
+                    .by_ref()
+                    .find(|&(_, c)| {
+                        let end = c == '"' && !escaped;
+                        escaped = c == '\\' && !escaped;
+                        end
+                    })
+                    .map_or(json.len(), |(i, _)| i + 1);
+                if let Some(top) = stack.last_mut().filter(|c| c.is_object && c.expects_key) {
+                    let key: String = serde_json::from_str(&json[start..end]).unwrap_or_default();
+                    let term = if top.prefix.is_empty() {
+                        key
+                    } else {
+                        format!("{}{}{}", top.prefix, separator, key)
+                    };
+                    definitions.push((term.clone(), line));
+                    top.last_term = term;
+                    top.expects_key = false;
+                }
+            }
+            '{' | '[' => {
+                let prefix = stack
+                    .last()
+                    .map(|c| c.last_term.clone())
+                    .unwrap_or_default();
+                stack.push(Container {
+                    prefix,
+                    is_object: c == '{',
+                    expects_key: true,
+                    last_term: String::new(),
+                });
             }
+            '}' | ']' => {
+                stack.pop();
+            }
+            ',' => {
+                if let Some(top) = stack.last_mut() {
+                    top.expects_key = true;
+                }
+            }
+            _ => {}
         }
+    }
+
+    let mut lines: std::collections::HashMap<&str, Vec<usize>> = Default::default();
+    for (term, line) in &definitions {
+        lines.entry(term).or_default().push(*line);
+    }
+    let mut duplicates: Vec<_> = lines
+        .into_iter()
+        .filter(|(_, lines)| lines.len() > 1)
+        .map(|(term, lines)| (term.to_owned(), lines))
+        .collect();
+    duplicates.sort_unstable_by_key(|(_, lines)| lines[0]);
+    duplicates
+}
 
-        deserializer.deserialize_map(TranslationVisitor)
+/// The encoding of the byte order mark or, without one, UTF-16 if it is recognized by its null
+/// bytes and UTF-8 otherwise.
+pub(super) fn guess_encoding(data: &[u8]) -> &'static Encoding {
+    Encoding::for_bom(data)
+        .map(|(encoding, _)| encoding)
+        .or_else(|| guess_utf16(data))
+        .unwrap_or(encoding_rs::UTF_8)
+}
+
+/// Recognizes UTF-16 without byte order mark. The characters that structure translation files
+/// and most of the text in Latin scripts are ASCII, so in UTF-16 every other byte is null,
+/// which never happens in text in UTF-8 or a single-byte encoding.
+pub(super) fn guess_utf16(data: &[u8]) -> Option<&'static Encoding> {
+    let sample = &data[..data.len().min(1024)];
+    let pairs = sample.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+    let (even, odd) = sample.chunks_exact(2).fold((0, 0), |(even, odd), pair| {
+        (
+            even + usize::from(pair[0] == 0),
+            odd + usize::from(pair[1] == 0),
+        )
+    });
+    if odd * 4 >= pairs && even * 20 <= pairs {
+        Some(encoding_rs::UTF_16LE)
+    } else if even * 4 >= pairs && odd * 20 <= pairs {
+        Some(encoding_rs::UTF_16BE)
+    } else {
+        None
     }
 }
 
@@ -131,8 +742,7 @@ mod tests {
 
     #[test]
     fn read_from_file() {
-        crate::config::init_test();
-        let res = load_from_file("testdata/en.json").unwrap();
+        let res = load_from_file(&crate::config::test(), "testdata/en.json").unwrap();
         println!("{:#?}", res);
         assert_eq!(res.len(), 20);
         assert_eq!(
@@ -144,26 +754,364 @@ mod tests {
 
     #[test]
     fn read_from_git_branch_tag_commit() {
-        crate::config::init_test();
-        let branch = load_from_git("foo", "testdata/en.json").unwrap();
-        let tag = load_from_git("blabla", "testdata/en.json").unwrap();
-        let commit = load_from_git("01452d761e", "testdata/en.json").unwrap();
+        let config = crate::config::test();
+        let branch = load_from_git(&config, "foo", "testdata/en.json").unwrap();
+        let tag = load_from_git(&config, "blabla", "testdata/en.json").unwrap();
+        let commit = load_from_git(&config, "01452d761e", "testdata/en.json").unwrap();
         assert_eq!(branch, tag);
         assert_eq!(branch, commit);
     }
 
+    #[test]
+    fn serialize_parse_round_trip() {
+        let translations = vec![
+            Translation::new("app.greeting".into(), "Grüße, \"{name}\"".into()),
+            Translation::new("app.title".into(), String::new()),
+        ];
+        for encoding in [
+            encoding_rs::UTF_8,
+            encoding_rs::UTF_16LE,
+            encoding_rs::UTF_16BE,
+        ] {
+            let data = serialize(&translations, encoding, false, ".").unwrap();
+            assert_eq!(
+                translations,
+                parse(&data, None, NonStringValues::Error, ".").unwrap()
+            );
+        }
+        assert!(serialize(&translations, encoding_rs::WINDOWS_1252, false, ".").is_ok());
+        assert!(serialize(&translations, encoding_rs::SHIFT_JIS, false, ".").is_err());
+    }
+
+    #[test]
+    fn nested_round_trip() {
+        let translations = vec![
+            Translation::new("app.menu.open".into(), "Open".into()),
+            Translation::new("app.title".into(), "Title".into()),
+            Translation::new("greeting".into(), "Hello".into()),
+        ];
+        let data = serialize(&translations, encoding_rs::UTF_8, true, ".").unwrap();
+        assert_eq!(
+            r#"{"app":{"menu":{"open":"Open"},"title":"Title"},"greeting":"Hello"}"#,
+            serde_json::from_slice::<serde_json::Value>(&data)
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            translations,
+            parse(&data, None, NonStringValues::Error, ".").unwrap()
+        );
+
+        let clash = [
+            Translation::new("app".into(), "App".into()),
+            Translation::new("app.title".into(), "Title".into()),
+        ];
+        assert!(serialize(&clash, encoding_rs::UTF_8, true, ".").is_err());
+        assert!(parse(
+            br#"{ "app": { "count": 1 } }"#,
+            None,
+            NonStringValues::Error,
+            "."
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn report_duplicate_terms() {
+        let json = r#"{
+            "app.title": "Title",
+            // "app.title": "commented out",
+            "app": {
+                "title": "Nested title",
+                "greeting": "Hello \"app.greeting\""
+            },
+            "app.greeting": "Hi",
+            "app.title": "Last title"
+        }"#;
+        let error = format!(
+            "{:#}",
+            parse(json.as_bytes(), None, NonStringValues::Error, ".").unwrap_err()
+        );
+        assert!(
+            error.ends_with("\n  \"app.title\" in lines 2, 5, 9\n  \"app.greeting\" in lines 6, 8"),
+            "{}",
+            error
+        );
+        assert_eq!(
+            vec![("a.b".to_string(), vec![1, 2])],
+            duplicate_terms("{ \"a\": { \"b\": \"1\" },\n \"a.b\": \"2\" }", ".")
+        );
+        assert!(duplicate_terms(r#"{ "a": { "b": "1" }, "b": "2", "a.c": "3" }"#, ".").is_empty());
+    }
+
+    #[test]
+    fn parse_json5() {
+        let json5 = "{\n  // Texts of the app\n  app: {\n    title: 'Title',\n  },\n  'app.greeting': \"It's me\",\n}";
+        assert_eq!(
+            vec![
+                Translation::new("app.title".into(), "Title".into()),
+                Translation::new("app.greeting".into(), "It's me".into()),
+            ],
+            parse(json5.as_bytes(), None, NonStringValues::Error, ".").unwrap()
+        );
+    }
+
+    #[test]
+    fn key_separator() {
+        let translations = vec![
+            Translation::new("app::menu::open".into(), "Open".into()),
+            Translation::new("app::title".into(), "Title".into()),
+        ];
+        let data = serialize(&translations, encoding_rs::UTF_8, true, "::").unwrap();
+        assert_eq!(
+            r#"{"app":{"menu":{"open":"Open"},"title":"Title"}}"#,
+            serde_json::from_slice::<serde_json::Value>(&data)
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            translations,
+            parse(&data, None, NonStringValues::Error, "::").unwrap()
+        );
+        assert_eq!(
+            vec![Translation::new("app/a.b".into(), "1".into())],
+            parse(
+                br#"{ "app": { "a.b": "1" } }"#,
+                None,
+                NonStringValues::Error,
+                "/"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn coerce_or_skip_non_strings() {
+        let json = br#"{ "count": 3, "enabled": true, "empty": null, "list": ["a"], "text": "b" }"#;
+        let t = |term: &str, translation: &str| Translation::new(term.into(), translation.into());
+        assert!(parse(json, None, NonStringValues::Error, ".").is_err());
+        assert!(parse(json, None, NonStringValues::Coerce, ".").is_err());
+        assert_eq!(
+            vec![t("count", "3"), t("enabled", "true"), t("empty", "")],
+            parse(
+                br#"{ "count": 3, "enabled": true, "empty": null }"#,
+                None,
+                NonStringValues::Coerce,
+                "."
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            vec![t("text", "b")],
+            parse(json, None, NonStringValues::Skip, ".").unwrap()
+        );
+    }
+
+    #[test]
+    fn merge_translation_files() {
+        let t = |term: &str, translation: &str| Translation::new(term.into(), translation.into());
+        let cart = Path::new("cart/en.json");
+        let checkout = Path::new("checkout/en.json");
+        let merged = merge_files(vec![
+            Ok((cart, vec![t("cart.title", "Cart"), t("common.ok", "OK")])),
+            Ok((
+                checkout,
+                vec![t("checkout.title", "Checkout"), t("common.ok", "OK")],
+            )),
+        ])
+        .unwrap();
+        assert_eq!(
+            vec![
+                t("cart.title", "Cart"),
+                t("common.ok", "OK"),
+                t("checkout.title", "Checkout")
+            ],
+            merged
+        );
+
+        let conflict = merge_files(vec![
+            Ok((cart, vec![t("common.ok", "OK")])),
+            Ok((checkout, vec![t("common.ok", "Okay")])),
+        ])
+        .unwrap_err();
+        assert_eq!(
+            "Term \"common.ok\" has different translations in cart/en.json and checkout/en.json.",
+            conflict.to_string()
+        );
+    }
+
+    #[test]
+    fn merge_base_of_feature_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let commit = |branch: &str, parent: Option<&git2::Commit>| {
+            let reference = format!("refs/heads/{}", branch);
+            let parents: Vec<_> = parent.into_iter().collect();
+            let id = repo
+                .commit(
+                    Some(&reference),
+                    &signature,
+                    &signature,
+                    branch,
+                    &tree,
+                    &parents,
+                )
+                .unwrap();
+            repo.find_commit(id).unwrap()
+        };
+        let start = commit("main", None);
+        commit("main", Some(&start));
+        commit("feature", Some(&start));
+        repo.set_head("refs/heads/feature").unwrap();
+
+        let main = repo.revparse_single("main").unwrap();
+        assert_eq!(start.id(), merge_base(&repo, &main).unwrap().id());
+    }
+
+    #[test]
+    fn find_uncommitted_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let (en, de) = (dir.path().join("en.json"), dir.path().join("de.json"));
+        fs::write(&en, "{}").unwrap();
+        fs::write(&de, "{}").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("en.json")).unwrap();
+        index.add_path(Path::new("de.json")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        index.write().unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "Start", &tree, &[])
+            .unwrap();
+
+        let files = [en.clone(), de.clone(), dir.path().join("fr.json")];
+        assert!(uncommitted_files(&files).is_empty());
+        fs::write(&de, "{ \"title\": \"Titel\" }").unwrap();
+        fs::write(dir.path().join("fr.json"), "{}").unwrap();
+        assert_eq!(vec![de, files[2].clone()], uncommitted_files(&files));
+    }
+
+    #[test]
+    fn remote_of_revision() {
+        let remotes = ["origin", "upstream", "upstream/mirror"];
+        assert_eq!(Some("origin"), remote_of("origin/main", &remotes));
+        assert_eq!(
+            Some("upstream"),
+            remote_of("refs/remotes/upstream/main", &remotes)
+        );
+        assert_eq!(
+            Some("upstream/mirror"),
+            remote_of("upstream/mirror/main", &remotes)
+        );
+        assert_eq!(Some("origin"), remote_of("v2.7.41", &remotes));
+        assert_eq!(None, remote_of("main", &["upstream"]));
+    }
+
+    #[test]
+    fn recognize_urls() {
+        assert!(is_url("https://artifacts.example.com/i18n/de.json?v=2"));
+        assert!(is_url(Path::new("http://localhost:8080/en.json")));
+        assert!(!is_url("i18n/https/en.json"));
+        assert!(!is_url("-"));
+    }
+
+    #[test]
+    fn detect_formats() {
+        let detect =
+            |path: &str, data: &str| Format::detect(Path::new(path), data.as_bytes(), None);
+        assert_eq!(Format::Properties, detect("messages.properties", "{"));
+        assert_eq!(Format::Json, detect("en.json", "app.title=Title"));
+        assert_eq!(
+            Format::QtLinguist,
+            detect("app_de.ts", "<?xml version=\"1.0\"?>")
+        );
+        assert_eq!(
+            Format::Json,
+            detect("-", "\n  { \"app.title\": \"Title\" }")
+        );
+        assert_eq!(Format::Json, detect("en.i18n", "// comment\n{}"));
+        assert_eq!(
+            Format::Properties,
+            detect("en.txt", "# comment\napp.title=Title")
+        );
+        assert_eq!(Format::Json, detect("empty", ""));
+        let utf16: Vec<_> = "\u{feff}<TS/>"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(
+            Format::QtLinguist,
+            Format::detect(Path::new("-"), &utf16, None)
+        );
+    }
+
+    #[test]
+    fn i18next_namespace() {
+        let i18next = crate::config::I18nextConfig {
+            namespace: Some("common".into()),
+        };
+        assert_eq!(
+            "common:cart.items_one",
+            i18next.term("cart.items_one".into())
+        );
+        assert_eq!(Some("cart.items_one"), i18next.key("common:cart.items_one"));
+        assert_eq!(None, i18next.key("admin:cart.items_one"));
+        assert_eq!(None, i18next.key("commonly:used"));
+        assert_eq!(
+            Some("admin:title"),
+            crate::config::I18nextConfig::default().key("admin:title")
+        );
+    }
+
+    #[test]
+    fn detect_utf16_without_bom() {
+        let text = "{ \"greeting\": \"Grüße 你好\" }";
+        let le: Vec<_> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let be: Vec<_> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(encoding_rs::UTF_16LE, guess_encoding(&le));
+        assert_eq!(encoding_rs::UTF_16BE, guess_encoding(&be));
+        assert_eq!(encoding_rs::UTF_8, guess_encoding(text.as_bytes()));
+        assert_eq!(encoding_rs::UTF_8, guess_encoding(b"\0"));
+        let expected = vec![Translation::new("greeting".into(), "Grüße 你好".into())];
+        assert_eq!(
+            expected,
+            parse(&le, None, NonStringValues::Error, ".").unwrap()
+        );
+        assert_eq!(
+            expected,
+            parse(&be, None, NonStringValues::Error, ".").unwrap()
+        );
+        let config = crate::config::test();
+        assert_eq!(
+            vec![Translation::new("greeting".into(), "Grüße".into())],
+            parse_file(
+                &config,
+                Path::new("-"),
+                &"greeting=Grüße"
+                    .encode_utf16()
+                    .flat_map(u16::to_le_bytes)
+                    .collect::<Vec<_>>(),
+                None
+            )
+            .unwrap()
+        );
+    }
+
     #[test]
     fn decode_parse_encodings() {
-        crate::config::init_test();
         let utf8 = include_bytes!("../../testdata/en-utf8.json");
         let utf8bom = include_bytes!("../../testdata/en-utf8-bom.json");
         let utf16be = include_bytes!("../../testdata/en-utf16be.json");
         let utf16le = include_bytes!("../../testdata/en-utf16le.json");
 
-        let utf8bom = parse(utf8bom, Source::Local).unwrap();
-        let utf16be = parse(utf16be, Source::Local).unwrap();
-        let utf16le = parse(utf16le, Source::Local).unwrap();
-        let utf8 = parse(utf8, Source::Local).unwrap();
+        let utf8bom = parse(utf8bom, None, NonStringValues::Error, ".").unwrap();
+        let utf16be = parse(utf16be, None, NonStringValues::Error, ".").unwrap();
+        let utf16le = parse(utf16le, None, NonStringValues::Error, ".").unwrap();
+        let utf8 = parse(utf8, None, NonStringValues::Error, ".").unwrap();
 
         assert_eq!(utf8, utf8bom);
         assert_eq!(utf16be, utf16le);
@@ -1,5 +1,18 @@
+pub mod bench;
+mod cache;
+mod commit;
 mod data;
+mod export;
+mod filters;
 mod local;
+mod properties;
+mod qt;
 mod remote;
+mod writer;
 
-pub use data::{load_data, Modification, Translation};
+pub use cache::exists as has_cached_data;
+pub use commit::commit_translation_files;
+pub use data::{load_data, LoadedData, Modification, Totals, Translation};
+pub use export::export_to_files;
+pub use local::{is_stdin, is_url, load_from_files, load_from_git_files};
+pub use remote::{Destination, TermMetadata};
@@ -0,0 +1,331 @@
+//! Java `.properties` files, e.g. the resource bundles of Java projects.
+//!
+//! Keys and values follow `java.util.Properties`: comments start with `#` or `!`, the key ends at
+//! the first unescaped `=`, `:` or whitespace, and lines ending with a backslash continue on the
+//! next line. Files without a configured encoding or byte order mark are read as UTF-8 like
+//! resource bundles since Java 9, falling back to ISO-8859-1 if they are no valid UTF-8.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use anyhow::Result;
+use encoding_rs::Encoding;
+
+use super::local::Translation;
+
+/// Whether the translation file is a `.properties` file, judged by its extension.
+pub fn is_properties(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .extension()
+        .map_or(false, |extension| extension == "properties")
+}
+
+fn decode(data: &[u8], encoding: Option<&'static Encoding>) -> String {
+    let encoding = encoding
+        .or_else(|| Encoding::for_bom(data).map(|(encoding, _)| encoding))
+        .or_else(|| super::local::guess_utf16(data))
+        .unwrap_or_else(|| match std::str::from_utf8(data) {
+            Ok(_) => encoding_rs::UTF_8,
+            // ISO-8859-1 is decoded as its superset windows-1252, like browsers do.
+            Err(_) => encoding_rs::WINDOWS_1252,
+        });
+    let (text, malformed) = encoding.decode_with_bom_removal(data);
+    if malformed {
+        log::warn!("Replaced some malformed characters in translation file.");
+    }
+    text.into_owned()
+}
+
+/// Joins the continued lines and drops comments and blank lines.
+fn logical_lines(text: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current: Option<String> = None;
+    for line in text.lines() {
+        let line = line.trim_start();
+        let line = match current.take() {
+            Some(mut previous) => {
+                previous.push_str(line);
+                previous
+            }
+            None if line.is_empty() || line.starts_with('#') || line.starts_with('!') => continue,
+            None => line.to_owned(),
+        };
+        let backslashes = line.chars().rev().take_while(|&c| c == '\\').count();
+        if backslashes % 2 == 1 {
+            current = Some(line[..line.len() - 1].to_owned());
+        } else {
+            lines.push(line);
+        }
+    }
+    lines.extend(current);
+    lines
+}
+
+/// Resolves the escape sequences of a key or value.
+fn unescape(text: &str) -> Result<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('f') => result.push('\u{c}'),
+            Some('u') => {
+                let code: String = chars.by_ref().take(4).collect();
+                let code = u32::from_str_radix(&code, 16)
+                    .ok()
+                    .filter(|_| code.len() == 4)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid escape sequence \\u{}.", code))?;
+                // Characters outside the basic plane are written as surrogate pairs.
+                if (0xd800..0xdc00).contains(&code) && chars.as_str().starts_with("\\u") {
+                    let low: String = chars.clone().skip(2).take(4).collect();
+                    if let Ok(low) = u32::from_str_radix(&low, 16) {
+                        if (0xdc00..0xe000).contains(&low) {
+                            let code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            result.extend(char::from_u32(code));
+                            chars.by_ref().take(6).for_each(drop);
+                            continue;
+                        }
+                    }
+                }
+                result.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+            Some(c) => result.push(c),
+            None => {}
+        }
+    }
+    Ok(result)
+}
+
+/// Splits a logical line into the escaped key and value.
+fn split(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            '=' | ':' => return (&line[..i], line[i + 1..].trim_start()),
+            c if c.is_whitespace() => {
+                // Whitespace may surround the separator or be the separator itself.
+                let rest = line[i..].trim_start();
+                let rest = rest.strip_prefix(&['=', ':'][..]).unwrap_or(rest);
+                return (&line[..i], rest.trim_start());
+            }
+            _ => {}
+        }
+    }
+    (line, "")
+}
+
+/// Parses the keys and values, decoded with `encoding` if there is one.
+pub fn parse(data: &[u8], encoding: Option<&'static Encoding>) -> Result<Vec<Translation>> {
+    logical_lines(&decode(data, encoding))
+        .iter()
+        .map(|line| {
+            let (key, value) = split(line);
+            Ok(Translation::new(unescape(key)?, unescape(value)?))
+        })
+        .collect()
+}
+
+fn escape(text: &str, is_key: bool, result: &mut String) {
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\u{c}' => result.push_str("\\f"),
+            '=' | ':' | '#' | '!' if is_key || i == 0 => {
+                result.push('\\');
+                result.push(c);
+            }
+            ' ' if is_key || i == 0 => result.push_str("\\ "),
+            ' '..='~' => result.push(c),
+            // Everything else is escaped, so the file is readable as ISO-8859-1 and UTF-8.
+            _ => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    result.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+        }
+    }
+}
+
+/// Writes the translations as `key=value` lines in the given order.
+pub fn serialize(translations: &[Translation]) -> String {
+    let mut result = String::new();
+    for t in translations {
+        escape(&t.term, true, &mut result);
+        result.push('=');
+        escape(&t.translation, false, &mut result);
+        result.push('\n');
+    }
+    result
+}
+
+/// Writes the translations into the text of an existing file. Comments, blank lines and the
+/// entries whose value is unchanged stay as they are, changed values are replaced behind their
+/// key, entries of removed keys are dropped with their continuation lines and new keys are
+/// appended in the order of the terms.
+pub fn update(text: &str, translations: &[Translation]) -> String {
+    let new: HashMap<&str, &str> = translations
+        .iter()
+        .map(|t| (t.term.as_str(), t.translation.as_str()))
+        .collect();
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut written = HashSet::new();
+    let mut result = String::with_capacity(text.len());
+    let mut lines = text.split_inclusive('\n');
+    while let Some(first) = lines.next() {
+        let content = first.trim_start();
+        if content.trim().is_empty() || content.starts_with('#') || content.starts_with('!') {
+            result.push_str(first);
+            continue;
+        }
+        let mut entry = vec![first];
+        let mut logical = String::new();
+        let mut line = content;
+        loop {
+            let content = line.trim_end_matches(&['\r', '\n'][..]);
+            let backslashes = content.chars().rev().take_while(|&c| c == '\\').count();
+            if backslashes % 2 == 0 {
+                logical.push_str(content);
+                break;
+            }
+            logical.push_str(&content[..content.len() - 1]);
+            match lines.next() {
+                Some(next) => {
+                    entry.push(next);
+                    line = next.trim_start();
+                }
+                None => break,
+            }
+        }
+
+        let (key, value) = split(&logical);
+        let (key, current) = match (unescape(key), unescape(value)) {
+            (Ok(key), Ok(current)) => (key, current),
+            // Entries that cannot be read are kept as they are.
+            _ => {
+                entry.iter().for_each(|line| result.push_str(line));
+                continue;
+            }
+        };
+        let translation = match new.get(key.as_str()) {
+            Some(translation) => translation,
+            None => continue,
+        };
+        if *translation == current {
+            entry.iter().for_each(|line| result.push_str(line));
+        } else {
+            result.push_str(&first[..first.len() - content.len()]);
+            result.push_str(&logical[..logical.len() - value.len()]);
+            escape(translation, false, &mut result);
+            if entry.last().map_or(false, |line| line.ends_with('\n')) {
+                result.push_str(newline);
+            }
+        }
+        written.insert(key);
+    }
+
+    let mut added: Vec<_> = translations
+        .iter()
+        .filter(|t| !written.contains(&t.term))
+        .collect();
+    added.sort_by(|a, b| a.cmp_by_term(b));
+    if !added.is_empty() && !result.is_empty() && !result.ends_with('\n') {
+        result.push_str(newline);
+    }
+    for t in added {
+        escape(&t.term, true, &mut result);
+        result.push('=');
+        escape(&t.translation, false, &mut result);
+        result.push_str(newline);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_properties() {
+        let data = "# comment\n\
+                    ! another comment\n\
+                    \n\
+                    app.title = Title\n\
+                    app.greeting:Hello, \\\n    {name}!\n\
+                    app.empty\n\
+                    app\\ key\\=with\\:separators value \\u00fc\\t\\ud83d\\ude00\n\
+                    app.path=C:\\\\temp\n";
+        let t = |term: &str, translation: &str| Translation::new(term.into(), translation.into());
+        assert_eq!(
+            vec![
+                t("app.title", "Title"),
+                t("app.greeting", "Hello, {name}!"),
+                t("app.empty", ""),
+                t("app key=with:separators", "value ü\t😀"),
+                t("app.path", "C:\\temp"),
+            ],
+            parse(data.as_bytes(), None).unwrap()
+        );
+        assert!(parse(b"app.title=\\u00", None).is_err());
+    }
+
+    #[test]
+    fn decode_latin1_and_utf8() {
+        let expected = vec![Translation::new("greeting".into(), "Grüße".into())];
+        assert_eq!(expected, parse(b"greeting=Gr\xfc\xdfe", None).unwrap());
+        assert_eq!(expected, parse("greeting=Grüße".as_bytes(), None).unwrap());
+    }
+
+    #[test]
+    fn serialize_parse_round_trip() {
+        let translations = vec![
+            Translation::new("app key=1".into(), " leading space, ünïcode 😀".into()),
+            Translation::new("app.lines".into(), "first\nsecond # not a comment".into()),
+            Translation::new("#app".into(), "".into()),
+        ];
+        let text = serialize(&translations);
+        assert!(text.is_ascii());
+        assert_eq!(translations, parse(text.as_bytes(), None).unwrap());
+        assert!(is_properties("src/main/resources/messages_de.properties"));
+        assert!(!is_properties("en.json"));
+    }
+
+    #[test]
+    fn update_keeps_layout() {
+        let text = "# Greetings\r\n\
+                    greeting = Hello\r\n\
+                    \r\n\
+                    farewell : Good\\\r\n    bye\r\n\
+                    gone=Gone\r\n\
+                    title=Title";
+        let translations = [
+            Translation::new("title".into(), "Title".into()),
+            Translation::new("greeting".into(), "Grüß dich".into()),
+            Translation::new("farewell".into(), "Goodbye".into()),
+            Translation::new("new".into(), "Neu".into()),
+        ];
+        assert_eq!(
+            "# Greetings\r\n\
+             greeting = Gr\\u00fc\\u00df dich\r\n\
+             \r\n\
+             farewell : Good\\\r\n    bye\r\n\
+             title=Title\r\n\
+             new=Neu\r\n",
+            update(text, &translations)
+        );
+    }
+}
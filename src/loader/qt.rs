@@ -0,0 +1,309 @@
+//! Qt Linguist `.ts` files, the XML translation source of Qt projects.
+//!
+//! Each message becomes a term named by its context and source text, e.g. `MainWindow.Open`, or by
+//! its `id` if it has one. Translations marked `unfinished` are read as empty, so they show up as
+//! untranslated, and messages marked `vanished` or `obsolete` are skipped. Of plural messages only
+//! the first numerus form is compared.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use encoding_rs::Encoding;
+
+use super::local::Translation;
+
+/// Whether the translation file is a Qt Linguist file, judged by its extension.
+pub fn is_ts(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .extension()
+        .map_or(false, |extension| extension == "ts")
+}
+
+#[derive(Debug, PartialEq)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn elements<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter_map(move |child| match child {
+            Node::Element(e) if e.name == name => Some(e),
+            _ => None,
+        })
+    }
+
+    fn element(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find_map(|child| match child {
+            Node::Element(e) if e.name == name => Some(e),
+            _ => None,
+        })
+    }
+
+    /// The text directly inside the element.
+    fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|child| match child {
+                Node::Text(text) => Some(text.as_str()),
+                Node::Element(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Replaces the character and entity references of XML.
+fn unescape(text: &str) -> Result<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .with_context(|| format!("Unterminated reference in {:?}.", text))?;
+        let reference = &rest[start + 1..start + end];
+        let c = match reference {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => reference
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| reference.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        result.push(c.with_context(|| format!("Unknown reference &{};.", reference))?);
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Minimal XML reader for the elements, attributes and text of a `.ts` file. Processing
+/// instructions, comments and the document type are skipped.
+struct Reader<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Reader<'a> {
+    fn skip_past(&mut self, end: &str) -> Result<&'a str> {
+        let i = self
+            .rest
+            .find(end)
+            .with_context(|| format!("Missing {:?}.", end))?;
+        let skipped = &self.rest[..i];
+        self.rest = &self.rest[i + end.len()..];
+        Ok(skipped)
+    }
+
+    /// Reads the children up to the end tag of `parent`, or up to the end of the document.
+    fn children(&mut self, parent: Option<&str>) -> Result<Vec<Node>> {
+        let mut children = Vec::new();
+        loop {
+            let text_end = self.rest.find('<').unwrap_or(self.rest.len());
+            let text = &self.rest[..text_end];
+            if !text.is_empty() {
+                children.push(Node::Text(unescape(text)?));
+            }
+            self.rest = &self.rest[text_end..];
+
+            if self.rest.is_empty() {
+                anyhow::ensure!(parent.is_none(), "Element {:?} is not closed.", parent);
+                return Ok(children);
+            } else if self.rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest.starts_with("<![CDATA[") {
+                self.rest = &self.rest["<![CDATA[".len()..];
+                children.push(Node::Text(self.skip_past("]]>")?.to_owned()));
+            } else if self.rest.starts_with("<!") {
+                self.skip_past(">")?;
+            } else if let Some(rest) = self.rest.strip_prefix("</") {
+                self.rest = rest;
+                let name = self.skip_past(">")?.trim();
+                anyhow::ensure!(
+                    Some(name) == parent,
+                    "Found end tag of {:?} instead of {:?}.",
+                    name,
+                    parent
+                );
+                return Ok(children);
+            } else {
+                children.push(Node::Element(self.element()?));
+            }
+        }
+    }
+
+    fn element(&mut self) -> Result<Element> {
+        self.rest = &self.rest[1..];
+        let tag = self.skip_past(">")?;
+        let (tag, empty) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let mut element = Element {
+            name: tag[..name_end].to_owned(),
+            ..Element::default()
+        };
+        let mut attributes = tag[name_end..].trim_start();
+        while !attributes.is_empty() {
+            let equals = attributes
+                .find('=')
+                .with_context(|| format!("Invalid attributes in tag {:?}.", tag))?;
+            let key = attributes[..equals].trim().to_owned();
+            let value = attributes[equals + 1..].trim_start();
+            let quote = value
+                .chars()
+                .next()
+                .filter(|&c| c == '"' || c == '\'')
+                .with_context(|| format!("Attribute {:?} is not quoted.", key))?;
+            let end = value[1..]
+                .find(quote)
+                .with_context(|| format!("Attribute {:?} is not closed.", key))?;
+            element
+                .attributes
+                .push((key, unescape(&value[1..end + 1])?));
+            attributes = value[end + 2..].trim_start();
+        }
+        if !empty {
+            element.children = self.children(Some(&element.name))?;
+        }
+        Ok(element)
+    }
+}
+
+fn read_document(text: &str) -> Result<Element> {
+    let nodes = Reader { rest: text }.children(None)?;
+    nodes
+        .into_iter()
+        .find_map(|node| match node {
+            Node::Element(e) if e.name == "TS" => Some(e),
+            _ => None,
+        })
+        .context("Qt Linguist file has no TS element.")
+}
+
+fn message(context: &str, message: &Element) -> Option<Translation> {
+    let translation = message.element("translation")?;
+    if let Some("vanished" | "obsolete") = translation.attribute("type") {
+        return None;
+    }
+    let term = match message.attribute("id") {
+        Some(id) => id.to_owned(),
+        None => format!("{}.{}", context, message.element("source")?.text()),
+    };
+    let text = if translation.attribute("type") == Some("unfinished") {
+        String::new()
+    } else {
+        match translation.element("numerusform") {
+            Some(form) => form.text(),
+            None => translation.text(),
+        }
+    };
+    Some(Translation::new(term, text))
+}
+
+/// Parses the messages of all contexts, decoded with `encoding` if there is one and as UTF-8
+/// otherwise.
+pub fn parse(data: &[u8], encoding: Option<&'static Encoding>) -> Result<Vec<Translation>> {
+    let encoding = encoding.unwrap_or_else(|| super::local::guess_encoding(data));
+    let (text, malformed) = encoding.decode_with_bom_removal(data);
+    if malformed {
+        log::warn!("Replaced some malformed characters in translation file.");
+    }
+    let document = read_document(&text).context("Failed to parse Qt Linguist file")?;
+    Ok(document
+        .elements("context")
+        .flat_map(|context| {
+            let name = context.element("name").map(Element::text);
+            let name = name.unwrap_or_default();
+            context
+                .elements("message")
+                .filter_map(|m| message(&name, m))
+                .collect::<Vec<_>>()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_contexts_and_messages() {
+        let data = r#"<?xml version="1.0" encoding="utf-8"?>
+<!DOCTYPE TS>
+<TS version="2.1" language="de_DE">
+<context>
+    <name>MainWindow</name>
+    <message>
+        <location filename="../src/mainwindow.cpp" line="42"/>
+        <source>Open &amp; close</source>
+        <translation>Öffnen &amp; schließen</translation>
+    </message>
+    <!-- <message><source>Commented</source></message> -->
+    <message>
+        <source>Save</source>
+        <translation type="unfinished">Speichern</translation>
+    </message>
+    <message>
+        <source>Quit</source>
+        <translation type="vanished">Beenden</translation>
+    </message>
+    <message numerus="yes">
+        <source>%n file(s)</source>
+        <translation>
+            <numerusform>%n Datei</numerusform>
+            <numerusform>%n Dateien</numerusform>
+        </translation>
+    </message>
+</context>
+<context>
+    <name>Dialog</name>
+    <message id="dialog.title">
+        <source>Title</source>
+        <translation><![CDATA[<b>Titel</b>]]></translation>
+    </message>
+</context>
+</TS>
+"#;
+        let t = |term: &str, translation: &str| Translation::new(term.into(), translation.into());
+        assert_eq!(
+            vec![
+                t("MainWindow.Open & close", "Öffnen & schließen"),
+                t("MainWindow.Save", ""),
+                t("MainWindow.%n file(s)", "%n Datei"),
+                t("dialog.title", "<b>Titel</b>"),
+            ],
+            parse(data.as_bytes(), None).unwrap()
+        );
+        assert!(is_ts("i18n/app_de.ts"));
+    }
+
+    #[test]
+    fn reject_malformed_files() {
+        assert!(parse(b"<TS><context></TS>", None).is_err());
+        assert!(parse(b"<TS version=2.1></TS>", None).is_err());
+        assert!(parse(b"<resources></resources>", None).is_err());
+        assert!(parse(b"<TS>&unknown;</TS>", None).is_err());
+    }
+}
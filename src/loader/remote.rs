@@ -1,20 +1,44 @@
 use anyhow::{Context, Result};
 use itertools::{EitherOrBoth, Itertools};
+use serde::{Deserialize, Serialize};
 
-use traduora::{
-    api::{
-        terms::{Term, Terms},
-        translations::Translations,
-        TermId,
-    },
-    Query,
-};
+use traduora::api::TermId;
 
-#[derive(Debug, Clone)]
+use crate::client::Client;
+use crate::compat::Term;
+use crate::config::AppConfig;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Translation {
     pub term_id: TermId,
     pub term: String,
     pub translation: String,
+    /// Missing in data cached by older versions.
+    #[serde(default)]
+    pub metadata: TermMetadata,
+}
+
+/// Information about a term on Traduora that is only shown for reference.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TermMetadata {
+    pub created: Option<String>,
+    pub modified: Option<String>,
+    pub labels: Vec<String>,
+}
+
+impl TermMetadata {
+    /// Shortens a timestamp like `2022-03-01T12:34:56.789Z` to `2022-03-01 12:34 UTC`.
+    ///
+    /// Timestamps in an unexpected format are returned unchanged.
+    pub fn format_date(date: &str) -> String {
+        let is_utc = date.ends_with('Z');
+        match date.get(..16) {
+            Some(minutes) if is_utc && minutes.as_bytes().get(10) == Some(&b'T') => {
+                format!("{} UTC", minutes.replacen('T', " ", 1))
+            }
+            _ => date.to_string(),
+        }
+    }
 }
 
 impl Translation {
@@ -25,37 +49,99 @@ impl Translation {
 
 impl From<(Term, String)> for Translation {
     fn from((term, translation): (Term, String)) -> Self {
+        let (created, modified) = term
+            .date
+            .map_or((None, None), |date| (date.created, date.modified));
         Self {
             term_id: term.id,
             term: term.value,
             translation,
+            metadata: TermMetadata {
+                created,
+                modified,
+                labels: term.labels.into_iter().map(|l| l.value).collect(),
+            },
+        }
+    }
+}
+
+/// Human-readable names of the project and locale that are updated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Destination {
+    pub project: String,
+    pub locale: String,
+}
+
+impl Destination {
+    /// Falls back to the ids from the configuration if the names are unknown.
+    pub fn unresolved(config: &AppConfig) -> Self {
+        Self {
+            project: config.project_id().to_string(),
+            locale: config.locale().to_string(),
         }
     }
 }
 
-pub fn fetch_from_traduora() -> Result<Vec<Translation>> {
-    use crate::config::*;
-    let client = create_client()?;
-    let project_id = crate::config::get().project_id();
-    let locale = crate::config::get().locale();
+impl std::fmt::Display for Destination {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?} / {}", self.project, self.locale)
+    }
+}
+
+fn fetch_destination(client: &Client) -> Result<Destination> {
+    let project_id = client.config().project_id();
+    let locale = client.config().locale();
+
+    let project = client
+        .project()
+        .with_context(|| format!("Failed to load project {:?}", project_id))?;
+
+    let locale = client
+        .locales()
+        .with_context(|| format!("Failed to load locales of project {:?}", project_id))?
+        .into_iter()
+        .find(|l| &l.locale.code == locale)
+        .map_or_else(
+            || locale.to_string(),
+            |l| match l.locale.region.as_str() {
+                "" => format!("{} ({})", l.locale.language, l.locale.code),
+                region => format!("{}, {} ({})", l.locale.language, region, l.locale.code),
+            },
+        );
+
+    Ok(Destination {
+        project: project.name,
+        locale,
+    })
+}
+
+pub fn fetch_from_traduora(config: &AppConfig) -> Result<(Vec<Translation>, Destination)> {
+    let client = crate::config::create_client(config)?;
+    let project_id = config.project_id();
+    let locale = config.locale();
 
-    let mut terms = Terms(project_id.clone())
-        .query(&client)
+    // The names are only shown to the user, so the ids are good enough if they cannot be loaded.
+    let destination = fetch_destination(&client).unwrap_or_else(|e| {
+        log::warn!("Failed to load the names of project and locale: {:#}", e);
+        Destination::unresolved(config)
+    });
+
+    let mut terms = client
+        .terms()
         .with_context(|| format!("Failed to load terms for project {:?}", project_id))?;
 
-    let mut translations = Translations::new(project_id.clone(), locale.clone())
-        .query(&client)
-        .with_context(|| {
-            format!(
-                "Failed to load translations for locale {:?} in project {:?}",
-                locale, project_id
-            )
-        })?;
+    let mut translations = client.translations().with_context(|| {
+        format!(
+            "Failed to load translations for locale {:?} in project {:?}",
+            locale, project_id
+        )
+    })?;
 
+    terms.retain(|t| t.value != crate::lock::LOCK_TERM);
     terms.sort_unstable_by(|t1, t2| t1.id.cmp(&t2.id));
     translations.sort_unstable_by(|t1, t2| t1.term_id.cmp(&t2.term_id));
 
-    Ok(terms
+    let translations = terms
         .into_iter()
         .merge_join_by(translations, |term, tl| term.id.cmp(&tl.term_id))
         .filter_map(|e| match e {
@@ -63,18 +149,33 @@ pub fn fetch_from_traduora() -> Result<Vec<Translation>> {
             EitherOrBoth::Left(term) => Some((term, String::new()).into()),
             EitherOrBoth::Right(_) => None,
         })
-        .collect())
+        .collect();
+
+    Ok((translations, destination))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::fetch_from_traduora;
+    use super::{fetch_from_traduora, TermMetadata};
+
+    #[test]
+    fn format_dates() {
+        assert_eq!(
+            "2022-03-01 12:34 UTC",
+            TermMetadata::format_date("2022-03-01T12:34:56.789Z")
+        );
+        assert_eq!("yesterday", TermMetadata::format_date("yesterday"));
+        assert_eq!(
+            "2022-03-01T12:34:56+01:00",
+            TermMetadata::format_date("2022-03-01T12:34:56+01:00")
+        );
+    }
 
     #[ignore = "needs access to a traduora instance"]
     #[test]
     fn fetch() {
-        crate::config::init().unwrap();
-        let res = fetch_from_traduora().unwrap();
+        let config = crate::config::load(&crate::cli::Cli::default()).unwrap();
+        let res = fetch_from_traduora(&config).unwrap();
         println!("{:#?}", res);
     }
 }
@@ -0,0 +1,807 @@
+//! Writes translations into an existing translation file in its own format, so a file that is
+//! maintained by hand keeps its layout.
+//!
+//! The terms that are still in the file keep their place, comments, indentation, line endings
+//! and encoding, only their translations are replaced. Terms that are gone are removed with their
+//! line and new terms are added at the end of the object whose keys they continue, or at the end
+//! of the file.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use encoding_rs::Encoding;
+
+use super::local::Translation;
+use super::properties;
+use crate::config::NonStringValues;
+
+/// Writes the translations into the existing content of the file at `path`, decoded with
+/// `encoding` if there is one. Terms that are added are nested at the `separator` if `nested` is
+/// set. Values that are no strings are handled as configured by `non_strings`.
+pub fn write(
+    path: &Path,
+    existing: &[u8],
+    encoding: Option<&'static Encoding>,
+    translations: &[Translation],
+    nested: bool,
+    non_strings: NonStringValues,
+    separator: &str,
+) -> Result<Vec<u8>> {
+    let bom = Encoding::for_bom(existing);
+    let encoding = encoding
+        .or_else(|| bom.map(|(encoding, _)| encoding))
+        .or_else(|| super::local::guess_utf16(existing))
+        .unwrap_or_else(|| match std::str::from_utf8(existing) {
+            Ok(_) => encoding_rs::UTF_8,
+            Err(_) if properties::is_properties(path) => encoding_rs::WINDOWS_1252,
+            Err(_) => encoding_rs::UTF_8,
+        });
+    let (text, malformed) = encoding.decode_with_bom_removal(existing);
+    anyhow::ensure!(
+        !malformed,
+        "{} is not encoded in {}, so it cannot be updated without damaging it.",
+        path.display(),
+        encoding.name()
+    );
+    let text = if properties::is_properties(path) {
+        properties::update(&text, translations)
+    } else {
+        update_json(&text, translations, nested, non_strings, separator)
+            .with_context(|| format!("Failed to update {}", path.display()))?
+    };
+    encode(&text, encoding, bom.is_some())
+}
+
+/// Encodes the text of a translation file. UTF-16 is always written with a byte order mark, so
+/// the encoding is detected when reading the file again, UTF-8 only if `bom` is set.
+pub fn encode(text: &str, encoding: &'static Encoding, bom: bool) -> Result<Vec<u8>> {
+    let utf16 = |to_bytes: fn(u16) -> [u8; 2]| {
+        std::iter::once('\u{feff}')
+            .chain(text.chars())
+            .collect::<String>()
+            .encode_utf16()
+            .flat_map(to_bytes)
+            .collect()
+    };
+    if encoding == encoding_rs::UTF_16LE {
+        Ok(utf16(u16::to_le_bytes))
+    } else if encoding == encoding_rs::UTF_16BE {
+        Ok(utf16(u16::to_be_bytes))
+    } else {
+        let (data, _, unmappable) = encoding.encode(text);
+        if unmappable {
+            anyhow::bail!(
+                "Some translations cannot be represented in encoding {}",
+                encoding.name()
+            );
+        }
+        let mut data = data.into_owned();
+        if bom && encoding == encoding_rs::UTF_8 {
+            data.splice(0..0, *b"\xef\xbb\xbf");
+        }
+        Ok(data)
+    }
+}
+
+/// An object of a JSON translation file with the positions of its parts.
+struct Object {
+    /// Term of the member that holds the object, empty for the root object.
+    prefix: String,
+    open: usize,
+    close: usize,
+    members: Vec<Member>,
+}
+
+struct Member {
+    term: String,
+    key: String,
+    /// Position of the quote that starts the key.
+    start: usize,
+    /// Position after the value.
+    end: usize,
+    value: Value,
+}
+
+enum Value {
+    /// The position of the string literal, including its quotes.
+    String(std::ops::Range<usize>),
+    Object(Object),
+    /// The position of a number, boolean or null that is read as its text.
+    Coerced(std::ops::Range<usize>),
+    /// The position of a value that is no string and kept as it is.
+    Skipped(std::ops::Range<usize>),
+}
+
+/// Reader of the structure of a JSON5 file whose comments are blanked out. Positions are byte
+/// offsets, which are the same in the original text because comments are blanked byte by byte.
+struct Reader<'a> {
+    json: &'a str,
+    pos: usize,
+    non_strings: NonStringValues,
+    separator: &'a str,
+}
+
+impl Reader<'_> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.json[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        self.skip_whitespace();
+        anyhow::ensure!(
+            self.json[self.pos..].starts_with(c),
+            "Expected {:?} at byte {}.",
+            c,
+            self.pos
+        );
+        self.pos += c.len_utf8();
+        Ok(())
+    }
+
+    /// Reads a string literal in single or double quotes and returns its range.
+    fn string(&mut self) -> Result<std::ops::Range<usize>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let quote = self.json[start..]
+            .chars()
+            .next()
+            .filter(|&c| c == '"' || c == '\'')
+            .with_context(|| format!("Expected a string at byte {}.", start))?;
+        self.pos += 1;
+        let mut escaped = false;
+        let len = self.json[self.pos..]
+            .find(|c| {
+                let end = c == quote && !escaped;
+                escaped = c == '\\' && !escaped;
+                end
+            })
+            .context("String is not closed.")?;
+        self.pos += len + 1;
+        Ok(start..self.pos)
+    }
+
+    /// Reads a value that is no string or object, with any arrays or objects nested in it, and
+    /// returns its range.
+    fn other(&mut self) -> Result<std::ops::Range<usize>> {
+        let start = self.pos;
+        let mut depth = 0usize;
+        while let Some(c) = self.json[self.pos..].chars().next() {
+            match c {
+                '"' | '\'' => {
+                    self.string()?;
+                    continue;
+                }
+                '[' | '{' => depth += 1,
+                ',' | '}' | ']' if depth == 0 => break,
+                ']' | '}' => depth -= 1,
+                _ => {}
+            }
+            self.pos += c.len_utf8();
+        }
+        let end = start + self.json[start..self.pos].trim_end().len();
+        anyhow::ensure!(end > start, "Expected a value at byte {}.", start);
+        self.pos = end;
+        Ok(start..end)
+    }
+
+    /// Reads a key, which may be an identifier without quotes, and returns its range and value.
+    fn key(&mut self) -> Result<(std::ops::Range<usize>, String)> {
+        self.skip_whitespace();
+        let rest = &self.json[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            let range = self.string()?;
+            let key = crate::json5::unquote(&self.json[range.clone()])?;
+            return Ok((range, key));
+        }
+        let start = self.pos;
+        self.pos += len;
+        Ok((start..self.pos, rest[..len].to_owned()))
+    }
+
+    fn object(&mut self, prefix: String) -> Result<Object> {
+        self.expect('{')?;
+        let open = self.pos - 1;
+        let mut members = Vec::new();
+        loop {
+            self.skip_whitespace();
+            // An empty object or a trailing comma.
+            if self.json[self.pos..].starts_with('}') {
+                break;
+            }
+            let (key_range, key) = self.key()?;
+            let term = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}{}{}", prefix, self.separator, key)
+            };
+            self.expect(':')?;
+            self.skip_whitespace();
+            let value = match self.json[self.pos..].chars().next() {
+                Some('"' | '\'') => Value::String(self.string()?),
+                Some('{') => Value::Object(self.object(term.clone())?),
+                Some(c) => match self.non_strings {
+                    NonStringValues::Coerce if c != '[' => Value::Coerced(self.other()?),
+                    NonStringValues::Skip => Value::Skipped(self.other()?),
+                    _ => anyhow::bail!("Translation of term {:?} is not a string.", term),
+                },
+                None => anyhow::bail!("Expected a value at byte {}.", self.pos),
+            };
+            members.push(Member {
+                term,
+                key,
+                start: key_range.start,
+                end: self.pos,
+                value,
+            });
+            self.skip_whitespace();
+            if self.json[self.pos..].starts_with(',') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        self.expect('}')?;
+        Ok(Object {
+            prefix,
+            open,
+            close: self.pos - 1,
+            members,
+        })
+    }
+}
+
+/// Replacement of the text between two positions.
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+/// Layout of the file that is followed when writing new terms.
+struct Style<'a> {
+    json: &'a str,
+    /// One level of indentation, e.g. two spaces or a tab.
+    indent: String,
+    newline: &'static str,
+    /// Separator of the keys that are joined to terms.
+    separator: &'a str,
+}
+
+impl Style<'_> {
+    fn line_start(&self, pos: usize) -> usize {
+        self.json[..pos].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    /// Indentation of the line, if the position is the first thing on it.
+    fn indent_at(&self, pos: usize) -> Option<&str> {
+        let before = &self.json[self.line_start(pos)..pos];
+        before.trim().is_empty().then(|| before)
+    }
+
+    /// End of the line, if nothing but a comma follows the position on it.
+    fn rest_of_line(&self, pos: usize) -> Option<usize> {
+        let rest = &self.json[pos..];
+        let end = rest.find('\n').unwrap_or(rest.len());
+        let line = rest[..end].trim_end_matches('\r').trim();
+        (line.is_empty() || line == ",").then(|| pos + end)
+    }
+
+    /// Serializes a new value with the indentation of the file, starting at `indent`.
+    fn value(&self, value: &serde_json::Value, indent: &str) -> String {
+        use serde::Serialize;
+
+        let mut data = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(self.indent.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut data, formatter);
+        // Writing strings and maps of strings to memory cannot fail.
+        let _ = value.serialize(&mut serializer);
+        let text = String::from_utf8(data).unwrap_or_default();
+        text.replace('\n', &format!("{}{}", self.newline, indent))
+    }
+}
+
+/// Writes the translations into the text of a JSON file, see the module documentation.
+fn update_json(
+    text: &str,
+    translations: &[Translation],
+    nested: bool,
+    non_strings: NonStringValues,
+    separator: &str,
+) -> Result<String> {
+    let json = crate::json5::blank_comments(text);
+    let root = Reader {
+        json: &json,
+        pos: 0,
+        non_strings,
+        separator,
+    }
+    .object(String::new())?;
+
+    let style = Style {
+        json: &json,
+        indent: root
+            .members
+            .first()
+            .and_then(|m| style_indent(&json, m.start))
+            .unwrap_or_else(|| "  ".into()),
+        newline: if text.contains("\r\n") { "\r\n" } else { "\n" },
+        separator,
+    };
+    let new: HashMap<&str, &str> = translations
+        .iter()
+        .map(|t| (t.term.as_str(), t.translation.as_str()))
+        .collect();
+
+    let mut edits = Vec::new();
+    let mut objects = Vec::new();
+    let mut written = std::collections::HashSet::new();
+    update_values(&root, &new, &style, &mut edits, &mut objects, &mut written)?;
+
+    let mut added: Vec<_> = new.keys().filter(|t| !written.contains(*t)).collect();
+    added.sort_unstable();
+    let mut additions = vec![Vec::new(); objects.len()];
+    for term in added {
+        // The deepest object whose keys the term continues, e.g. `app` for `app.menu.open`.
+        let index = objects
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| {
+                o.prefix.is_empty() || term.starts_with(&format!("{}{}", o.prefix, separator))
+            })
+            .max_by_key(|(_, o)| o.prefix.len())
+            .map_or(0, |(i, _)| i);
+        let key = match objects[index].prefix.len() {
+            0 => term,
+            len => &term[len + separator.len()..],
+        };
+        additions[index].push((key, new[term]));
+    }
+    for (object, entries) in objects.iter().zip(additions) {
+        remove_and_add(object, &entries, &new, nested, &style, &mut edits)?;
+    }
+
+    edits.sort_by_key(|e| (std::cmp::Reverse(e.start), e.start == e.end));
+    let mut text = text.to_owned();
+    for edit in edits {
+        text.replace_range(edit.start..edit.end, &edit.text);
+    }
+    Ok(text)
+}
+
+/// Indentation of the member at `pos`, if it starts its line.
+fn style_indent(json: &str, pos: usize) -> Option<String> {
+    let line_start = json[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &json[line_start..pos];
+    (!indent.is_empty() && indent.trim().is_empty()).then(|| indent.to_owned())
+}
+
+/// Whether none of the translations of the member is written anymore.
+fn is_removed(member: &Member, new: &HashMap<&str, &str>) -> bool {
+    match &member.value {
+        Value::String(_) | Value::Coerced(_) => !new.contains_key(member.term.as_str()),
+        Value::Skipped(_) => false,
+        Value::Object(object) => {
+            !object.members.is_empty() && object.members.iter().all(|m| is_removed(m, new))
+        }
+    }
+}
+
+/// Replaces the changed translations and collects the objects that are kept and the terms that
+/// are written already.
+fn update_values<'a>(
+    object: &'a Object,
+    new: &HashMap<&str, &str>,
+    style: &Style,
+    edits: &mut Vec<Edit>,
+    objects: &mut Vec<&'a Object>,
+    written: &mut std::collections::HashSet<&'a str>,
+) -> Result<()> {
+    objects.push(object);
+    for member in object.members.iter().filter(|m| !is_removed(m, new)) {
+        match &member.value {
+            Value::String(range) => {
+                let current = crate::json5::unquote(&style.json[range.clone()])?;
+                let translation = new[member.term.as_str()];
+                if translation != current {
+                    edits.push(Edit {
+                        start: range.start,
+                        end: range.end,
+                        text: serde_json::to_string(translation)?,
+                    });
+                }
+                written.insert(&member.term);
+            }
+            Value::Object(child) => update_values(child, new, style, edits, objects, written)?,
+            Value::Coerced(range) | Value::Skipped(range) => {
+                let current = match &style.json[range.clone()] {
+                    "null" => "",
+                    current => current,
+                };
+                // Skipped values are only replaced if the term is translated after all.
+                if let Some(&translation) = new.get(member.term.as_str()) {
+                    if translation != current {
+                        edits.push(Edit {
+                            start: range.start,
+                            end: range.end,
+                            text: serde_json::to_string(translation)?,
+                        });
+                    }
+                }
+                written.insert(&member.term);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Removes the members of the object that are gone and adds the new `entries` after the last
+/// member that is kept.
+fn remove_and_add(
+    object: &Object,
+    entries: &[(&str, &str)],
+    new: &HashMap<&str, &str>,
+    nested: bool,
+    style: &Style,
+    edits: &mut Vec<Edit>,
+) -> Result<()> {
+    let json = style.json;
+    let kept: Vec<_> = object
+        .members
+        .iter()
+        .filter(|m| !is_removed(m, new))
+        .collect();
+    let multiline = object.members.is_empty() || json[object.open..object.close].contains('\n');
+    let object_indent: String = json[style.line_start(object.open)..]
+        .chars()
+        .take_while(|&c| c == ' ' || c == '\t')
+        .collect();
+    let member_indent = object
+        .members
+        .first()
+        .and_then(|m| style.indent_at(m.start))
+        .map_or_else(
+            || format!("{}{}", object_indent, style.indent),
+            str::to_owned,
+        );
+
+    let mut values = serde_json::Map::new();
+    for &(key, translation) in entries {
+        let parts: Vec<_> = if nested {
+            key.split(style.separator).collect()
+        } else {
+            vec![key]
+        };
+        let clash = || {
+            format!(
+                "Term {:?} cannot be added because it clashes with others.",
+                key
+            )
+        };
+        anyhow::ensure!(!kept.iter().any(|m| m.key == parts[0]), "{}", clash());
+        let (leaf, parents) = parts.split_last().context("Term is empty.")?;
+        let mut map = &mut values;
+        for part in parents {
+            map = map
+                .entry(*part)
+                .or_insert_with(|| serde_json::Value::Object(Default::default()))
+                .as_object_mut()
+                .with_context(clash)?;
+        }
+        anyhow::ensure!(!map.contains_key(*leaf), "{}", clash());
+        map.insert(leaf.to_string(), translation.into());
+    }
+    let entries: Vec<_> = values
+        .iter()
+        .map(|(key, value)| {
+            let value = if multiline {
+                style.value(value, &member_indent)
+            } else {
+                value.to_string()
+            };
+            format!("{}: {}", serde_json::Value::from(key.as_str()), value)
+        })
+        .collect();
+
+    let last = match kept.last() {
+        Some(last) => last,
+        None => {
+            // Only the root object can lose all of its members.
+            if !object.members.is_empty() || !entries.is_empty() {
+                let text = match (entries.is_empty(), multiline) {
+                    (true, _) => String::new(),
+                    (false, true) => format!(
+                        "{nl}{}{}{nl}{}",
+                        member_indent,
+                        entries.join(&format!(",{}{}", style.newline, member_indent)),
+                        object_indent,
+                        nl = style.newline
+                    ),
+                    (false, false) => format!(" {} ", entries.join(", ")),
+                };
+                edits.push(Edit {
+                    start: object.open + 1,
+                    end: object.close,
+                    text,
+                });
+            }
+            return Ok(());
+        }
+    };
+    for member in object.members.iter().filter(|m| is_removed(m, new)) {
+        edits.push(deletion(member, style));
+    }
+
+    let comma_after = |member: &Member| {
+        let rest = &json[member.end..];
+        let trimmed = rest.trim_start();
+        trimmed
+            .starts_with(',')
+            .then(|| member.end + rest.len() - trimmed.len())
+    };
+    let comma = comma_after(last);
+    // Files with trailing commas keep one after the last member.
+    let trailing = object.members.last().and_then(comma_after).is_some();
+    if entries.is_empty() {
+        if let Some(comma) = comma.filter(|_| !trailing) {
+            edits.push(Edit {
+                start: comma,
+                end: comma + 1,
+                text: String::new(),
+            });
+        }
+        return Ok(());
+    }
+    let after_comma = comma.map_or(last.end, |comma| comma + 1);
+    let (at, text) = match style.rest_of_line(after_comma).filter(|_| multiline) {
+        Some(line_end) => {
+            // Before a carriage return, so the line keeps its ending.
+            let line_end = if json[..line_end].ends_with('\r') {
+                line_end - 1
+            } else {
+                line_end
+            };
+            let separator = format!(",{}{}", style.newline, member_indent);
+            let text = format!(
+                "{}{}{}{}",
+                style.newline,
+                member_indent,
+                entries.join(&separator),
+                if trailing { "," } else { "" }
+            );
+            (line_end, text)
+        }
+        None => (after_comma, format!(" {}", entries.join(", "))),
+    };
+    let text = match comma {
+        Some(_) => text,
+        None if at == last.end => format!(",{}", text),
+        None => {
+            edits.push(Edit {
+                start: last.end,
+                end: last.end,
+                text: ",".into(),
+            });
+            text
+        }
+    };
+    edits.push(Edit {
+        start: at,
+        end: at,
+        text,
+    });
+    Ok(())
+}
+
+/// Removes the member with its comma, and with its line if nothing else is on it. A member
+/// without a comma takes the spaces before it instead.
+fn deletion(member: &Member, style: &Style) -> Edit {
+    let json = style.json;
+    match (
+        style.indent_at(member.start),
+        style.rest_of_line(member.end),
+    ) {
+        (Some(_), Some(line_end)) => Edit {
+            start: style.line_start(member.start),
+            end: (line_end + 1).min(json.len()),
+            text: String::new(),
+        },
+        _ => {
+            let is_space = |c| c == ' ' || c == '\t';
+            let rest = json[member.end..].trim_start_matches(is_space);
+            let (start, end) = match rest.strip_prefix(',') {
+                Some(rest) => (
+                    member.start,
+                    json.len() - rest.trim_start_matches(is_space).len(),
+                ),
+                None => (
+                    json[..member.start].trim_end_matches(is_space).len(),
+                    member.end,
+                ),
+            };
+            Edit {
+                start,
+                end,
+                text: String::new(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(text: &str, translations: &[(&str, &str)], nested: bool) -> String {
+        let translations: Vec<_> = translations
+            .iter()
+            .map(|(term, translation)| Translation::new(term.to_string(), translation.to_string()))
+            .collect();
+        update_json(text, &translations, nested, NonStringValues::Error, ".").unwrap()
+    }
+
+    #[test]
+    fn keep_layout_of_json() {
+        let text = "{\n\
+                    \t// Texts of the app\n\
+                    \t\"app\": {\n\
+                    \t\t\"title\": \"Title\", // shown in the title bar\n\
+                    \t\t\"old\": \"Old\"\n\
+                    \t},\n\
+                    \t\"gone\": \"Gone\",\n\
+                    \t\"zebra\": \"Zebra\"\n\
+                    }\n";
+        let translations = [
+            ("zebra", "Zebra"),
+            ("app.title", "Titel \"neu\""),
+            ("app.menu.open", "Öffnen"),
+            ("new", "Neu"),
+        ];
+        assert_eq!(
+            "{\n\
+             \t// Texts of the app\n\
+             \t\"app\": {\n\
+             \t\t\"title\": \"Titel \\\"neu\\\"\", // shown in the title bar\n\
+             \t\t\"menu\": {\n\
+             \t\t\t\"open\": \"Öffnen\"\n\
+             \t\t}\n\
+             \t},\n\
+             \t\"zebra\": \"Zebra\",\n\
+             \t\"new\": \"Neu\"\n\
+             }\n",
+            update(text, &translations, true)
+        );
+        assert_eq!(
+            "{\n\
+             \t// Texts of the app\n\
+             \t\"app\": {\n\
+             \t\t\"title\": \"Titel \\\"neu\\\"\", // shown in the title bar\n\
+             \t\t\"menu.open\": \"Öffnen\"\n\
+             \t},\n\
+             \t\"zebra\": \"Zebra\",\n\
+             \t\"new\": \"Neu\"\n\
+             }\n",
+            update(text, &translations, false)
+        );
+    }
+
+    #[test]
+    fn remove_and_add_terms() {
+        let text = "{\r\n  \"a\": \"1\",\r\n  \"b\": \"2\"\r\n}\r\n";
+        assert_eq!(
+            "{\r\n  \"a\": \"1\"\r\n}\r\n",
+            update(text, &[("a", "1")], false)
+        );
+        assert_eq!(
+            "{\r\n  \"b\": \"2\",\r\n  \"c\": \"3\"\r\n}\r\n",
+            update(text, &[("b", "2"), ("c", "3")], false)
+        );
+        assert_eq!("{}\r\n", update(text, &[], false));
+        assert_eq!(
+            r#"{ "a": "1", "c": "3" }"#,
+            update(
+                r#"{ "a": "1", "b": "2" }"#,
+                &[("a", "1"), ("c", "3")],
+                false
+            )
+        );
+        assert_eq!("{\n  \"a\": \"1\"\n}", update("{}", &[("a", "1")], false));
+        let clash = [
+            Translation::new("a.b".into(), "2".into()),
+            Translation::new("a".into(), "1".into()),
+        ];
+        assert!(update_json(
+            r#"{ "a": { "b": "2" } }"#,
+            &clash,
+            false,
+            NonStringValues::Error,
+            "."
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn update_json5() {
+        let text = "{\n  // 'quoted' comment\n  title: 'Title',\n  'old': \"Old\",\n}\n";
+        assert_eq!(
+            "{\n  // 'quoted' comment\n  title: \"Titel\",\n  \"new\": \"Neu\",\n}\n",
+            update(text, &[("title", "Titel"), ("new", "Neu")], false)
+        );
+        assert_eq!(
+            "{\n  // 'quoted' comment\n  title: 'Title',\n}\n",
+            update(text, &[("title", "Title")], false)
+        );
+    }
+
+    #[test]
+    fn update_non_strings() {
+        let text = r#"{ "count": 3, "empty": null, "list": [1, "]"], "text": "a" }"#;
+        let translations = [
+            Translation::new("count".into(), "4".into()),
+            Translation::new("empty".into(), "".into()),
+        ];
+        assert_eq!(
+            r#"{ "count": "4", "empty": null }"#,
+            update_json(
+                &text.replace(r#", "list": [1, "]"]"#, ""),
+                &translations,
+                false,
+                NonStringValues::Coerce,
+                "."
+            )
+            .unwrap()
+        );
+        assert!(update_json(text, &translations, false, NonStringValues::Coerce, ".").is_err());
+        assert_eq!(
+            r#"{ "count": "4", "empty": null, "list": [1, "]"] }"#,
+            update_json(text, &translations, false, NonStringValues::Skip, ".").unwrap()
+        );
+    }
+
+    #[test]
+    fn add_terms_at_key_separator() {
+        let text = "{\n  \"app\": {\n    \"title\": \"Title\"\n  }\n}\n";
+        let translations = [
+            Translation::new("app/title".into(), "Title".into()),
+            Translation::new("app/menu/open".into(), "Open".into()),
+        ];
+        assert_eq!(
+            "{\n  \"app\": {\n    \"title\": \"Title\",\n    \"menu\": {\n      \"open\": \"Open\"\n    }\n  }\n}\n",
+            update_json(text, &translations, true, NonStringValues::Error, "/").unwrap()
+        );
+    }
+
+    #[test]
+    fn keep_encoding() {
+        let translations = [Translation::new("greeting".into(), "Grüße".into())];
+        let utf8bom = write(
+            Path::new("en.json"),
+            b"\xef\xbb\xbf{ \"greeting\": \"Hallo\" }",
+            None,
+            &translations,
+            false,
+            NonStringValues::Error,
+            ".",
+        )
+        .unwrap();
+        assert_eq!("\u{feff}{ \"greeting\": \"Grüße\" }".as_bytes(), utf8bom);
+        let latin1 = write(
+            Path::new("messages.properties"),
+            b"# Gr\xfc\xdfe\ngreeting=Hallo\n",
+            None,
+            &translations,
+            false,
+            NonStringValues::Error,
+            ".",
+        )
+        .unwrap();
+        assert_eq!(
+            b"# Gr\xfc\xdfe\ngreeting=Gr\\u00fc\\u00dfe\n".to_vec(),
+            latin1
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Project-wide lock that prevents concurrent updates.
+//!
+//! The lock is a marker term on Traduora, so it also covers instances running
+//! on other machines. Its translation in the locale of the holder names the holder and when the
+//! lock was acquired, so locks of crashed instances expire. Instances that update other locales
+//! look the translation up in all locales of the project.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use traduora::api::TermId;
+
+use crate::client::Client;
+
+/// Term that marks the project as locked. It is hidden from the differences.
+pub const LOCK_TERM: &str = "traduora-update.lock";
+
+/// Lock on the configured project. Must be released explicitly because
+/// the client may be replaced while the lock is held.
+#[derive(Debug)]
+pub struct ProjectLock {
+    term_id: TermId,
+}
+
+/// Translation of the lock term.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Marker {
+    holder: String,
+    /// Seconds since the Unix epoch. Unknown for locks of earlier versions, which only stored
+    /// the holder.
+    #[serde(default)]
+    since: Option<u64>,
+}
+
+impl Marker {
+    fn new(holder: String) -> Self {
+        Self {
+            holder,
+            since: Some(now()),
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        serde_json::from_str(value).unwrap_or_else(|_| Self {
+            holder: value.to_string(),
+            since: None,
+        })
+    }
+
+    /// Whether the lock is older than `expiry` at `now`. Locks of unknown age never expire.
+    fn is_stale(&self, now: u64, expiry: Duration) -> bool {
+        self.since
+            .map_or(false, |since| now.saturating_sub(since) >= expiry.as_secs())
+    }
+}
+
+impl std::fmt::Display for Marker {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.since {
+            Some(since) => write!(
+                f,
+                "{} for {} minutes",
+                self.holder,
+                now().saturating_sub(since) / 60
+            ),
+            None => write!(f, "{}", self.holder),
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn holder(client: &Client) -> String {
+    client.config().login().user().to_owned()
+}
+
+fn find_locks(client: &Client) -> Result<Vec<TermId>> {
+    Ok(client
+        .terms()
+        .context("Failed to check whether the project is locked.")?
+        .into_iter()
+        .filter(|t| t.value == LOCK_TERM)
+        .map(|t| t.id)
+        .collect())
+}
+
+/// The marker of the lock, which is searched in the own locale first and then in the other
+/// locales of the project.
+fn find_marker(client: &Client, term_id: &TermId) -> Option<Marker> {
+    let own = client.config().locale();
+    let mut locales = vec![own.clone()];
+    match client.locales() {
+        Ok(others) => locales.extend(
+            others
+                .into_iter()
+                .map(|l| l.locale.code)
+                .filter(|code| code != own),
+        ),
+        Err(e) => log::warn!("Failed to load the locales of the project: {:#}", e),
+    }
+    locales.iter().find_map(|locale| {
+        client
+            .translations_in(locale)
+            .ok()?
+            .into_iter()
+            .find(|t| &t.term_id == term_id)
+            .map(|t| t.value)
+            .filter(|v| !v.is_empty())
+            .map(|v| Marker::parse(&v))
+    })
+}
+
+fn locked(marker: Option<Marker>, expiry: Duration) -> anyhow::Error {
+    let holder = marker.map_or_else(|| "unknown".into(), |m| m.to_string());
+    anyhow::anyhow!(
+        "Another instance is updating the project (held by {}). \
+        If no update is running, delete the term {:?} in Traduora \
+        or wait until the lock expires after {} minutes.",
+        holder,
+        LOCK_TERM,
+        expiry.as_secs() / 60
+    )
+}
+
+/// Acquires the lock or fails if another instance holds it. Stale locks are removed first.
+pub fn acquire(client: &Client) -> Result<ProjectLock> {
+    let expiry = client.config().stale_lock_after();
+    for term_id in find_locks(client)? {
+        match find_marker(client, &term_id) {
+            Some(marker) if marker.is_stale(now(), expiry) => {
+                log::warn!("Removing the stale project lock of {}.", marker);
+                client
+                    .delete_term(term_id)
+                    .context("Failed to remove the stale project lock.")?;
+            }
+            marker => return Err(locked(marker, expiry)),
+        }
+    }
+
+    let term = match client.create_term(LOCK_TERM.into()) {
+        Ok(term) => term,
+        // Another instance created the lock since it was checked.
+        Err(e) if crate::compat::is_conflict(&e) => return Err(locked(None, expiry)),
+        Err(e) => return Err(e.context("Failed to lock the project.")),
+    };
+    log::info!("Locked project with term {:?}.", term.id);
+
+    let lock = ProjectLock { term_id: term.id };
+    let marker = Marker::new(holder(client));
+    let value = serde_json::to_string(&marker)?;
+    if let Err(e) = client.edit_translation(lock.term_id.clone(), value) {
+        log::warn!("Failed to record holder of the project lock: {:?}", e);
+    }
+
+    // Servers that accept duplicate terms do not report the conflict, so the lock only counts
+    // if no other instance created one at the same time.
+    let others = find_locks(client)?
+        .into_iter()
+        .any(|term_id| term_id != lock.term_id);
+    if others {
+        if let Err(e) = lock.release(client) {
+            log::error!("{:?}", e);
+        }
+        return Err(locked(None, expiry));
+    }
+    Ok(lock)
+}
+
+impl ProjectLock {
+    pub fn release(self, client: &Client) -> Result<()> {
+        client.delete_term(self.term_id.clone()).with_context(|| {
+            format!(
+                "Failed to unlock the project. Delete the term {:?} in Traduora.",
+                LOCK_TERM
+            )
+        })?;
+        log::info!("Unlocked project.");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expire_stale_locks() {
+        let marker = Marker {
+            holder: "user@example.com".into(),
+            since: Some(1_000),
+        };
+        let stored = serde_json::to_string(&marker).unwrap();
+        assert_eq!(marker, Marker::parse(&stored));
+
+        let hour = Duration::from_secs(3600);
+        assert!(!marker.is_stale(1_000 + 3599, hour));
+        assert!(marker.is_stale(1_000 + 3600, hour));
+
+        let earlier = Marker::parse("user@example.com");
+        assert_eq!("user@example.com", earlier.holder);
+        assert!(!earlier.is_stale(u64::MAX, hour));
+    }
+}
@@ -0,0 +1,98 @@
+//! Logging to stderr and to a buffer that can be shown in the window.
+//!
+//! The level is taken from `RUST_LOG` unless it is overridden on the command line
+//! with `-v` or `-q`. The buffer only receives the messages that pass the same filter.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use log::{LevelFilter, Log, Metadata, Record};
+use once_cell::sync::Lazy;
+
+/// Number of messages kept in the buffer. Older messages are dropped.
+const CAPACITY: usize = 1000;
+
+static BUFFER: Lazy<Mutex<VecDeque<String>>> = Lazy::new(Default::default);
+
+struct Logger {
+    inner: env_logger::Logger,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        self.inner.log(record);
+        if let Ok(mut buffer) = BUFFER.lock() {
+            if buffer.len() == CAPACITY {
+                buffer.pop_front();
+            }
+            buffer.push_back(format!(
+                "[{} {}] {}",
+                record.level(),
+                record.target(),
+                record.args()
+            ));
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Level for the number of `-v` (positive) or `-q` (negative) flags, `None` without flags.
+fn level(verbosity: i8) -> Option<LevelFilter> {
+    match verbosity {
+        0 => None,
+        i8::MIN..=-1 => Some(LevelFilter::Off),
+        1 => Some(LevelFilter::Info),
+        2 => Some(LevelFilter::Debug),
+        _ => Some(LevelFilter::Trace),
+    }
+}
+
+pub fn init(verbosity: i8) {
+    let mut builder = env_logger::Builder::from_default_env();
+    if let Some(level) = level(verbosity) {
+        builder.filter_level(level);
+    }
+    let inner = builder.build();
+    let max_level = inner.filter();
+    if log::set_boxed_logger(Box::new(Logger { inner })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// All buffered messages, oldest first.
+pub fn buffered() -> String {
+    BUFFER
+        .lock()
+        .map(|buffer| {
+            buffer
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbosity_to_level() {
+        assert_eq!(None, level(0));
+        assert_eq!(Some(LevelFilter::Off), level(-1));
+        assert_eq!(Some(LevelFilter::Info), level(1));
+        assert_eq!(Some(LevelFilter::Debug), level(2));
+        assert_eq!(Some(LevelFilter::Trace), level(5));
+    }
+}
@@ -1,51 +1,525 @@
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
 use anyhow::{Context, Result};
 use druid::{AppLauncher, PlatformError, WindowDesc};
 
+use cli::{Cli, Command};
+use config::AppConfig;
+use exit_code::ExitCode;
+
+mod atomic;
+mod browser;
+mod cli;
+mod client;
+mod column;
+mod compat;
+mod completions;
 mod config;
+mod diff;
+mod dry_run;
+mod exit_code;
+mod expander;
+mod export;
+mod geometry;
+mod icu;
+mod json5;
+mod keyring;
 mod layout;
+mod lazy;
 mod loader;
+mod lock;
+mod logging;
 mod modal_host;
+mod notification;
+mod palette;
+mod picker;
+mod preflight;
+mod report;
+mod secret;
+mod strings;
+mod terminal;
+mod tooltip;
+mod transport;
+mod tri_state;
 mod updater;
+mod virtual_list;
+mod warnings;
+mod wizard;
 
 fn main() -> Result<()> {
-    env_logger::init();
+    let cli = cli::init();
+    logging::init(cli.verbosity);
+
+    match &cli.command {
+        Command::Gui => {}
+        Command::Diff => run_headless(&cli, |config| diff(config, &cli)),
+        Command::Apply => run_headless(&cli, |config| apply(config, &cli)),
+        Command::Check => run_headless(&cli, |config| check(config, &cli)),
+        Command::Terminal => run_terminal(&cli),
+        Command::Export { output } => run_export(&cli, output.as_deref()),
+        Command::ListProjects => list_projects(&cli),
+        Command::ListLocales => list_locales(&cli),
+        Command::Pick => pick(&cli),
+        Command::Preflight => run_preflight(&cli),
+        Command::StoreCredentials => store_credentials(&cli),
+        Command::EncryptSecret => return encrypt_secret(),
+        Command::ValidateConfig { ping } => validate_config(&cli, *ping),
+        Command::Schema(file) => return write_schema(file),
+        Command::Completions(shell) => {
+            print!("{}", completions::script(*shell));
+            return Ok(());
+        }
+        Command::Bench(iterations) => return run_bench(&cli, *iterations),
+    }
+
+    let state = match config::load(&cli) {
+        Ok(config) => {
+            strings::init(config.language());
+            let config = Arc::new(config);
+            // The startup screen asks for the passphrase before anything is loaded.
+            let loaded = if secret::needs_passphrase(&config) {
+                Err(anyhow::anyhow!(
+                    "No passphrase was entered for the encrypted secrets."
+                ))
+            } else {
+                preflight::run(&config)
+                    .into_result()
+                    .and_then(|_| loader::load_data(&config))
+            };
+            match loaded {
+                Ok(data) => layout::AppState::build(config, data),
+                Err(e) => layout::AppState::startup_failed(e, Some(config)),
+            }
+        }
+        // Without any config file, the setup wizard creates one.
+        Err(_) if config::find_file(&cli).is_err() => layout::AppState::first_run(),
+        Err(e) => layout::AppState::startup_failed(e, None),
+    };
+    run(state, cli).map_err(Into::into)
+}
+
+fn write_schema(schema_file: &Path) -> Result<()> {
+    let schema =
+        serde_json::to_string_pretty(&config::schema()).context("Failed to generate schema.")?;
+    atomic::write(schema_file, schema).context("Failed to save schema to file.")
+}
+
+fn run_preflight(cli: &Cli) -> ! {
+    run_headless(cli, |config| {
+        let report = preflight::run(config);
+        println!("{}", report);
+        report.exit_code().unwrap_or(ExitCode::NoChanges)
+    })
+}
+
+/// Asks for the secret of the configured login, stores it in the keyring and checks the login.
+fn store_credentials(cli: &Cli) -> ! {
+    let config = init_config(cli);
+    let login = config.login();
+    let keyring = match login.keyring() {
+        Some(keyring) => keyring,
+        None => {
+            eprintln!(
+                "The config file contains the secret. Replace password or client_secret with \
+                 \"keyring\": \"<name of the entry>\" to read it from the keyring."
+            );
+            ExitCode::Config.exit();
+        }
+    };
+    println!(
+        "Storing the secret of {} in keyring entry {}.",
+        login.user(),
+        keyring
+    );
+    if let Err(e) = keyring::store(keyring, login.user()) {
+        eprintln!("{:?}", e);
+        ExitCode::Failure.exit();
+    }
+    match config::create_client(&config) {
+        Ok(_) => {
+            println!("Stored the secret, the login to Traduora succeeded.");
+            ExitCode::NoChanges.exit();
+        }
+        Err(e) => {
+            eprintln!(
+                "Stored the secret, but the login to Traduora failed: {:?}",
+                e
+            );
+            ExitCode::classify(&e, ExitCode::Failure).exit();
+        }
+    }
+}
+
+/// Asks for a passphrase and a secret and prints the secret encrypted for the config file.
+fn encrypt_secret() -> Result<()> {
+    let passphrase = secret::ask_on_terminal("Passphrase: ")?;
+    anyhow::ensure!(
+        passphrase == secret::ask_on_terminal("Repeat the passphrase: ")?,
+        "The passphrases differ."
+    );
+    let encrypted = secret::encrypt(
+        &secret::ask_on_terminal("Password or client secret: ")?,
+        &passphrase,
+    )?;
+    println!("{}", encrypted);
+    Ok(())
+}
 
-    if write_schema()? {
-        return Ok(());
+/// Asks for the passphrase if the configuration contains encrypted secrets.
+fn unlock_on_terminal(config: &AppConfig) -> Result<()> {
+    if secret::needs_passphrase(config) {
+        secret::unlock(
+            config,
+            &secret::ask_on_terminal("Passphrase of the encrypted secrets: ")?,
+        )?;
     }
+    Ok(())
+}
 
-    let config_result = config::init();
-    match config_result.and_then(|_| loader::load_data()) {
-        Ok(data) => run(data),
-        Err(e) => run_startup_failed(e),
+/// Reports all problems of the configuration at once instead of stopping at the first one.
+fn validate_config(cli: &Cli, ping: bool) -> ! {
+    let config_file = match config::find_file(cli) {
+        Ok(config_file) => config_file,
+        Err(e) => {
+            eprintln!("{:?}", e);
+            ExitCode::Config.exit();
+        }
+    };
+    println!("Validating {}", config_file.display());
+    let problems = config::validate(&config_file);
+    for problem in &problems {
+        println!("[FAIL] {}", problem);
+    }
+    if !problems.is_empty() {
+        ExitCode::Config.exit();
+    }
+    let config = match config::load_from(&config_file, cli) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("[FAIL] {:#}", e);
+            ExitCode::Config.exit();
+        }
+    };
+    if ping {
+        if let Err(e) = unlock_on_terminal(&config) {
+            println!("[FAIL] {:#}", e);
+            ExitCode::Config.exit();
+        }
     }
-    .map_err(Into::into)
+    let report = preflight::run_checks(&config, ping);
+    print!("{}", report);
+    report.exit_code().unwrap_or(ExitCode::NoChanges).exit();
 }
 
-fn write_schema() -> Result<bool> {
-    use itertools::Itertools;
+/// Reads the configuration for a mode without a window or exits if it is invalid.
+fn init_config(cli: &Cli) -> AppConfig {
+    match config::load(cli).and_then(|config| {
+        unlock_on_terminal(&config)?;
+        Ok(config)
+    }) {
+        Ok(config) => {
+            strings::init(config.language());
+            config
+        }
+        Err(e) => {
+            eprintln!("{:?}", e);
+            ExitCode::Config.exit();
+        }
+    }
+}
 
-    std::env::args_os()
-        .tuple_windows()
-        .find_map(|(pred, succ)| (pred == "--generate-config-schema").then(|| succ))
-        .map_or(Ok(false), |schema_file| {
-            let schema = schemars::schema_for!(config::AppConfig);
-            let schema =
-                serde_json::to_string_pretty(&schema).context("Failed to generate schema.")?;
-            std::fs::write(schema_file, schema).context("Failed to save schema to file.")?;
-            Ok(true)
-        })
+/// Reads the configuration and runs a mode without a window for every configured locale.
+fn run_headless(cli: &Cli, run: impl Fn(&AppConfig) -> ExitCode) -> ! {
+    for_each_locale(&init_config(cli), run)
 }
 
-fn run(data: Vec<loader::Translation>) -> Result<(), PlatformError> {
-    let state = layout::AppState::build(data);
-    let main_window = WindowDesc::new(layout::build_ui).title("Traduora-Update");
-    AppLauncher::with_window(main_window)
-        .delegate(layout::Delegate)
-        .launch(state)
+/// Runs `run` for every configured locale and exits with the combined exit code.
+/// With several locales, the output of each one starts with a heading.
+fn for_each_locale(config: &AppConfig, run: impl Fn(&AppConfig) -> ExitCode) -> ! {
+    let count = config.locales().count();
+    let mut code = ExitCode::NoChanges;
+    for index in 0..count {
+        let config = config.with_locale(index);
+        if count > 1 {
+            println!("== {} ==", config.locale());
+        }
+        code = code.and(run(&config));
+    }
+    code.exit();
+}
+
+/// Determines the differences for a mode without a window, restricted to the terms selected on
+/// the command line. Prints the error if that fails.
+fn load_headless(config: &AppConfig, cli: &Cli) -> Result<loader::LoadedData, ExitCode> {
+    let report = preflight::run(config);
+    if let Some(code) = report.exit_code() {
+        eprint!("Pre-flight checks failed:\n{}", report);
+        return Err(code);
+    }
+    match loader::load_data(config) {
+        Ok(mut data) => {
+            data.translations.retain(|t| cli.terms.matches(&t.term));
+            if let Some(path) = &cli.report {
+                let path = if config.locales().count() > 1 {
+                    report::path_for_locale(path, config.locale().value())
+                } else {
+                    path.clone()
+                };
+                if let Err(e) = report::write(config, path, &data) {
+                    eprintln!("{:?}", e);
+                    return Err(ExitCode::Failure);
+                }
+            }
+            for path in &data.uncommitted {
+                eprintln!("{} has uncommitted changes.", path.display());
+            }
+            if config.icu_messages() {
+                let invalid = warnings::invalid_messages(&data.translations);
+                if !invalid.is_empty() {
+                    eprintln!("Some translations are no valid ICU messages:");
+                    for (t, error) in invalid {
+                        eprintln!("  {:?}: {}", t.term, error);
+                    }
+                    return Err(ExitCode::Config);
+                }
+            }
+            Ok(data)
+        }
+        Err(e) => {
+            eprintln!("{:?}", e);
+            Err(ExitCode::classify(&e, ExitCode::Config))
+        }
+    }
+}
+
+/// Prints the operations an update would perform without changing anything on Traduora.
+fn diff(config: &AppConfig, cli: &Cli) -> ExitCode {
+    let data = match load_headless(config, cli) {
+        Ok(data) => data,
+        Err(code) => return code,
+    };
+    if data.offline {
+        eprintln!("Traduora is unreachable, the planned operations are based on cached data.");
+    }
+    print!("{}", dry_run::Plan(&data.translations));
+    if data.translations.is_empty() {
+        ExitCode::NoChanges
+    } else {
+        ExitCode::Changes
+    }
 }
 
-fn run_startup_failed(err: anyhow::Error) -> Result<(), PlatformError> {
-    let window = WindowDesc::new(layout::build_ui_startup_failed).title("Traduora-Update");
-    AppLauncher::with_window(window).launch(err.into())
+/// Fails if the local translations and Traduora differ, e.g. to gate merges in a CI pipeline.
+fn check(config: &AppConfig, cli: &Cli) -> ExitCode {
+    let data = match load_headless(config, cli) {
+        Ok(data) => data,
+        Err(code) => return code,
+    };
+    if data.offline {
+        eprintln!("Traduora is unreachable, so it cannot be checked.");
+        return ExitCode::Network;
+    }
+    if data.translations.is_empty() {
+        println!("Traduora is in sync with the local translations.");
+        return ExitCode::NoChanges;
+    }
+    println!("Traduora is out of sync with the local translations:");
+    print!("{}", dry_run::Plan(&data.translations));
+    ExitCode::Changes
+}
+
+/// Applies all differences to Traduora without asking, e.g. in a CI pipeline.
+fn apply(config: &AppConfig, cli: &Cli) -> ExitCode {
+    let data = match load_headless(config, cli) {
+        Ok(data) => data,
+        Err(code) => return code,
+    };
+    if data.translations.is_empty() {
+        println!("Traduora is up to date.");
+        return ExitCode::NoChanges;
+    }
+    if data.offline {
+        eprintln!("Traduora is unreachable, updating terms is disabled.");
+        return ExitCode::Network;
+    }
+
+    let progress = |p: updater::Progress| match p.step {
+        updater::Step::Started => {}
+        updater::Step::Succeeded | updater::Step::Failed => println!(
+            "[{}/{}] {:?} {:?}: {}",
+            p.current,
+            p.total,
+            p.operation,
+            p.term,
+            if p.step == updater::Step::Succeeded {
+                "done"
+            } else {
+                "failed"
+            }
+        ),
+    };
+    // Nobody can enter new login data, so an expired login fails the remaining items.
+    match updater::run(
+        config,
+        data.translations,
+        progress,
+        || None,
+        &AtomicBool::new(false),
+    ) {
+        Ok(()) => ExitCode::Changes,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::of_update(&e)
+        }
+    }
+}
+
+/// Writes the state of Traduora to the translation file, the opposite direction of an update.
+fn run_export(cli: &Cli, output: Option<&Path>) -> ! {
+    let config = init_config(cli);
+    if output.is_some() && config.locales().count() > 1 {
+        eprintln!("Option --output needs --locale because several locales are configured.");
+        ExitCode::Failure.exit();
+    }
+    for_each_locale(&config, |config| {
+        let outputs = match output {
+            Some(output) => vec![output.to_owned()],
+            None => config.translation_files().to_vec(),
+        };
+        match loader::export_to_files(config, &outputs) {
+            Ok(count) if !loader::is_stdin(&outputs[0]) => {
+                let outputs: Vec<_> = outputs.iter().map(|o| o.display().to_string()).collect();
+                println!("Exported {} terms to {}.", count, outputs.join(", "));
+                ExitCode::NoChanges
+            }
+            Ok(_) => ExitCode::NoChanges,
+            Err(e) => {
+                eprintln!("{:?}", e);
+                ExitCode::classify(&e, ExitCode::Failure)
+            }
+        }
+    })
+}
+
+/// Prints the projects to help with filling in `project_id` of the configuration.
+fn list_projects(cli: &Cli) -> ! {
+    let config = init_config(cli);
+    let projects = config::create_client(&config).and_then(|client| client.projects());
+    match projects {
+        Ok(projects) => {
+            for project in projects {
+                println!("{}  {}", project.id, project.name);
+            }
+            ExitCode::NoChanges.exit();
+        }
+        Err(e) => {
+            eprintln!("{:?}", e);
+            ExitCode::classify(&e, ExitCode::Failure).exit();
+        }
+    }
+}
+
+/// Prints the locales of the project to help with filling in `locale` of the configuration.
+fn list_locales(cli: &Cli) -> ! {
+    let config = init_config(cli);
+    let locales = config::create_client(&config).and_then(|client| client.locales());
+    match locales {
+        Ok(locales) => {
+            for l in locales {
+                println!(
+                    "{:<8} {} {}",
+                    l.locale.code.value(),
+                    l.locale.language,
+                    l.locale.region
+                );
+            }
+            ExitCode::NoChanges.exit();
+        }
+        Err(e) => {
+            eprintln!("{:?}", e);
+            ExitCode::classify(&e, ExitCode::Failure).exit();
+        }
+    }
+}
+
+/// Asks for a project and one of its locales on Traduora and prints how to configure them.
+fn pick(cli: &Cli) -> ! {
+    let config = init_config(cli);
+    match pick_interactively(&config, cli) {
+        Ok(()) => ExitCode::NoChanges.exit(),
+        Err(e) => {
+            eprintln!("{:?}", e);
+            ExitCode::classify(&e, ExitCode::Failure).exit();
+        }
+    }
+}
+
+fn pick_interactively(config: &AppConfig, cli: &Cli) -> Result<()> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let projects = picker::fetch_projects(config)?;
+    let project = picker::prompt(stdin.lock(), stdout.lock(), "Project", &projects)?;
+    let locales = picker::fetch_locales(&config.with_remote_project(&project.value, ""))?;
+    let locale = picker::prompt(stdin.lock(), stdout.lock(), "Locale", &locales)?;
+    let properties = serde_json::json!({
+        "project_id": project.value,
+        "files": { locale.value.as_str(): config.translation_file().to_string_lossy() },
+    });
+    println!(
+        "\nUse it in the config file:\n{}",
+        config::snippet(&config::find_file(cli)?, &properties)?.trim_end()
+    );
+    println!(
+        "or once on the command line:\n  --project-id {} --locale {}",
+        project.value, locale.value
+    );
+    Ok(())
+}
+
+/// Interactive mode in the terminal for machines without a graphical session.
+fn run_terminal(cli: &Cli) -> ! {
+    run_headless(cli, |config| match load_headless(config, cli) {
+        Ok(data) => match terminal::run(config, data) {
+            Ok(()) => ExitCode::NoChanges,
+            Err(e) => {
+                eprintln!("{:?}", e);
+                ExitCode::Failure
+            }
+        },
+        Err(code) => code,
+    })
+}
+
+/// Hidden mode to measure load and merge performance.
+fn run_bench(cli: &Cli, iterations: usize) -> Result<()> {
+    let config = config::load(cli)?;
+    unlock_on_terminal(&config)?;
+    let report = loader::bench::run(&config, iterations)?;
+    print!("{}", report);
+    Ok(())
+}
+
+fn run(state: layout::AppState, cli: Cli) -> Result<(), PlatformError> {
+    let mut main_window = WindowDesc::new(layout::build_root)
+        .title("Traduora-Update")
+        .menu(layout::build_menu(state.try_config()));
+    if let Some(geometry) = geometry::load() {
+        main_window = main_window
+            .window_size(geometry.size())
+            .set_position(geometry.position());
+    }
+    // Without a valid configuration, the default look is used.
+    let launcher = AppLauncher::with_window(main_window).configure_env(|env, state| {
+        match state.try_config() {
+            Some(config) => {
+                palette::apply(env, config.theme());
+                palette::scale(env, config.ui_scale());
+            }
+            None => palette::apply(env, Default::default()),
+        }
+    });
+    let delegate = layout::Delegate::new(launcher.get_external_handle(), Arc::new(cli));
+    launcher.delegate(delegate).launch(state)
 }
@@ -0,0 +1,64 @@
+//! Desktop notifications for events that happen while the user is busy elsewhere.
+//!
+//! The windowing toolkit does not report when the window loses focus, so the window
+//! counts as unattended while the mouse is outside of it, which includes the window
+//! being minimized.
+
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result};
+use druid::widget::{prelude::*, Controller};
+
+static POINTER_INSIDE: AtomicBool = AtomicBool::new(false);
+
+/// Whether the user is probably not looking at the window.
+pub fn unattended() -> bool {
+    !POINTER_INSIDE.load(Ordering::Relaxed)
+}
+
+/// Shows a notification with the native notification service of the desktop.
+pub fn send(title: &str, body: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {} with title {}",
+            quote(body),
+            quote(title)
+        ));
+        command
+    } else if cfg!(unix) {
+        let mut command = Command::new("notify-send");
+        command
+            .arg("--app-name=Traduora-Update")
+            .arg(title)
+            .arg(body);
+        command
+    } else {
+        anyhow::bail!("Desktop notifications are not supported on this platform.");
+    };
+    command
+        .spawn()
+        .with_context(|| format!("Failed to run {:?}", command))?;
+    Ok(())
+}
+
+/// Keeps track of whether the mouse is over the widget, which should span the whole window.
+pub struct Presence;
+
+impl<T, W: Widget<T>> Controller<T, W> for Presence {
+    fn lifecycle(
+        &mut self,
+        child: &mut W,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &T,
+        env: &Env,
+    ) {
+        if let LifeCycle::HotChanged(hot) = event {
+            POINTER_INSIDE.store(*hot, Ordering::Relaxed);
+        }
+        child.lifecycle(ctx, event, data, env);
+    }
+}
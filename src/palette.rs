@@ -0,0 +1,98 @@
+//! Color palettes and scaling of the user interface.
+//!
+//! The dark palette is the druid default, the light and high contrast palettes override its colors.
+
+use druid::{theme, Color, Env, Key};
+
+use crate::config::Theme;
+
+fn light(env: &mut Env) {
+    env.set(
+        theme::WINDOW_BACKGROUND_COLOR,
+        Color::rgb8(0xf5, 0xf5, 0xf5),
+    );
+    env.set(theme::LABEL_COLOR, Color::rgb8(0x1e, 0x1e, 0x1e));
+    env.set(theme::PLACEHOLDER_COLOR, Color::rgb8(0x70, 0x70, 0x70));
+    env.set(theme::PRIMARY_LIGHT, Color::rgb8(0x00, 0x8d, 0xdd));
+    env.set(theme::PRIMARY_DARK, Color::rgb8(0x00, 0x5f, 0x99));
+    env.set(theme::BACKGROUND_LIGHT, Color::rgb8(0xff, 0xff, 0xff));
+    env.set(theme::BACKGROUND_DARK, Color::rgb8(0xe4, 0xe4, 0xe4));
+    env.set(theme::FOREGROUND_LIGHT, Color::rgb8(0x1e, 0x1e, 0x1e));
+    env.set(theme::FOREGROUND_DARK, Color::rgb8(0x50, 0x50, 0x50));
+    env.set(theme::BUTTON_DARK, Color::rgb8(0xd0, 0xd0, 0xd0));
+    env.set(theme::BUTTON_LIGHT, Color::rgb8(0xf8, 0xf8, 0xf8));
+    env.set(theme::BORDER_DARK, Color::rgb8(0xb0, 0xb0, 0xb0));
+    env.set(theme::BORDER_LIGHT, Color::rgb8(0x60, 0x60, 0x60));
+    env.set(theme::SELECTION_COLOR, Color::rgb8(0x9f, 0xd3, 0xff));
+    env.set(theme::SELECTION_TEXT_COLOR, Color::rgb8(0x00, 0x00, 0x00));
+    env.set(theme::CURSOR_COLOR, Color::BLACK);
+    env.set(theme::SCROLLBAR_COLOR, Color::rgb8(0x40, 0x40, 0x40));
+    env.set(theme::SCROLLBAR_BORDER_COLOR, Color::rgb8(0xa0, 0xa0, 0xa0));
+}
+
+/// Pure black and white with yellow highlights, following common high contrast themes.
+fn high_contrast(env: &mut Env) {
+    let yellow = Color::rgb8(0xff, 0xff, 0x00);
+    env.set(theme::WINDOW_BACKGROUND_COLOR, Color::BLACK);
+    env.set(theme::LABEL_COLOR, Color::WHITE);
+    env.set(theme::PLACEHOLDER_COLOR, Color::rgb8(0xc0, 0xc0, 0xc0));
+    env.set(theme::PRIMARY_LIGHT, yellow.clone());
+    env.set(theme::PRIMARY_DARK, yellow.clone());
+    env.set(theme::BACKGROUND_LIGHT, Color::BLACK);
+    env.set(theme::BACKGROUND_DARK, Color::BLACK);
+    env.set(theme::FOREGROUND_LIGHT, Color::WHITE);
+    env.set(theme::FOREGROUND_DARK, Color::WHITE);
+    env.set(theme::BUTTON_DARK, Color::BLACK);
+    env.set(theme::BUTTON_LIGHT, Color::BLACK);
+    env.set(theme::BORDER_DARK, Color::WHITE);
+    env.set(theme::BORDER_LIGHT, yellow.clone());
+    env.set(theme::SELECTION_COLOR, yellow.clone());
+    env.set(theme::SELECTION_TEXT_COLOR, Color::BLACK);
+    env.set(theme::CURSOR_COLOR, yellow);
+    env.set(theme::SCROLLBAR_COLOR, Color::WHITE);
+    env.set(theme::SCROLLBAR_BORDER_COLOR, Color::BLACK);
+    env.set(theme::BUTTON_BORDER_WIDTH, 2.);
+    env.set(theme::TEXTBOX_BORDER_WIDTH, 2.);
+}
+
+/// Edge length of checkboxes, which is unrelated to the height of other widgets so they
+/// can be enlarged on their own.
+pub const CHECKBOX_SIZE: Key<f64> = Key::new("me.erik-hennig.traduora-update.checkbox-size");
+
+/// Edge length of checkboxes before scaling.
+pub fn checkbox_size(theme: Theme) -> f64 {
+    match theme {
+        Theme::Dark | Theme::Light => 18.,
+        Theme::HighContrast => 28.,
+    }
+}
+
+/// Multiplies text sizes and widget dimensions with `factor`.
+pub fn scale(env: &mut Env, factor: f64) {
+    let keys = [
+        theme::TEXT_SIZE_NORMAL,
+        theme::TEXT_SIZE_LARGE,
+        theme::BASIC_WIDGET_HEIGHT,
+        theme::WIDE_WIDGET_WIDTH,
+        theme::BORDERED_WIDGET_HEIGHT,
+        theme::SCROLLBAR_WIDTH,
+        theme::WIDGET_PADDING_VERTICAL,
+        theme::WIDGET_PADDING_HORIZONTAL,
+        theme::WIDGET_CONTROL_COMPONENT_PADDING,
+        CHECKBOX_SIZE,
+    ];
+    for key in keys {
+        let value = env.get(&key);
+        env.set(key, value * factor);
+    }
+}
+
+/// Applies the palette of `theme` to the environment.
+pub fn apply(env: &mut Env, theme: Theme) {
+    match theme {
+        Theme::Dark => {}
+        Theme::Light => light(env),
+        Theme::HighContrast => high_contrast(env),
+    }
+    env.set(CHECKBOX_SIZE, checkbox_size(theme));
+}
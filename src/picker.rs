@@ -0,0 +1,294 @@
+//! Lists the projects of the account and the locales of a project on Traduora, so they can be
+//! chosen instead of copying their ids out of the web interface.
+
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use druid::widget::{
+    Controller, CrossAxisAlignment, Either, Flex, Label, LineBreaking, List, Scroll,
+};
+use druid::{
+    im, Data, Env, Event, EventCtx, ExtEventSink, Lens, Selector, SingleUse, Target, Widget,
+    WidgetExt,
+};
+
+use crate::config::AppConfig;
+use crate::strings;
+
+const PROJECTS_LOADED: Selector<SingleUse<Result<Vec<Choice>>>> =
+    Selector::new("me.erik-hennig.traduora-update.picker-projects-loaded");
+
+const LOCALES_LOADED: Selector<SingleUse<Result<Vec<Choice>>>> =
+    Selector::new("me.erik-hennig.traduora-update.picker-locales-loaded");
+
+/// A project or locale on the server.
+#[derive(Data, Debug, Clone, PartialEq)]
+pub struct Choice {
+    pub value: String,
+    pub label: String,
+}
+
+/// Projects the account has access to.
+pub fn fetch_projects(config: &AppConfig) -> Result<Vec<Choice>> {
+    let projects = crate::config::create_client(config)?.projects()?;
+    Ok(projects
+        .into_iter()
+        .map(|p| Choice {
+            label: format!("{} ({})", p.name, p.id),
+            value: p.id,
+        })
+        .collect())
+}
+
+/// Locales of the configured project.
+pub fn fetch_locales(config: &AppConfig) -> Result<Vec<Choice>> {
+    let locales = crate::config::create_client(config)?.locales()?;
+    Ok(locales
+        .into_iter()
+        .map(|l| Choice {
+            label: format!(
+                "{} {} {}",
+                l.locale.code.value(),
+                l.locale.language,
+                l.locale.region
+            ),
+            value: l.locale.code.value().to_string(),
+        })
+        .collect())
+}
+
+/// Prints the numbered choices and reads the number of one from `input` until it is valid.
+pub fn prompt<'a>(
+    mut input: impl BufRead,
+    mut output: impl Write,
+    question: &str,
+    choices: &'a [Choice],
+) -> Result<&'a Choice> {
+    anyhow::ensure!(!choices.is_empty(), "There is nothing to choose from.");
+    for (i, choice) in choices.iter().enumerate() {
+        writeln!(output, "{:>3}) {}", i + 1, choice.label)?;
+    }
+    loop {
+        write!(output, "{} [1-{}]: ", question, choices.len())?;
+        output.flush()?;
+        let mut line = String::new();
+        let read = input
+            .read_line(&mut line)
+            .context("Failed to read the choice.")?;
+        anyhow::ensure!(read > 0, "Nothing was chosen.");
+        match line.trim().parse::<usize>() {
+            Ok(n) if (1..=choices.len()).contains(&n) => return Ok(&choices[n - 1]),
+            _ => writeln!(output, "Enter a number between 1 and {}.", choices.len())?,
+        }
+    }
+}
+
+/// State of the lists to choose a project and one of its locales.
+#[derive(Data, Debug, Clone, Default, Lens)]
+pub struct Picker {
+    projects: im::Vector<Choice>,
+    project_id: String,
+    locales: im::Vector<Choice>,
+    locale: String,
+    /// Login and host that Traduora is queried with.
+    #[data(ignore)]
+    #[lens(ignore)]
+    config: Option<Arc<AppConfig>>,
+    /// Whether a request to Traduora is running.
+    busy: bool,
+    /// Progress or the error of the last request.
+    status: String,
+}
+
+impl Picker {
+    /// Fetches the projects of the account in the background. Previous choices are dropped.
+    pub fn load_projects(&mut self, sink: ExtEventSink, config: AppConfig) {
+        *self = Self {
+            busy: true,
+            status: strings::get().picker_loading_projects.into(),
+            ..Self::default()
+        };
+        let config = Arc::new(config);
+        self.config = Some(Arc::clone(&config));
+        std::thread::spawn(move || {
+            let result = fetch_projects(&config);
+            sink.submit_command(PROJECTS_LOADED, SingleUse::new(result), Target::Auto)
+                .expect("Failed to submit projects loaded command.");
+        });
+    }
+
+    /// Fetches the locales of the chosen project in the background.
+    fn load_locales(&mut self, sink: ExtEventSink) {
+        let config = match &self.config {
+            Some(config) => config.with_remote_project(&self.project_id, ""),
+            None => return,
+        };
+        self.busy = true;
+        self.status = strings::get().picker_loading_locales.into();
+        self.locales.clear();
+        self.locale.clear();
+        std::thread::spawn(move || {
+            let result = fetch_locales(&config);
+            sink.submit_command(LOCALES_LOADED, SingleUse::new(result), Target::Auto)
+                .expect("Failed to submit locales loaded command.");
+        });
+    }
+
+    /// Shows the error of a finished request. The choices are empty then.
+    fn finish_request(&mut self, result: Result<Vec<Choice>>) -> im::Vector<Choice> {
+        self.busy = false;
+        match result {
+            Ok(choices) => {
+                self.status.clear();
+                choices.into()
+            }
+            Err(e) => {
+                log::error!("Failed to query Traduora for the choices: {:?}", e);
+                self.status = (strings::get().picker_failed)(&e);
+                im::Vector::new()
+            }
+        }
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.busy
+    }
+
+    /// The chosen project id and locale once both are chosen.
+    pub fn chosen(&self) -> Option<(&str, &str)> {
+        if self.busy || self.project_id.is_empty() || self.locale.is_empty() {
+            None
+        } else {
+            Some((&self.project_id, &self.locale))
+        }
+    }
+}
+
+/// Handles the results of the background requests and loads the locales once a project is chosen.
+struct PickerController;
+
+impl<W: Widget<Picker>> Controller<Picker, W> for PickerController {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut Picker,
+        env: &Env,
+    ) {
+        if let Event::Command(cmd) = event {
+            if let Some(result) = cmd.get(PROJECTS_LOADED).and_then(SingleUse::take) {
+                data.projects = data.finish_request(result);
+                ctx.set_handled();
+                return;
+            }
+            if let Some(result) = cmd.get(LOCALES_LOADED).and_then(SingleUse::take) {
+                data.locales = data.finish_request(result);
+                ctx.set_handled();
+                return;
+            }
+        }
+
+        let project_id = data.project_id.clone();
+        child.event(ctx, event, data, env);
+        if data.project_id != project_id && !data.project_id.is_empty() {
+            data.load_locales(ctx.get_external_handle());
+        }
+    }
+}
+
+/// Radio list to pick one of the choices, whose value is stored in the string.
+fn build_choices() -> impl Widget<(String, im::Vector<Choice>)> {
+    Scroll::new(List::new(|| {
+        Label::new(|(selected, choice): &(String, Choice), _: &Env| {
+            let mark = if *selected == choice.value {
+                "●"
+            } else {
+                "○"
+            };
+            format!("{} {}", mark, choice.label)
+        })
+        .padding((0., 2.))
+        .expand_width()
+        .on_click(|_, (selected, choice): &mut (String, Choice), _| {
+            *selected = choice.value.clone();
+        })
+    }))
+    .vertical()
+    .fix_height(120.)
+}
+
+fn labeled(label: &str, input: impl Widget<Picker> + 'static) -> impl Widget<Picker> {
+    Flex::row()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(label).fix_width(150.))
+        .with_child(input)
+}
+
+/// Lists of the projects and, once one is chosen, of its locales.
+pub fn build() -> impl Widget<Picker> {
+    let projects = Either::new(
+        |p: &Picker, _: &_| p.projects.is_empty(),
+        Flex::column(),
+        labeled(
+            strings::get().picker_project,
+            build_choices().fix_width(400.).lens(druid::lens::Map::new(
+                |p: &Picker| (p.project_id.clone(), p.projects.clone()),
+                |p: &mut Picker, (project_id, _)| p.project_id = project_id,
+            )),
+        ),
+    );
+
+    let locales = Either::new(
+        |p: &Picker, _: &_| p.locales.is_empty(),
+        Flex::column(),
+        labeled(
+            strings::get().picker_locale,
+            build_choices().fix_width(400.).lens(druid::lens::Map::new(
+                |p: &Picker| (p.locale.clone(), p.locales.clone()),
+                |p: &mut Picker, (locale, _)| p.locale = locale,
+            )),
+        ),
+    );
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(projects)
+        .with_default_spacer()
+        .with_child(locales)
+        .with_default_spacer()
+        .with_child(
+            Label::new(|p: &Picker, _: &_| p.status.clone())
+                .with_line_break_mode(LineBreaking::WordWrap),
+        )
+        .controller(PickerController)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_until_valid() {
+        let choices = vec![
+            Choice {
+                value: "1".into(),
+                label: "Web (1)".into(),
+            },
+            Choice {
+                value: "2".into(),
+                label: "App (2)".into(),
+            },
+        ];
+        let mut output = Vec::new();
+        let chosen = prompt(&b"x\n3\n 2 \n"[..], &mut output, "Project", &choices).unwrap();
+        assert_eq!("2", chosen.value);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("  1) Web (1)\n  2) App (2)\nProject [1-2]: "));
+        assert_eq!(2, output.matches("Enter a number between 1 and 2.").count());
+
+        assert!(prompt(&b""[..], Vec::new(), "Project", &choices).is_err());
+        assert!(prompt(&b"1\n"[..], Vec::new(), "Project", &[]).is_err());
+    }
+}
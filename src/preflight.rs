@@ -0,0 +1,381 @@
+//! Checks that run before any differences are computed.
+//!
+//! Every check that does not pass comes with a hint how to fix the problem.
+
+use std::fmt;
+
+use crate::client::Client;
+use crate::compat;
+use crate::config::AppConfig;
+use crate::exit_code::ExitCode;
+use anyhow::Result;
+
+#[derive(Debug)]
+pub enum Outcome {
+    Passed,
+    Skipped(&'static str),
+    Warning {
+        error: anyhow::Error,
+        hint: &'static str,
+    },
+    Failed {
+        error: anyhow::Error,
+        hint: &'static str,
+    },
+}
+
+impl Outcome {
+    fn failed(error: anyhow::Error, hint: &'static str) -> Self {
+        Self::Failed { error, hint }
+    }
+
+    fn warning(error: anyhow::Error, hint: &'static str) -> Self {
+        Self::Warning { error, hint }
+    }
+
+    /// Returns `true` if the outcome is [`Passed`] or [`Warning`].
+    ///
+    /// [`Passed`]: Outcome::Passed
+    /// [`Warning`]: Outcome::Warning
+    fn is_usable(&self) -> bool {
+        matches!(self, Self::Passed | Self::Warning { .. })
+    }
+
+    /// Returns `true` if the outcome is [`Failed`].
+    ///
+    /// [`Failed`]: Outcome::Failed
+    pub fn is_failed(&self) -> bool {
+        matches!(self, Self::Failed { .. })
+    }
+}
+
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub outcome: Outcome,
+    /// Whether the check talks to Traduora.
+    pub remote: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Report(pub Vec<Check>);
+
+impl Report {
+    fn push(&mut self, name: &'static str, outcome: Outcome) -> bool {
+        let usable = outcome.is_usable();
+        self.0.push(Check {
+            name,
+            outcome,
+            remote: false,
+        });
+        usable
+    }
+
+    fn push_remote(&mut self, name: &'static str, outcome: Outcome) -> bool {
+        let usable = self.push(name, outcome);
+        if let Some(check) = self.0.last_mut() {
+            check.remote = true;
+        }
+        usable
+    }
+
+    pub fn has_failures(&self) -> bool {
+        self.0.iter().any(|c| c.outcome.is_failed())
+    }
+
+    /// Exit code for the first failed check, if any.
+    pub fn exit_code(&self) -> Option<ExitCode> {
+        self.0.iter().find(|c| c.outcome.is_failed()).map(|c| {
+            if c.remote {
+                ExitCode::Network
+            } else {
+                ExitCode::Config
+            }
+        })
+    }
+
+    /// Turns the report into an error if any check failed.
+    pub fn into_result(self) -> Result<()> {
+        log::info!("Pre-flight checks:\n{}", self);
+        if self.has_failures() {
+            Err(anyhow::anyhow!("Pre-flight checks failed:\n{}", self))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for check in &self.0 {
+            match &check.outcome {
+                Outcome::Passed => writeln!(f, "[ OK ] {}", check.name)?,
+                Outcome::Skipped(reason) => writeln!(f, "[SKIP] {} ({})", check.name, reason)?,
+                Outcome::Warning { error, hint } => {
+                    writeln!(f, "[WARN] {}: {:#}", check.name, error)?;
+                    writeln!(f, "       Hint: {}", hint)?;
+                }
+                Outcome::Failed { error, hint } => {
+                    writeln!(f, "[FAIL] {}: {:#}", check.name, error)?;
+                    writeln!(f, "       Hint: {}", hint)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_config(config: &AppConfig) -> Outcome {
+    if config.host().is_empty() {
+        return Outcome::failed(
+            anyhow::anyhow!("Host is empty."),
+            "Set `host` to the address of your Traduora instance, e.g. `localhost:8080`.",
+        );
+    }
+    if config.host().contains("://") {
+        return Outcome::failed(
+            anyhow::anyhow!("Host {:?} contains a protocol.", config.host()),
+            "Remove the protocol from `host` and use `with_ssl` to choose between HTTP and HTTPS.",
+        );
+    }
+    if config.translation_file().as_os_str().is_empty() {
+        return Outcome::failed(
+            anyhow::anyhow!("Translation file is empty."),
+            "Set `translation_file` to the path of your translation file.",
+        );
+    }
+    if !config.with_ssl() {
+        return Outcome::warning(
+            anyhow::anyhow!("The connection to Traduora is not encrypted."),
+            "Set `with_ssl` to true if your Traduora instance supports HTTPS.",
+        );
+    }
+    Outcome::Passed
+}
+
+fn check_translation_file(config: &AppConfig) -> Outcome {
+    match crate::loader::load_from_files(config, config.translation_files()) {
+        Ok(_) => Outcome::Passed,
+        Err(e) => Outcome::failed(
+            e,
+            "Check that `translation_file` points to a readable flat JSON file. \
+            Relative paths are resolved from the working directory.",
+        ),
+    }
+}
+
+fn check_revision(config: &AppConfig) -> Outcome {
+    if config.revision().is_empty() {
+        return Outcome::Skipped("no revision configured");
+    }
+    if crate::loader::is_stdin(config.translation_file()) {
+        return Outcome::Skipped("translation file is read from stdin");
+    }
+    if config.translation_files().iter().any(crate::loader::is_url) {
+        return Outcome::Skipped("translation file is downloaded");
+    }
+    match crate::loader::load_from_git_files(config, config.revision(), config.translation_files())
+    {
+        Ok(_) => Outcome::Passed,
+        Err(e) => Outcome::failed(
+            e,
+            "Check that `revision` exists in the repository containing the translation file \
+            and that the file was committed at that revision.",
+        ),
+    }
+}
+
+fn check_server(config: &AppConfig) -> Outcome {
+    let fun = || -> Result<compat::Flavor> {
+        let client = crate::config::create_unauthenticated_client(config)?;
+        compat::detect(&client)
+    };
+
+    server_outcome(fun(), crate::loader::has_cached_data(config))
+}
+
+/// The outcome of connecting to the server. It is only a warning if the server is unreachable
+/// and `cached` data of the last successful run can be used instead.
+fn server_outcome(connected: Result<compat::Flavor>, cached: bool) -> Outcome {
+    match connected {
+        Ok(_) => Outcome::Passed,
+        Err(e) if cached && crate::transport::is_unreachable(&e) => Outcome::warning(
+            e,
+            "Cached data from the last successful run is used. Updating terms is disabled.",
+        ),
+        Err(e) => Outcome::failed(
+            e,
+            "Check `host`, `with_ssl` and `validate_certs` and make sure the server is running.",
+        ),
+    }
+}
+
+fn check_credentials(config: &AppConfig) -> (Outcome, Option<Client>) {
+    match crate::config::create_client(config) {
+        Ok(client) => (Outcome::Passed, Some(client)),
+        Err(e) => (
+            Outcome::failed(
+                e,
+                "Check `mail` and `password` or `client_id` and `client_secret`.",
+            ),
+            None,
+        ),
+    }
+}
+
+fn check_locale(config: &AppConfig, client: &Client) -> Outcome {
+    let locales = match client.locales() {
+        Ok(locales) => locales,
+        Err(e) => return Outcome::failed(
+            e.context(format!(
+                "Failed to load locales of project {:?}.",
+                config.project_id()
+            )),
+            "Check that `project_id` is correct and that the account has access to the project.",
+        ),
+    };
+
+    if locales.iter().any(|l| &l.locale.code == config.locale()) {
+        Outcome::Passed
+    } else {
+        Outcome::failed(
+            anyhow::anyhow!(
+                "Locale {:?} does not exist in project {:?}. Available locales: {}",
+                config.locale(),
+                config.project_id(),
+                locales
+                    .iter()
+                    .map(|l| l.locale.code.value())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            "Fix `locale` in the configuration or add the locale to the project in Traduora.",
+        )
+    }
+}
+
+/// Runs all checks. Checks that depend on a previous check are skipped if it did not pass.
+pub fn run(config: &AppConfig) -> Report {
+    run_checks(config, true)
+}
+
+/// Like [`run`], but the checks that talk to Traduora are skipped unless `remote` is set.
+pub fn run_checks(config: &AppConfig, remote: bool) -> Report {
+    let mut report = Report::default();
+
+    report.push("Configuration is sane", check_config(config));
+    report.push(
+        "Translation file is readable",
+        check_translation_file(config),
+    );
+    report.push("Git revision is resolvable", check_revision(config));
+
+    if !remote {
+        for name in [
+            "Traduora server is reachable",
+            "Credentials are valid",
+            "Locale exists",
+        ] {
+            report.push_remote(name, Outcome::Skipped("not requested"));
+        }
+        return report;
+    }
+
+    push_remote_checks(&mut report, config, check_server(config));
+    report
+}
+
+/// Pushes the outcome of connecting to the server and the checks that need to log in.
+fn push_remote_checks(report: &mut Report, config: &AppConfig, server: Outcome) {
+    // A warning means the server is unreachable but cached data is used instead, so there is
+    // nothing to log in to either.
+    let unreachable = matches!(server, Outcome::Warning { .. });
+    if !report.push_remote("Traduora server is reachable", server) || unreachable {
+        report.push_remote(
+            "Credentials are valid",
+            Outcome::Skipped("server is unreachable"),
+        );
+        report.push_remote("Locale exists", Outcome::Skipped("server is unreachable"));
+        return;
+    }
+
+    let (outcome, client) = check_credentials(config);
+    report.push_remote("Credentials are valid", outcome);
+
+    match client {
+        Some(client) => report.push_remote("Locale exists", check_locale(config, &client)),
+        None => report.push_remote("Locale exists", Outcome::Skipped("login failed")),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_failures() {
+        let mut report = Report::default();
+        report.push(
+            "Configuration is sane",
+            check_config(&crate::config::test()),
+        );
+        report.push(
+            "Git revision is resolvable",
+            Outcome::Skipped("no revision"),
+        );
+        assert!(!report.has_failures());
+        assert!(report
+            .to_string()
+            .starts_with("[WARN] Configuration is sane"));
+
+        report.push(
+            "Translation file is readable",
+            Outcome::failed(anyhow::anyhow!("Not found."), "Fix it."),
+        );
+        assert!(report.has_failures());
+        assert_eq!(Some(ExitCode::Config), report.exit_code());
+        assert!(report.into_result().is_err());
+    }
+
+    #[test]
+    fn skip_login_if_server_is_unreachable_with_cache() {
+        let unreachable = || {
+            Err(reqwest::blocking::get("http://127.0.0.1:1")
+                .unwrap_err()
+                .into())
+        };
+        let mut report = Report::default();
+        push_remote_checks(
+            &mut report,
+            &crate::config::test(),
+            server_outcome(unreachable(), true),
+        );
+        assert!(!report.has_failures());
+        assert!(matches!(report.0[0].outcome, Outcome::Warning { .. }));
+        for check in &report.0[1..] {
+            assert!(matches!(
+                check.outcome,
+                Outcome::Skipped("server is unreachable")
+            ));
+        }
+        assert_eq!(3, report.0.len());
+
+        let mut report = Report::default();
+        push_remote_checks(
+            &mut report,
+            &crate::config::test(),
+            server_outcome(unreachable(), false),
+        );
+        assert_eq!(Some(ExitCode::Network), report.exit_code());
+        assert_eq!(3, report.0.len());
+
+        let mut report = Report::default();
+        let rejected = Err(anyhow::anyhow!("Invalid certificate."));
+        push_remote_checks(
+            &mut report,
+            &crate::config::test(),
+            server_outcome(rejected, true),
+        );
+        assert!(report.0[0].outcome.is_failed());
+    }
+}
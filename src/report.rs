@@ -0,0 +1,252 @@
+//! Human-readable report of the pending changes, e.g. to attach to a merge request.
+//!
+//! The format is chosen by the file extension: `.html` or `.htm` writes HTML,
+//! everything else writes Markdown.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use traduora::api::TermId;
+
+use crate::config::AppConfig;
+use crate::loader::{LoadedData, Modification, TermMetadata, Translation};
+
+/// One table of the report.
+struct Section {
+    title: &'static str,
+    columns: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+fn sections(data: &LoadedData) -> [Section; 3] {
+    let modified = |id: &TermId| {
+        data.metadata
+            .get(id)
+            .and_then(|m: &TermMetadata| m.modified.as_deref())
+            .map(TermMetadata::format_date)
+            .unwrap_or_default()
+    };
+    let select = |f: &dyn Fn(&Translation) -> Option<Vec<String>>| -> Vec<Vec<String>> {
+        data.translations.iter().filter_map(f).collect()
+    };
+    [
+        Section {
+            title: "Created",
+            columns: &["Term", "Translation"],
+            rows: select(&|t| {
+                matches!(t.modification, Modification::Added)
+                    .then(|| vec![t.term.clone(), t.translation.clone()])
+            }),
+        },
+        Section {
+            title: "Updated",
+            columns: &["Term", "Before", "After", "Last modified"],
+            rows: select(&|t| match &t.modification {
+                Modification::Updated(id, previous) => Some(vec![
+                    t.term.clone(),
+                    previous.clone(),
+                    t.translation.clone(),
+                    modified(id),
+                ]),
+                _ => None,
+            }),
+        },
+        Section {
+            title: "Deleted",
+            columns: &["Term", "Translation", "Last modified"],
+            rows: select(&|t| match &t.modification {
+                Modification::Removed(id) => {
+                    Some(vec![t.term.clone(), t.translation.clone(), modified(id)])
+                }
+                _ => None,
+            }),
+        },
+    ]
+}
+
+/// Formats seconds since the unix epoch as `YYYY-MM-DD HH:MM UTC`.
+fn format_timestamp(secs: u64) -> String {
+    let (days, secs) = ((secs / 86400) as i64, secs % 86400);
+    // Conversion of days to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60
+    )
+}
+
+fn heading(config: &AppConfig, data: &LoadedData, now: u64) -> String {
+    let mut heading = format!(
+        "Generated {} for locale {} of project {}",
+        format_timestamp(now),
+        config.locale().value(),
+        config.project_id().value()
+    );
+    if data.offline {
+        heading.push_str(", based on cached data because Traduora was unreachable");
+    }
+    heading + "."
+}
+
+fn markdown_cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+fn to_markdown(config: &AppConfig, data: &LoadedData, now: u64) -> String {
+    let sections = sections(data);
+    let mut md = String::from("# Translation changes\n\n");
+    md.push_str(&format!("{}\n\n", heading(config, data, now)));
+    md.push_str("| Created | Updated | Deleted |\n| ---: | ---: | ---: |\n");
+    md.push_str(&format!(
+        "| {} | {} | {} |\n",
+        sections[0].rows.len(),
+        sections[1].rows.len(),
+        sections[2].rows.len()
+    ));
+    for section in sections.iter().filter(|s| !s.rows.is_empty()) {
+        md.push_str(&format!(
+            "\n## {} ({})\n\n",
+            section.title,
+            section.rows.len()
+        ));
+        md.push_str(&format!("| {} |\n", section.columns.join(" | ")));
+        md.push_str(&format!("|{}\n", " --- |".repeat(section.columns.len())));
+        for row in &section.rows {
+            let cells: Vec<_> = row.iter().map(|c| markdown_cell(c)).collect();
+            md.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+    }
+    md
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn to_html(config: &AppConfig, data: &LoadedData, now: u64) -> String {
+    let sections = sections(data);
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Translation changes</title>\n</head>\n<body>\n<h1>Translation changes</h1>\n",
+    );
+    html.push_str(&format!(
+        "<p>{}</p>\n",
+        html_escape(&heading(config, data, now))
+    ));
+    html.push_str("<table>\n<tr><th>Created</th><th>Updated</th><th>Deleted</th></tr>\n");
+    html.push_str(&format!(
+        "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n</table>\n",
+        sections[0].rows.len(),
+        sections[1].rows.len(),
+        sections[2].rows.len()
+    ));
+    for section in sections.iter().filter(|s| !s.rows.is_empty()) {
+        html.push_str(&format!(
+            "<h2>{} ({})</h2>\n<table>\n",
+            section.title,
+            section.rows.len()
+        ));
+        html.push_str(&format!(
+            "<tr><th>{}</th></tr>\n",
+            section.columns.join("</th><th>")
+        ));
+        for row in &section.rows {
+            let cells: Vec<_> = row.iter().map(|c| html_escape(c)).collect();
+            html.push_str(&format!("<tr><td>{}</td></tr>\n", cells.join("</td><td>")));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Path of the report for `locale` if several locales are processed, e.g. `report.de.md`
+/// for `report.md`.
+pub fn path_for_locale(path: &Path, locale: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, locale, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, locale),
+    };
+    path.with_file_name(name)
+}
+
+/// Writes a report of the changes in `data` to `path`.
+pub fn write(config: &AppConfig, path: impl AsRef<Path>, data: &LoadedData) -> Result<()> {
+    let path = path.as_ref();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let is_html = path.extension().map_or(false, |ext| {
+        ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm")
+    });
+    let report = if is_html {
+        to_html(config, data, now)
+    } else {
+        to_markdown(config, data, now)
+    };
+    crate::atomic::write(path, report)?;
+    log::info!("Wrote report to {}.", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_timestamps() {
+        assert_eq!("1970-01-01 00:00 UTC", format_timestamp(0));
+        assert_eq!("2022-03-01 12:34 UTC", format_timestamp(1_646_138_040));
+        assert_eq!("2024-02-29 23:59 UTC", format_timestamp(1_709_251_140));
+    }
+
+    #[test]
+    fn report_path_per_locale() {
+        assert_eq!(
+            Path::new("out/report.de.md"),
+            path_for_locale(Path::new("out/report.md"), "de")
+        );
+        assert_eq!(
+            Path::new("report.ru"),
+            path_for_locale(Path::new("report"), "ru")
+        );
+    }
+
+    #[test]
+    fn markdown_tables() {
+        let data = LoadedData {
+            translations: vec![
+                Translation::added("a|b".into(), "line 1\nline 2".into()),
+                Translation::removed("gone".into(), "Weg".into(), "1".into()),
+            ],
+            ..Default::default()
+        };
+        let md = to_markdown(&crate::config::test(), &data, 0);
+        assert!(md.contains("| 1 | 0 | 1 |\n"));
+        assert!(md.contains("## Created (1)\n\n| Term | Translation |\n| --- | --- |\n| a\\|b | line 1<br>line 2 |\n"));
+        assert!(md.contains("## Deleted (1)"));
+        assert!(!md.contains("## Updated"));
+    }
+}
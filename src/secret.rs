@@ -0,0 +1,130 @@
+//! Passwords and client secrets that are stored encrypted in the config file, so it can be kept
+//! in version control.
+//!
+//! The secret is encrypted with AES-256-GCM under a key derived from a passphrase with
+//! PBKDF2-HMAC-SHA256. The passphrase is taken from `TRADUORA_UPDATE_PASSPHRASE` or asked for once
+//! per run before anything is loaded: on the terminal by the command line modes and on the startup
+//! screen by the window. It is then kept with [`unlock`].
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+
+use crate::config::AppConfig;
+
+/// Marks a value of `password` or `client_secret` as encrypted.
+pub const PREFIX: &str = "encrypted:";
+
+/// Environment variable with the passphrase, e.g. for CI.
+const PASSPHRASE_ENV: &str = "TRADUORA_UPDATE_PASSPHRASE";
+
+const SALT_LEN: usize = 16;
+const IV_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const ITERATIONS: u32 = 100_000;
+
+/// Passphrase entered by the user.
+static PASSPHRASE: OnceCell<String> = OnceCell::new();
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(PREFIX)
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Aes256Gcm {
+    let mut key = [0; 32];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(passphrase.as_bytes(), salt, ITERATIONS, &mut key);
+    Aes256Gcm::new(&key.into())
+}
+
+/// Encrypts the secret to a value for the config file, i.e. the prefix followed by the salt,
+/// nonce, tag and cipher text in Base64.
+pub fn encrypt(secret: &str, passphrase: &str) -> Result<String> {
+    let mut salt = [0; SALT_LEN];
+    OsRng
+        .try_fill_bytes(&mut salt)
+        .map_err(|e| anyhow::anyhow!("Failed to generate a salt: {}", e))?;
+    let iv = Aes256Gcm::generate_nonce(&mut OsRng);
+    let sealed = cipher(passphrase, &salt)
+        .encrypt(&iv, secret.as_bytes())
+        .ok()
+        .context("Failed to encrypt the secret.")?;
+    // The tag is appended to the cipher text, but stored in front of it.
+    let (encrypted, tag) = sealed.split_at(sealed.len() - TAG_LEN);
+    let data = [&salt[..], &iv, tag, encrypted].concat();
+    Ok(format!("{}{}", PREFIX, base64::encode(data)))
+}
+
+/// Decrypts a value written by [`encrypt`].
+pub fn decrypt(value: &str, passphrase: &str) -> Result<String> {
+    let data = value
+        .strip_prefix(PREFIX)
+        .and_then(|data| base64::decode(data).ok())
+        .filter(|data| data.len() >= SALT_LEN + IV_LEN + TAG_LEN)
+        .context("Encrypted secret is damaged, encrypt it again with encrypt-secret.")?;
+    let (salt, data) = data.split_at(SALT_LEN);
+    let (iv, data) = data.split_at(IV_LEN);
+    let (tag, encrypted) = data.split_at(TAG_LEN);
+    let sealed = [encrypted, tag].concat();
+    let secret = cipher(passphrase, salt)
+        .decrypt(Nonce::from_slice(iv), sealed.as_slice())
+        .ok()
+        .context("Failed to decrypt the secret, the passphrase is wrong.")?;
+    String::from_utf8(secret).context("Decrypted secret is not UTF-8.")
+}
+
+/// The secret as it is or decrypted with the passphrase if it is encrypted.
+pub fn reveal(value: &str) -> Result<String> {
+    if is_encrypted(value) {
+        decrypt(value, passphrase()?)
+    } else {
+        Ok(value.to_owned())
+    }
+}
+
+fn passphrase() -> Result<&'static str> {
+    PASSPHRASE
+        .get_or_try_init(|| std::env::var(PASSPHRASE_ENV))
+        .map(String::as_str)
+        .ok()
+        .context("No passphrase was entered for the encrypted secrets.")
+}
+
+/// Whether the login of the configuration is encrypted and the passphrase must be asked for.
+pub fn needs_passphrase(config: &AppConfig) -> bool {
+    config.login().secret().map_or(false, is_encrypted) && passphrase().is_err()
+}
+
+/// Keeps the passphrase for the rest of the run after checking that it decrypts the login of
+/// the configuration.
+pub fn unlock(config: &AppConfig, passphrase: &str) -> Result<()> {
+    if let Some(secret) = config.login().secret().filter(|s| is_encrypted(s)) {
+        decrypt(secret, passphrase)?;
+    }
+    // Only the first passphrase is kept, which decrypted the same secrets.
+    let _ = PASSPHRASE.set(passphrase.to_owned());
+    Ok(())
+}
+
+/// Reads a line from the terminal without echoing it, even if stdin is redirected.
+pub fn ask_on_terminal(prompt: &str) -> Result<String> {
+    rpassword::prompt_password(prompt).context("Failed to read from the terminal.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let encrypted = encrypt("12345678", "correct horse").unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.contains("12345678"));
+        assert_ne!(encrypted, encrypt("12345678", "correct horse").unwrap());
+        assert_eq!("12345678", decrypt(&encrypted, "correct horse").unwrap());
+        assert!(decrypt(&encrypted, "wrong").is_err());
+        assert!(decrypt("encrypted:abc", "correct horse").is_err());
+        assert_eq!("plain", reveal("plain").unwrap());
+    }
+}
@@ -0,0 +1,596 @@
+//! Texts of the user interface in all supported languages.
+//!
+//! The language is taken from the configuration or, if it is not configured,
+//! from the locale environment variables of the system. Texts with values
+//! are functions so every language can place the values where it needs them.
+
+use std::path::Path;
+
+use once_cell::sync::OnceCell;
+use traduora::api::TermId;
+
+use crate::config::Language;
+use crate::icu::Problem;
+use crate::loader::{TermMetadata, Totals};
+use crate::updater::{Operation, Progress, Step};
+use crate::warnings::Warning;
+
+/// Number of selected entries and of all entries of a tab.
+type Selected = (usize, usize);
+
+pub struct Strings {
+    pub select_all: &'static str,
+    pub deselect_all: &'static str,
+    pub select_matching: fn(usize) -> String,
+    pub deselect_matching: fn(usize) -> String,
+    pub term: &'static str,
+    pub translation: &'static str,
+    pub changes_to_traduora: &'static str,
+    pub offline_banner: &'static str,
+    pub uncommitted_banner: fn(&str) -> String,
+    pub update_terms: &'static str,
+    pub reloading: &'static str,
+    pub reload: &'static str,
+    pub export: &'static str,
+    pub filter_placeholder: &'static str,
+    pub group_by_namespace: &'static str,
+    pub show_term_ids: &'static str,
+    pub no_namespace: &'static str,
+    pub removed: &'static str,
+    pub added: &'static str,
+    pub updated: &'static str,
+    pub unchanged: &'static str,
+    pub update_disabled_offline: &'static str,
+    pub update_disabled_read_only: &'static str,
+    pub status_bar: fn(&str, &str, &str, &str, &str) -> String,
+    pub no_revision: &'static str,
+    pub pushing_to: fn(&str) -> String,
+    pub summary: fn(usize, usize, usize) -> String,
+    pub following_deleted: &'static str,
+    pub deletion_keyword: &'static str,
+    pub confirm_deletion: fn(usize, &str) -> String,
+    pub update: &'static str,
+    pub cancel: &'static str,
+    pub uploading: &'static str,
+    pub cancelling: &'static str,
+    pub progress: fn(&Progress) -> String,
+    pub login_rejected: &'static str,
+    pub client_id: &'static str,
+    pub mail: &'static str,
+    pub client_secret: &'static str,
+    pub password: &'static str,
+    pub log_in: &'static str,
+    pub skip: &'static str,
+    pub finished_uploading: &'static str,
+    pub no_error: &'static str,
+    pub cancelled: fn(usize, usize) -> String,
+    pub update_failed: fn(usize) -> String,
+    pub failed_terms: fn(Option<Operation>, usize) -> String,
+    pub ok: &'static str,
+    pub retry_failed: &'static str,
+    pub status_reloading: &'static str,
+    pub status_reloaded: &'static str,
+    pub reload_failed: fn(&anyhow::Error) -> String,
+    pub exported: fn(usize, &Path) -> String,
+    pub export_failed: fn(&anyhow::Error) -> String,
+    pub startup_failed: &'static str,
+    pub choose_config: &'static str,
+    pub chosen_config: fn(&Path) -> String,
+    pub retry: &'static str,
+    pub retrying: &'static str,
+    pub passphrase_required: &'static str,
+    pub passphrase: &'static str,
+    pub unlock: &'static str,
+    pub term_metadata: fn(&TermId, &TermMetadata) -> String,
+    pub statistics: fn(Selected, Selected, Selected, &Totals) -> String,
+    pub only_warnings: &'static str,
+    pub warning: fn(&Warning) -> String,
+    pub open_in_traduora: &'static str,
+    pub opened_in_traduora: fn(&str) -> String,
+    pub open_in_traduora_failed: fn(&anyhow::Error) -> String,
+    pub file_menu: &'static str,
+    pub open_project: &'static str,
+    pub show_log: &'static str,
+    pub log_title: &'static str,
+    pub log_empty: &'static str,
+    pub locale_menu: &'static str,
+    pub switching_locale: fn(&str) -> String,
+    pub locale_busy: &'static str,
+    pub project_menu: &'static str,
+    pub switching_project: fn(&str) -> String,
+    pub project_busy: &'static str,
+    pub opened_project: fn(&Path) -> String,
+    pub open_project_failed: fn(&anyhow::Error) -> String,
+    pub wizard_intro: &'static str,
+    pub wizard_host: &'static str,
+    pub wizard_with_ssl: &'static str,
+    pub wizard_client_credentials: &'static str,
+    pub wizard_connect: &'static str,
+    pub wizard_translation_file: &'static str,
+    pub wizard_browse: &'static str,
+    pub wizard_secret_hint: &'static str,
+    pub wizard_save: &'static str,
+    pub wizard_starting: &'static str,
+    pub choose_on_traduora: &'static str,
+    pub picker_title: &'static str,
+    pub picker_open: &'static str,
+    pub picker_loading_projects: &'static str,
+    pub picker_loading_locales: &'static str,
+    pub picker_project: &'static str,
+    pub picker_locale: &'static str,
+    pub picker_failed: fn(&anyhow::Error) -> String,
+}
+
+static EN: Strings = Strings {
+    select_all: "Select all",
+    deselect_all: "Deselect all",
+    select_matching: |count| format!("Select all {} matching", count),
+    deselect_matching: |count| format!("Deselect all {} matching", count),
+    term: "Term",
+    translation: "Translation",
+    changes_to_traduora: "Changes to Traduora:",
+    offline_banner:
+        "Offline: Traduora is unreachable. Showing differences to the last cached server state.",
+    uncommitted_banner: |files| {
+        format!(
+            "Uncommitted changes in {}. Make sure the edits are finished before updating.",
+            files
+        )
+    },
+    update_terms: "Update terms",
+    reloading: "Reloading…",
+    reload: "Reload (F5)",
+    export: "Export…",
+    filter_placeholder: "Filter terms and translations",
+    group_by_namespace: "Group by namespace",
+    show_term_ids: "Show term ids",
+    no_namespace: "(no namespace)",
+    removed: "Removed",
+    added: "Added",
+    updated: "Updated",
+    unchanged: "Unchanged",
+    update_disabled_offline: "Updating terms is disabled while offline.",
+    update_disabled_read_only: "Read-only mode: updating terms is disabled.",
+    status_bar: |url, project, locale, file, revision| {
+        format!(
+            "{}  |  Project: {}  |  Locale: {}  |  File: {}  |  Revision: {}",
+            url, project, locale, file, revision
+        )
+    },
+    no_revision: "none",
+    pushing_to: |destination| format!("Pushing to {}", destination),
+    summary: |created, deleted, updated| {
+        format!(
+            "{} terms will be created, {} deleted and {} updated.",
+            created, deleted, updated
+        )
+    },
+    following_deleted: "The following terms will be deleted:",
+    deletion_keyword: "DELETE",
+    confirm_deletion: |count, keyword| {
+        format!(
+            "Deleting {} terms cannot be undone. Type {} to confirm:",
+            count, keyword
+        )
+    },
+    update: "Update",
+    cancel: "Cancel",
+    uploading: "Uploading terms.",
+    cancelling: "Cancelling after the current term.",
+    progress: |progress| {
+        let (verb, past_tense) = match progress.operation {
+            Operation::Create => ("create", "Created"),
+            Operation::Update => ("update", "Updated"),
+            Operation::Delete => ("delete", "Deleted"),
+        };
+        match progress.step {
+            Step::Started => format!("Trying to {} term {:?}.", verb, progress.term),
+            Step::Succeeded => format!("{} term {:?}.", past_tense, progress.term),
+            Step::Failed => format!("Failed to {} term {:?}.", verb, progress.term),
+        }
+    },
+    login_rejected: "Traduora rejected the login. Please log in again to continue the update.",
+    client_id: "Client id",
+    mail: "Mail",
+    client_secret: "Client secret",
+    password: "Password",
+    log_in: "Log in",
+    skip: "Skip",
+    finished_uploading: "Finished uploading terms.",
+    no_error: "No error occurred.",
+    cancelled: |applied, remaining| {
+        format!(
+            "Update cancelled. {} terms were applied, {} were not.",
+            applied, remaining
+        )
+    },
+    update_failed: |count| format!("Failed to create, update or delete {} terms.", count),
+    failed_terms: |operation, count| match operation {
+        Some(Operation::Create) => format!("Failed to create {} terms", count),
+        Some(Operation::Update) => format!("Failed to update {} terms", count),
+        Some(Operation::Delete) => format!("Failed to delete {} terms", count),
+        None => format!("Other errors ({})", count),
+    },
+    ok: "Ok",
+    retry_failed: "Retry failed",
+    status_reloading: "Reloading data.",
+    status_reloaded: "Reloaded data.",
+    reload_failed: |e| format!("Failed to reload data: {:#}", e),
+    exported: |count, path| format!("Exported {} entries to {}.", count, path.display()),
+    export_failed: |e| format!("Export failed: {:#}", e),
+    startup_failed: "Failed to start. Please fix the error and retry.",
+    choose_config: "Choose config file…",
+    chosen_config: |path| format!("Using config file {}", path.display()),
+    retry: "Retry",
+    retrying: "Retrying…",
+    passphrase_required: "The config file contains encrypted secrets. Enter the passphrase to decrypt them.",
+    passphrase: "Passphrase",
+    unlock: "Unlock",
+    term_metadata: |id, metadata| {
+        let date = |date: &Option<String>| {
+            date.as_deref()
+                .map_or_else(|| "unknown".to_string(), TermMetadata::format_date)
+        };
+        let labels = if metadata.labels.is_empty() {
+            "none".to_string()
+        } else {
+            metadata.labels.join(", ")
+        };
+        format!(
+            "Term id: {}\nCreated: {}\nModified: {}\nLabels: {}",
+            id,
+            date(&metadata.created),
+            date(&metadata.modified),
+            labels
+        )
+    },
+    statistics: |removed, added, updated, totals| {
+        let completion = totals
+            .completion()
+            .map_or_else(|| "n/a".to_string(), |c| format!("{:.0} %", c));
+        format!(
+            "Removed: {} of {} selected  |  Added: {} of {} selected  |  Updated: {} of {} selected  |  \
+             Terms in file: {}  |  Translated on Traduora: {} of {} ({})",
+            removed.0,
+            removed.1,
+            added.0,
+            added.1,
+            updated.0,
+            updated.1,
+            totals.local_terms,
+            totals.remote_translated,
+            totals.remote_terms,
+            completion
+        )
+    },
+    only_warnings: "Show only warnings",
+    warning: |warning| match warning {
+        Warning::EmptyTranslation => "The translation is empty.".to_string(),
+        Warning::MuchShorter { previous, current } => format!(
+            "The translation is much shorter than on Traduora ({} instead of {} characters).",
+            current, previous
+        ),
+        Warning::PlaceholdersDiffer { previous, current } => format!(
+            "The placeholders differ from Traduora: [{}] instead of [{}].",
+            current.join(", "),
+            previous.join(", ")
+        ),
+        Warning::InvalidMessage(error) => format!("The translation is no valid ICU message: {}", error),
+    },
+    open_in_traduora: "Open in Traduora",
+    opened_in_traduora: |term| {
+        format!(
+            "Opened Traduora. The term {:?} was copied to the clipboard to search for it.",
+            term
+        )
+    },
+    open_in_traduora_failed: |e| format!("Failed to open Traduora: {:#}", e),
+    file_menu: "File",
+    open_project: "Open project…",
+    show_log: "Show log",
+    log_title: "Log",
+    log_empty: "No messages were logged. Start with -v to log more details.",
+    locale_menu: "Locale",
+    switching_locale: |locale| format!("Loading locale {}.", locale),
+    locale_busy: "The locale cannot be switched while data is loaded or terms are updated.",
+    project_menu: "Project",
+    switching_project: |project| format!("Loading project {}.", project),
+    project_busy: "The project cannot be switched while data is loaded or terms are updated.",
+    opened_project: |path| format!("Opened project {} in a new window.", path.display()),
+    open_project_failed: |e| format!("Failed to open project: {:#}", e),
+    wizard_intro: "No config file was found. Log in to Traduora and choose the project to create traduora-update.json in the working directory.",
+    wizard_host: "Host",
+    wizard_with_ssl: "HTTPS",
+    wizard_client_credentials: "Log in with client credentials",
+    wizard_connect: "Connect",
+    wizard_translation_file: "Translation file",
+    wizard_browse: "Browse…",
+    wizard_secret_hint: "The secret is saved in plain text. Replace it with ${VARIABLE} or \"keyring\" in the config file to keep it out of the file.",
+    wizard_save: "Save and start",
+    wizard_starting: "Starting…",
+    choose_on_traduora: "Choose on Traduora…",
+    picker_title: "Choose project and locale",
+    picker_open: "Open",
+    picker_loading_projects: "Loading projects…",
+    picker_loading_locales: "Loading locales…",
+    picker_project: "Project",
+    picker_locale: "Locale",
+    picker_failed: |e| format!("Failed: {:#}", e),
+};
+
+static DE: Strings = Strings {
+    select_all: "Alle auswählen",
+    deselect_all: "Alle abwählen",
+    select_matching: |count| format!("Alle {} Treffer auswählen", count),
+    deselect_matching: |count| format!("Alle {} Treffer abwählen", count),
+    term: "Begriff",
+    translation: "Übersetzung",
+    changes_to_traduora: "Änderungen gegenüber Traduora:",
+    offline_banner:
+        "Offline: Traduora ist nicht erreichbar. Die Unterschiede beziehen sich auf den zuletzt zwischengespeicherten Stand.",
+    uncommitted_banner: |files| {
+        format!(
+            "Nicht committete Änderungen in {}. Die Bearbeitung sollte vor dem Aktualisieren abgeschlossen sein.",
+            files
+        )
+    },
+    update_terms: "Begriffe aktualisieren",
+    reloading: "Lädt neu…",
+    reload: "Neu laden (F5)",
+    export: "Exportieren…",
+    filter_placeholder: "Begriffe und Übersetzungen filtern",
+    group_by_namespace: "Nach Namensraum gruppieren",
+    show_term_ids: "Begriffs-IDs anzeigen",
+    no_namespace: "(kein Namensraum)",
+    removed: "Entfernt",
+    added: "Hinzugefügt",
+    updated: "Geändert",
+    unchanged: "Unverändert",
+    update_disabled_offline: "Im Offline-Modus können keine Begriffe aktualisiert werden.",
+    update_disabled_read_only: "Im Nur-Lesen-Modus können keine Begriffe aktualisiert werden.",
+    status_bar: |url, project, locale, file, revision| {
+        format!(
+            "{}  |  Projekt: {}  |  Sprache: {}  |  Datei: {}  |  Revision: {}",
+            url, project, locale, file, revision
+        )
+    },
+    no_revision: "keine",
+    pushing_to: |destination| format!("Übertragen nach {}", destination),
+    summary: |created, deleted, updated| {
+        format!(
+            "{} Begriffe werden erstellt, {} gelöscht und {} geändert.",
+            created, deleted, updated
+        )
+    },
+    following_deleted: "Die folgenden Begriffe werden gelöscht:",
+    deletion_keyword: "LÖSCHEN",
+    confirm_deletion: |count, keyword| {
+        format!(
+            "Das Löschen von {} Begriffen kann nicht rückgängig gemacht werden. Zur Bestätigung {} eingeben:",
+            count, keyword
+        )
+    },
+    update: "Aktualisieren",
+    cancel: "Abbrechen",
+    uploading: "Begriffe werden hochgeladen.",
+    cancelling: "Abbruch nach dem aktuellen Begriff.",
+    progress: |progress| {
+        let participle = match progress.operation {
+            Operation::Create => "erstellt",
+            Operation::Update => "geändert",
+            Operation::Delete => "gelöscht",
+        };
+        match progress.step {
+            Step::Started => format!("Begriff {:?} wird {}.", progress.term, participle),
+            Step::Succeeded => format!("Begriff {:?} {}.", progress.term, participle),
+            Step::Failed => format!(
+                "Begriff {:?} konnte nicht {} werden.",
+                progress.term, participle
+            ),
+        }
+    },
+    login_rejected:
+        "Traduora hat die Anmeldung abgelehnt. Bitte melde dich erneut an, um die Aktualisierung fortzusetzen.",
+    client_id: "Client-ID",
+    mail: "E-Mail",
+    client_secret: "Client-Secret",
+    password: "Passwort",
+    log_in: "Anmelden",
+    skip: "Überspringen",
+    finished_uploading: "Hochladen der Begriffe abgeschlossen.",
+    no_error: "Es ist kein Fehler aufgetreten.",
+    cancelled: |applied, remaining| {
+        format!(
+            "Aktualisierung abgebrochen. {} Begriffe wurden übertragen, {} nicht.",
+            applied, remaining
+        )
+    },
+    update_failed: |count| {
+        format!(
+            "{} Begriffe konnten nicht erstellt, geändert oder gelöscht werden.",
+            count
+        )
+    },
+    failed_terms: |operation, count| match operation {
+        Some(Operation::Create) => format!("{} Begriffe konnten nicht erstellt werden", count),
+        Some(Operation::Update) => format!("{} Begriffe konnten nicht geändert werden", count),
+        Some(Operation::Delete) => format!("{} Begriffe konnten nicht gelöscht werden", count),
+        None => format!("Sonstige Fehler ({})", count),
+    },
+    ok: "Ok",
+    retry_failed: "Fehlgeschlagene wiederholen",
+    status_reloading: "Daten werden neu geladen.",
+    status_reloaded: "Daten neu geladen.",
+    reload_failed: |e| format!("Neuladen der Daten fehlgeschlagen: {:#}", e),
+    exported: |count, path| format!("{} Einträge nach {} exportiert.", count, path.display()),
+    export_failed: |e| format!("Export fehlgeschlagen: {:#}", e),
+    startup_failed: "Start fehlgeschlagen. Bitte behebe den Fehler und versuche es erneut.",
+    choose_config: "Konfigurationsdatei wählen…",
+    chosen_config: |path| format!("Verwende Konfigurationsdatei {}", path.display()),
+    retry: "Erneut versuchen",
+    retrying: "Versuche erneut…",
+    passphrase_required: "Die Konfigurationsdatei enthält verschlüsselte Geheimnisse. Gib die Passphrase ein, um sie zu entschlüsseln.",
+    passphrase: "Passphrase",
+    unlock: "Entsperren",
+    term_metadata: |id, metadata| {
+        let date = |date: &Option<String>| {
+            date.as_deref()
+                .map_or_else(|| "unbekannt".to_string(), TermMetadata::format_date)
+        };
+        let labels = if metadata.labels.is_empty() {
+            "keine".to_string()
+        } else {
+            metadata.labels.join(", ")
+        };
+        format!(
+            "Begriffs-ID: {}\nErstellt: {}\nGeändert: {}\nLabels: {}",
+            id,
+            date(&metadata.created),
+            date(&metadata.modified),
+            labels
+        )
+    },
+    statistics: |removed, added, updated, totals| {
+        let completion = totals
+            .completion()
+            .map_or_else(|| "k. A.".to_string(), |c| format!("{:.0} %", c));
+        format!(
+            "Entfernt: {} von {} ausgewählt  |  Hinzugefügt: {} von {} ausgewählt  |  \
+             Geändert: {} von {} ausgewählt  |  Begriffe in Datei: {}  |  \
+             Übersetzt in Traduora: {} von {} ({})",
+            removed.0,
+            removed.1,
+            added.0,
+            added.1,
+            updated.0,
+            updated.1,
+            totals.local_terms,
+            totals.remote_translated,
+            totals.remote_terms,
+            completion
+        )
+    },
+    only_warnings: "Nur Warnungen anzeigen",
+    warning: |warning| match warning {
+        Warning::EmptyTranslation => "Die Übersetzung ist leer.".to_string(),
+        Warning::MuchShorter { previous, current } => format!(
+            "Die Übersetzung ist viel kürzer als in Traduora ({} statt {} Zeichen).",
+            current, previous
+        ),
+        Warning::PlaceholdersDiffer { previous, current } => format!(
+            "Die Platzhalter weichen von Traduora ab: [{}] statt [{}].",
+            current.join(", "),
+            previous.join(", ")
+        ),
+        Warning::InvalidMessage(error) => {
+            let problem = match &error.problem {
+                Problem::UnclosedArgument => "Argument ist nicht mit } geschlossen".to_string(),
+                Problem::UnmatchedBrace => "} schließt kein Argument".to_string(),
+                Problem::InvalidArgumentName => "Argument hat keinen gültigen Namen".to_string(),
+                Problem::ExpectedCommaOrBrace => ", oder } erwartet".to_string(),
+                Problem::UnknownType(kind) => format!("Unbekannter Argumenttyp {:?}", kind),
+                Problem::InvalidSelector(selector) => format!("Ungültiger Selektor {:?}", selector),
+                Problem::DuplicateSelector(selector) => {
+                    format!("Selektor {:?} kommt mehrfach vor", selector)
+                }
+                Problem::MissingVariant(selector) => {
+                    format!("Auf Selektor {:?} folgt keine Nachricht in Klammern", selector)
+                }
+                Problem::InvalidOffset => "Auf offset: folgt keine Zahl".to_string(),
+                Problem::MissingOther => {
+                    "Plural- oder Select-Argument hat keine Variante other".to_string()
+                }
+            };
+            format!(
+                "Die Übersetzung ist keine gültige ICU-Nachricht: {} bei Zeichen {}.",
+                problem,
+                error.position + 1
+            )
+        }
+    },
+    open_in_traduora: "In Traduora öffnen",
+    opened_in_traduora: |term| {
+        format!(
+            "Traduora geöffnet. Der Begriff {:?} wurde zum Suchen in die Zwischenablage kopiert.",
+            term
+        )
+    },
+    open_in_traduora_failed: |e| format!("Öffnen von Traduora fehlgeschlagen: {:#}", e),
+    file_menu: "Datei",
+    open_project: "Projekt öffnen…",
+    show_log: "Protokoll anzeigen",
+    log_title: "Protokoll",
+    log_empty: "Es wurden keine Meldungen protokolliert. Mit -v werden mehr Details protokolliert.",
+    locale_menu: "Sprache",
+    switching_locale: |locale| format!("Sprache {} wird geladen.", locale),
+    locale_busy: "Die Sprache kann nicht gewechselt werden, während Daten geladen oder Begriffe aktualisiert werden.",
+    project_menu: "Projekt",
+    switching_project: |project| format!("Projekt {} wird geladen.", project),
+    project_busy: "Das Projekt kann nicht gewechselt werden, während Daten geladen oder Begriffe aktualisiert werden.",
+    opened_project: |path| format!("Projekt {} in neuem Fenster geöffnet.", path.display()),
+    open_project_failed: |e| format!("Öffnen des Projekts fehlgeschlagen: {:#}", e),
+    wizard_intro: "Es wurde keine Konfigurationsdatei gefunden. Melde dich bei Traduora an und wähle das Projekt, um traduora-update.json im Arbeitsverzeichnis anzulegen.",
+    wizard_host: "Host",
+    wizard_with_ssl: "HTTPS",
+    wizard_client_credentials: "Mit Client-Zugangsdaten anmelden",
+    wizard_connect: "Verbinden",
+    wizard_translation_file: "Übersetzungsdatei",
+    wizard_browse: "Durchsuchen…",
+    wizard_secret_hint: "Das Geheimnis wird im Klartext gespeichert. Ersetze es in der Konfigurationsdatei durch ${VARIABLE} oder \"keyring\", damit es nicht in der Datei steht.",
+    wizard_save: "Speichern und starten",
+    wizard_starting: "Starte…",
+    choose_on_traduora: "Auf Traduora auswählen…",
+    picker_title: "Projekt und Sprache auswählen",
+    picker_open: "Öffnen",
+    picker_loading_projects: "Lade Projekte…",
+    picker_loading_locales: "Lade Sprachen…",
+    picker_project: "Projekt",
+    picker_locale: "Sprache",
+    picker_failed: |e| format!("Fehlgeschlagen: {:#}", e),
+};
+
+/// Determines the language from the usual locale environment variables, e.g. `LANG=de_DE.UTF-8`.
+fn system_language() -> Language {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+        .unwrap_or_default();
+    parse_locale(&locale)
+}
+
+fn parse_locale(locale: &str) -> Language {
+    match locale.split(|c| c == '_' || c == '-' || c == '.').next() {
+        Some("de") => Language::De,
+        _ => Language::En,
+    }
+}
+
+static LANGUAGE: OnceCell<Language> = OnceCell::new();
+
+/// Sets the configured language. Has no effect once texts were requested, which use the
+/// system language without a configured one.
+pub fn init(language: Option<Language>) {
+    let _ = LANGUAGE.set(language.unwrap_or_else(system_language));
+}
+
+/// Texts in the configured language.
+pub fn get() -> &'static Strings {
+    match LANGUAGE.get_or_init(system_language) {
+        Language::En => &EN,
+        Language::De => &DE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_from_locale() {
+        assert_eq!(Language::De, parse_locale("de_DE.UTF-8"));
+        assert_eq!(Language::De, parse_locale("de"));
+        assert_eq!(Language::En, parse_locale("en_US.UTF-8"));
+        assert_eq!(Language::En, parse_locale("C"));
+        assert_eq!(Language::En, parse_locale(""));
+    }
+}
@@ -0,0 +1,332 @@
+//! Interactive mode in the terminal for machines where no window can be opened, e.g. over SSH.
+//!
+//! It mirrors the tabs and checkboxes of the window with typed commands and
+//! only needs the standard input and output, so it works in any terminal.
+
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::AtomicBool;
+
+use anyhow::{Context, Result};
+
+use crate::config::{AppConfig, DefaultSelect};
+use crate::loader::{LoadedData, Modification, Translation};
+
+const HELP: &str = "\
+Commands:
+  removed, added, updated   switch to the tab and list its entries
+  list                      list the entries of the current tab
+  <n> [<n> ...]             select or deselect the entries with the given numbers
+  all                       select or deselect all entries of the current tab
+  apply                     apply the selected entries of all tabs to Traduora
+  help                      show this help
+  quit                      leave without applying anything";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Removed,
+    Added,
+    Updated,
+}
+
+impl Tab {
+    fn contains(self, t: &Translation) -> bool {
+        matches!(
+            (self, &t.modification),
+            (Tab::Removed, Modification::Removed(_))
+                | (Tab::Added, Modification::Added)
+                | (Tab::Updated, Modification::Updated(..))
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Show(Tab),
+    List,
+    /// 1-based numbers of entries in the current tab.
+    Toggle(Vec<usize>),
+    ToggleAll,
+    Apply,
+    Help,
+    Quit,
+}
+
+impl Command {
+    fn parse(line: &str) -> Result<Self, String> {
+        let command = match line.trim() {
+            "removed" | "r" => Command::Show(Tab::Removed),
+            "added" | "a" => Command::Show(Tab::Added),
+            "updated" | "u" => Command::Show(Tab::Updated),
+            "list" | "l" | "" => Command::List,
+            "all" => Command::ToggleAll,
+            "apply" => Command::Apply,
+            "help" | "h" | "?" => Command::Help,
+            "quit" | "q" | "exit" => Command::Quit,
+            numbers => Command::Toggle(
+                numbers
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|n| !n.is_empty())
+                    .map(|n| n.parse().map_err(|_| format!("Unknown command {:?}.", n)))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+        Ok(command)
+    }
+}
+
+/// The entries of all tabs together with their selection.
+struct Session {
+    entries: Vec<(Translation, bool)>,
+    tab: Tab,
+}
+
+impl Session {
+    fn new(translations: Vec<Translation>, select: DefaultSelect) -> Self {
+        Self {
+            entries: translations
+                .into_iter()
+                .map(|t| {
+                    let selected = select.selects(&t.modification);
+                    (t, selected)
+                })
+                .collect(),
+            tab: Tab::Added,
+        }
+    }
+
+    fn tab_entries(&mut self) -> impl Iterator<Item = &mut (Translation, bool)> {
+        let tab = self.tab;
+        self.entries
+            .iter_mut()
+            .filter(move |(t, _)| tab.contains(t))
+    }
+
+    fn toggle(&mut self, numbers: &[usize]) -> Result<(), String> {
+        let len = self.tab_entries().count();
+        if let Some(n) = numbers.iter().find(|&&n| n == 0 || n > len) {
+            return Err(format!("There is no entry {} in this tab.", n));
+        }
+        for (i, (_, selected)) in self.tab_entries().enumerate() {
+            if numbers.contains(&(i + 1)) {
+                *selected = !*selected;
+            }
+        }
+        Ok(())
+    }
+
+    /// Selects all entries of the tab unless all are selected already, like the checkbox in the header.
+    fn toggle_all(&mut self) {
+        let select = !self.tab_entries().all(|(_, selected)| *selected);
+        self.tab_entries()
+            .for_each(|(_, selected)| *selected = select);
+    }
+
+    /// The prompt to confirm that the selected entries are applied and the answer it expects.
+    /// Deleting terms cannot be undone, so it needs the same keyword as in the window.
+    fn confirmation(selected: &[Translation]) -> (String, &'static str) {
+        let strings = crate::strings::get();
+        let deleted = selected
+            .iter()
+            .filter(|t| matches!(t.modification, Modification::Removed(_)))
+            .count();
+        if deleted > 0 {
+            let prompt = (strings.confirm_deletion)(deleted, strings.deletion_keyword);
+            (format!("{} ", prompt), strings.deletion_keyword)
+        } else {
+            let prompt = format!(
+                "Apply {} changes to Traduora? Type \"yes\" to confirm: ",
+                selected.len()
+            );
+            (prompt, "yes")
+        }
+    }
+
+    fn selected(&self) -> Vec<Translation> {
+        self.entries
+            .iter()
+            .filter(|(_, selected)| *selected)
+            .map(|(t, _)| t.clone())
+            .collect()
+    }
+
+    fn print(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let tab = self.tab;
+        let counts = [Tab::Removed, Tab::Added, Tab::Updated]
+            .map(|tab| self.entries.iter().filter(|(t, _)| tab.contains(t)).count());
+        writeln!(
+            out,
+            "== {:?} == (removed: {}, added: {}, updated: {})",
+            tab, counts[0], counts[1], counts[2]
+        )?;
+        for (i, (t, selected)) in self.tab_entries().enumerate() {
+            let mark = if *selected { 'x' } else { ' ' };
+            match &t.modification {
+                Modification::Updated(_, previous) => writeln!(
+                    out,
+                    "[{}] {:>4} {}: {:?} -> {:?}",
+                    mark,
+                    i + 1,
+                    t.term,
+                    previous,
+                    t.translation
+                )?,
+                _ => writeln!(
+                    out,
+                    "[{}] {:>4} {}: {:?}",
+                    mark,
+                    i + 1,
+                    t.term,
+                    t.translation
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs the interactive mode until the user quits or the selected entries were applied.
+pub fn run(config: &AppConfig, data: LoadedData) -> Result<()> {
+    let stdin = io::stdin();
+    let mut stdin = stdin.lock();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    if data.offline {
+        writeln!(
+            out,
+            "Traduora is unreachable, the entries are based on cached data and cannot be applied."
+        )?;
+    }
+    for path in &data.uncommitted {
+        writeln!(
+            out,
+            "{} has uncommitted changes, make sure the edits are finished before applying.",
+            path.display()
+        )?;
+    }
+    let offline = data.offline;
+    let mut session = Session::new(data.translations, config.default_select());
+    session.print(&mut out)?;
+    writeln!(out, "Type \"help\" for a list of commands.")?;
+
+    let mut line = String::new();
+    loop {
+        write!(out, "> ")?;
+        out.flush()?;
+        line.clear();
+        if stdin
+            .read_line(&mut line)
+            .context("Failed to read command.")?
+            == 0
+        {
+            return Ok(());
+        }
+        match Command::parse(&line) {
+            Ok(Command::Show(tab)) => {
+                session.tab = tab;
+                session.print(&mut out)?;
+            }
+            Ok(Command::List) => session.print(&mut out)?,
+            Ok(Command::Toggle(numbers)) => match session.toggle(&numbers) {
+                Ok(()) => session.print(&mut out)?,
+                Err(e) => writeln!(out, "{}", e)?,
+            },
+            Ok(Command::ToggleAll) => {
+                session.toggle_all();
+                session.print(&mut out)?;
+            }
+            Ok(Command::Apply) if offline => writeln!(
+                out,
+                "Updating terms is disabled while Traduora is unreachable."
+            )?,
+            Ok(Command::Apply) => {
+                let selected = session.selected();
+                let (prompt, answer) = Session::confirmation(&selected);
+                write!(out, "{}", prompt)?;
+                out.flush()?;
+                line.clear();
+                stdin
+                    .read_line(&mut line)
+                    .context("Failed to read command.")?;
+                if line.trim() != answer {
+                    writeln!(out, "Nothing was applied.")?;
+                    continue;
+                }
+                return apply(config, selected, &mut out);
+            }
+            Ok(Command::Help) => writeln!(out, "{}", HELP)?,
+            Ok(Command::Quit) => return Ok(()),
+            Err(e) => writeln!(out, "{}", e)?,
+        }
+    }
+}
+
+fn apply(config: &AppConfig, selected: Vec<Translation>, out: &mut impl Write) -> Result<()> {
+    let progress = |p: crate::updater::Progress| {
+        if p.step != crate::updater::Step::Started {
+            println!(
+                "[{}/{}] {:?} {:?}: {:?}",
+                p.current, p.total, p.operation, p.term, p.step
+            );
+        }
+    };
+    // Nobody can enter new login data here, so an expired login fails the remaining items.
+    match crate::updater::run(config, selected, progress, || None, &AtomicBool::new(false)) {
+        Ok(()) => writeln!(out, "All changes were applied.")?,
+        Err(e) => writeln!(out, "{}", e)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(Ok(Command::Show(Tab::Removed)), Command::parse("removed\n"));
+        assert_eq!(Ok(Command::List), Command::parse(""));
+        assert_eq!(Ok(Command::Toggle(vec![1, 3, 4])), Command::parse("1, 3 4"));
+        assert!(Command::parse("delete").is_err());
+    }
+
+    #[test]
+    fn toggle_entries_of_tab() {
+        let translations = vec![
+            Translation::added("a".into(), "A".into()),
+            Translation::removed("b".into(), "B".into(), "1".into()),
+            Translation::added("c".into(), "C".into()),
+        ];
+        let mut session = Session::new(translations.clone(), DefaultSelect::default());
+        session.toggle(&[2]).unwrap();
+        assert_eq!(vec!["a", "b"], terms(&session.selected()));
+        assert!(session.toggle(&[3]).is_err());
+
+        session.toggle_all();
+        assert_eq!(vec!["a", "b", "c"], terms(&session.selected()));
+        session.toggle_all();
+        assert_eq!(vec!["b"], terms(&session.selected()));
+
+        let keep_removed = DefaultSelect {
+            removed: false,
+            ..DefaultSelect::default()
+        };
+        let session = Session::new(translations, keep_removed);
+        assert_eq!(vec!["a", "c"], terms(&session.selected()));
+    }
+
+    #[test]
+    fn confirm_deletion_with_keyword() {
+        let added = Translation::added("a".into(), "A".into());
+        assert_eq!("yes", Session::confirmation(std::slice::from_ref(&added)).1);
+
+        let removed = Translation::removed("b".into(), "B".into(), "1".into());
+        let (prompt, answer) = Session::confirmation(&[added, removed]);
+        assert_eq!(crate::strings::get().deletion_keyword, answer);
+        assert!(prompt.starts_with("Deleting 1 terms cannot be undone."));
+    }
+
+    fn terms(translations: &[Translation]) -> Vec<&str> {
+        translations.iter().map(|t| t.term.as_str()).collect()
+    }
+}
@@ -0,0 +1,97 @@
+//! A text that floats next to the mouse while it hovers over a widget.
+//!
+//! The text is painted above all other widgets of the window instead of being
+//! part of the layout, so showing it does not move the surrounding widgets.
+
+use druid::widget::prelude::*;
+use druid::{theme, Point, TextLayout, WidgetPod};
+
+type Text<T> = Box<dyn Fn(&T, &Env) -> Option<String>>;
+
+/// Distance between the mouse and the tooltip.
+const OFFSET: f64 = 16.;
+
+const PADDING: f64 = 6.;
+
+pub struct Tooltip<T> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    text: Text<T>,
+    layout: TextLayout<String>,
+    /// Position of the mouse while it hovers over the child.
+    mouse: Option<Point>,
+}
+
+impl<T: Data> Tooltip<T> {
+    /// Shows the text returned by `text` while hovering over `child`, unless it is `None`.
+    pub fn new(
+        child: impl Widget<T> + 'static,
+        text: impl Fn(&T, &Env) -> Option<String> + 'static,
+    ) -> Self {
+        let mut layout = TextLayout::new();
+        layout.set_text_color(theme::LABEL_COLOR);
+        Self {
+            child: WidgetPod::new(child).boxed(),
+            text: Box::new(text),
+            layout,
+            mouse: None,
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Tooltip<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if let Event::MouseMove(mouse) = event {
+            let mouse = ctx.is_hot().then(|| mouse.pos);
+            if mouse != self.mouse {
+                self.mouse = mouse;
+                ctx.request_paint();
+            }
+        }
+        self.child.event(ctx, event, data, env);
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        if let LifeCycle::HotChanged(false) = event {
+            self.mouse = None;
+            ctx.request_paint();
+        }
+        self.child.lifecycle(ctx, event, data, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        self.child.update(ctx, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        let size = self.child.layout(ctx, bc, data, env);
+        self.child.set_origin(ctx, data, env, Point::ORIGIN);
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.child.paint(ctx, data, env);
+
+        let (mouse, text) = match (self.mouse, (self.text)(data, env)) {
+            (Some(mouse), Some(text)) => (mouse, text),
+            _ => return,
+        };
+        self.layout.set_text(text);
+        self.layout.rebuild_if_needed(ctx.text(), env);
+
+        let layout = self.layout.clone();
+        let origin = mouse + (OFFSET, OFFSET);
+        let background = env.get(theme::BACKGROUND_LIGHT);
+        let border = env.get(theme::BORDER_DARK);
+        ctx.paint_with_z_index(1, move |ctx| {
+            let rect = layout
+                .size()
+                .to_rect()
+                .with_origin(origin + (PADDING, PADDING))
+                .inset(PADDING)
+                .to_rounded_rect(3.);
+            ctx.fill(rect, &background);
+            ctx.stroke(rect, &border, 1.);
+            layout.draw(ctx, origin + (PADDING, PADDING));
+        });
+    }
+}
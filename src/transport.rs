@@ -0,0 +1,231 @@
+//! HTTP connection that every request to Traduora is sent with.
+//!
+//! The client of the `traduora` crate cannot be configured, so requests are sent with a client
+//! that applies the timeout, retries, proxy and certificate validation of the configuration.
+//! The crate is still used for the models and the login payload.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use traduora::{
+    api::{auth::AccessToken as Session, AccessToken},
+    Login,
+};
+
+use crate::compat::{self, Envelope};
+use crate::config::AppConfig;
+
+/// Connection to a Traduora instance, logged in once it has an access token.
+pub struct Api {
+    http: reqwest::blocking::Client,
+    server_url: String,
+    token: Option<AccessToken>,
+    max_retries: u32,
+}
+
+impl std::fmt::Debug for Api {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Api")
+            .field("server_url", &self.server_url)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Why a request failed, as far as it matters for sending it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    /// The connection could not be established, so the request never reached the server.
+    Connect,
+    Timeout,
+    Status(StatusCode),
+    Other,
+}
+
+impl Failure {
+    fn of(result: &reqwest::Result<reqwest::blocking::Response>) -> Option<Self> {
+        match result {
+            Ok(response) if response.status().is_success() => None,
+            Ok(response) => Some(Failure::Status(response.status())),
+            Err(e) if e.is_connect() => Some(Failure::Connect),
+            Err(e) if e.is_timeout() => Some(Failure::Timeout),
+            Err(_) => Some(Failure::Other),
+        }
+    }
+
+    /// Whether the failure may be gone when the request is sent again. After a timeout or an
+    /// overloaded gateway, the server may have processed the request already, so only requests
+    /// without side effects are sent again.
+    fn is_transient(self, method: &Method) -> bool {
+        match self {
+            Failure::Connect => true,
+            Failure::Timeout => method == Method::GET,
+            Failure::Status(status) => {
+                method == Method::GET
+                    && matches!(
+                        status,
+                        StatusCode::BAD_GATEWAY
+                            | StatusCode::SERVICE_UNAVAILABLE
+                            | StatusCode::GATEWAY_TIMEOUT
+                    )
+            }
+            Failure::Other => false,
+        }
+    }
+}
+
+/// Whether the error was caused by Traduora not being reachable, i.e. the connection failed or
+/// timed out. Other errors, like a rejected login or an invalid response, are not.
+pub fn is_unreachable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .map_or(false, |e| e.is_connect() || e.is_timeout())
+    })
+}
+
+/// HTTP client with the timeout, proxy and certificate validation of the configuration.
+pub fn client(config: &AppConfig) -> Result<reqwest::blocking::Client> {
+    let mut http = reqwest::blocking::Client::builder()
+        .danger_accept_invalid_certs(!config.validate_certs())
+        .timeout(config.timeout());
+    if let Some(proxy) = config.proxy() {
+        http = proxy.apply(http)?;
+    }
+    http.build().context("Failed to create HTTP client.")
+}
+
+impl Api {
+    pub fn new(config: &AppConfig) -> Result<Self> {
+        Ok(Self {
+            http: client(config)?,
+            server_url: config.server_url(),
+            token: None,
+            max_retries: config.max_retries(),
+        })
+    }
+
+    /// The same connection, logged in with the access token.
+    pub fn authenticate(self, token: AccessToken) -> Self {
+        Self {
+            token: Some(token),
+            ..self
+        }
+    }
+
+    /// Asks Traduora for an access token.
+    pub fn login(&self, login: &Login) -> Result<AccessToken> {
+        let body = serde_json::to_value(login)?;
+        let session: Envelope<Session> =
+            self.request(Method::POST, "api/v1/auth/token", Some(body))?;
+        Ok(session.into_inner().access_token)
+    }
+
+    /// Sends the request. Connection failures, and for `GET` also timeouts and overloaded gateways,
+    /// are retried up to the configured number of times, waiting a second longer before each
+    /// attempt.
+    fn send(
+        &self,
+        mut request: reqwest::blocking::Request,
+    ) -> reqwest::Result<reqwest::blocking::Response> {
+        let mut attempt = 0;
+        loop {
+            let retry = request.try_clone().filter(|_| attempt < self.max_retries);
+            let method = request.method().clone();
+            let result = self.http.execute(request);
+            let transient = Failure::of(&result).map_or(false, |f| f.is_transient(&method));
+            match retry {
+                Some(retry) if transient => {
+                    attempt += 1;
+                    let reason = match &result {
+                        Ok(response) => response.status().to_string(),
+                        Err(e) => e.to_string(),
+                    };
+                    log::warn!(
+                        "Request {} {} failed, retrying ({}/{}): {}",
+                        retry.method(),
+                        retry.url(),
+                        attempt,
+                        self.max_retries,
+                        reason
+                    );
+                    std::thread::sleep(Duration::from_secs(attempt.into()));
+                    request = retry;
+                }
+                _ => return result,
+            }
+        }
+    }
+
+    /// Sends a request to the path, which is relative to the host, and deserializes the response.
+    pub fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<T> {
+        let url = format!("{}/{}", self.server_url, path.trim_start_matches('/'));
+        log::debug!("REST api call {} {}", method, url);
+
+        let mut request = self.http.request(method.clone(), &url);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token.value());
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request
+            .build()
+            .and_then(|request| self.send(request))
+            .with_context(|| format!("Failed to send request {} {}", method, url))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .with_context(|| format!("Failed to read response of {} {}", method, url))?;
+
+        if !status.is_success() {
+            let request = format!("{} {}", method, url);
+            return Err(compat::response_error(status, &text, &request));
+        }
+
+        let text = if text.is_empty() { "null" } else { &text };
+        serde_json::from_str(text)
+            .with_context(|| format!("Failed to parse response of {} {}", method, url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_only_requests_without_side_effects() {
+        let unavailable = Failure::Status(StatusCode::SERVICE_UNAVAILABLE);
+        assert!(unavailable.is_transient(&Method::GET));
+        assert!(Failure::Timeout.is_transient(&Method::GET));
+        assert!(Failure::Connect.is_transient(&Method::GET));
+        assert!(!Failure::Status(StatusCode::INTERNAL_SERVER_ERROR).is_transient(&Method::GET));
+
+        for method in [Method::POST, Method::PATCH, Method::DELETE] {
+            assert!(!unavailable.is_transient(&method));
+            assert!(!Failure::Timeout.is_transient(&method));
+            assert!(Failure::Connect.is_transient(&method));
+        }
+        assert!(!Failure::Other.is_transient(&Method::GET));
+    }
+
+    #[test]
+    fn detect_unreachable_server() {
+        let refused = reqwest::blocking::get("http://127.0.0.1:1").unwrap_err();
+        assert!(is_unreachable(
+            &anyhow::Error::from(refused).context("Failed to load terms.")
+        ));
+        assert!(!is_unreachable(&compat::response_error(
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized",
+            "GET terms"
+        )));
+    }
+}
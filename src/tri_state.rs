@@ -0,0 +1,185 @@
+//! A checkbox that can show a mixed state, e.g. for selecting all items of a list.
+
+use druid::kurbo::{BezPath, Line};
+use druid::piet::{LineCap, LineJoin, LinearGradient, RenderContext, StrokeStyle, UnitPoint};
+use druid::widget::{prelude::*, Label, LabelText};
+use druid::{lens, theme, Data, LensExt, WidgetExt};
+
+use crate::palette::CHECKBOX_SIZE;
+
+/// How many items of a list are selected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Data)]
+pub enum Selection {
+    None,
+    Some,
+    All,
+}
+
+impl Selection {
+    pub fn of(mut selected: impl Iterator<Item = bool>) -> Self {
+        match selected.next() {
+            None => Selection::None,
+            Some(first) => {
+                let uniform = if first {
+                    Selection::All
+                } else {
+                    Selection::None
+                };
+                if selected.all(|s| s == first) {
+                    uniform
+                } else {
+                    Selection::Some
+                }
+            }
+        }
+    }
+}
+
+/// Checkbox for a single `bool`. Unlike druid's checkbox, it grows with [`CHECKBOX_SIZE`].
+pub fn checkbox(text: impl Into<LabelText<Selection>>) -> impl Widget<bool> {
+    TriStateCheckbox::new(text).lens(lens::Identity.map(
+        |checked: &bool| {
+            if *checked {
+                Selection::All
+            } else {
+                Selection::None
+            }
+        },
+        |checked: &mut bool, selection: Selection| *checked = selection == Selection::All,
+    ))
+}
+
+/// Checkbox for a [`Selection`]. Clicking it selects everything unless everything is
+/// selected already, in which case everything is deselected.
+pub struct TriStateCheckbox {
+    label: Label<Selection>,
+}
+
+impl TriStateCheckbox {
+    pub fn new(text: impl Into<LabelText<Selection>>) -> Self {
+        Self {
+            label: Label::new(text),
+        }
+    }
+}
+
+impl Widget<Selection> for TriStateCheckbox {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut Selection, _env: &Env) {
+        match event {
+            Event::MouseDown(_) => {
+                ctx.set_active(true);
+                ctx.request_paint();
+            }
+            Event::MouseUp(_) if ctx.is_active() => {
+                ctx.set_active(false);
+                if ctx.is_hot() {
+                    *data = match data {
+                        Selection::All => Selection::None,
+                        Selection::None | Selection::Some => Selection::All,
+                    };
+                }
+                ctx.request_paint();
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &Selection,
+        env: &Env,
+    ) {
+        self.label.lifecycle(ctx, event, data, env);
+        if let LifeCycle::HotChanged(_) = event {
+            ctx.request_paint();
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &Selection, data: &Selection, env: &Env) {
+        self.label.update(ctx, old_data, data, env);
+        ctx.request_paint();
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &Selection,
+        env: &Env,
+    ) -> Size {
+        let x_padding = env.get(theme::WIDGET_CONTROL_COMPONENT_PADDING);
+        let check_size = env.get(CHECKBOX_SIZE);
+        let label_size = self.label.layout(ctx, bc, data, env);
+        bc.constrain(Size::new(
+            check_size + x_padding + label_size.width,
+            check_size.max(label_size.height),
+        ))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &Selection, env: &Env) {
+        let size = env.get(CHECKBOX_SIZE);
+        let x_padding = env.get(theme::WIDGET_CONTROL_COMPONENT_PADDING);
+        let border_width = 1.;
+
+        let rect = Size::new(size, size)
+            .to_rect()
+            .inset(-border_width / 2.)
+            .to_rounded_rect(2.);
+        let background = LinearGradient::new(
+            UnitPoint::TOP,
+            UnitPoint::BOTTOM,
+            (
+                env.get(theme::BACKGROUND_LIGHT),
+                env.get(theme::BACKGROUND_DARK),
+            ),
+        );
+        ctx.fill(rect, &background);
+
+        let border_color = if ctx.is_hot() {
+            env.get(theme::BORDER_LIGHT)
+        } else {
+            env.get(theme::BORDER_DARK)
+        };
+        ctx.stroke(rect, &border_color, border_width);
+
+        // The marks are drawn in the same proportions as the checkmark of druid's checkbox.
+        let scale = size / 18.;
+        let style = StrokeStyle::new()
+            .line_cap(LineCap::Round)
+            .line_join(LineJoin::Round);
+        match data {
+            Selection::None => {}
+            Selection::Some => {
+                let dash = Line::new((5. * scale, size / 2.), (size - 5. * scale, size / 2.));
+                ctx.stroke_styled(dash, &env.get(theme::LABEL_COLOR), 2., &style);
+            }
+            Selection::All => {
+                let mut path = BezPath::new();
+                path.move_to((4. * scale, 9. * scale));
+                path.line_to((8. * scale, 13. * scale));
+                path.line_to((14. * scale, 5. * scale));
+                ctx.stroke_styled(path, &env.get(theme::LABEL_COLOR), 2., &style);
+            }
+        }
+
+        self.label.draw_at(ctx, (size + x_padding, 0.));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_of_items() {
+        assert_eq!(Selection::None, Selection::of([].into_iter()));
+        assert_eq!(Selection::All, Selection::of([true, true].into_iter()));
+        assert_eq!(Selection::None, Selection::of([false, false].into_iter()));
+        assert_eq!(
+            Selection::Some,
+            Selection::of([true, false, true].into_iter())
+        );
+    }
+}
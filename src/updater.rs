@@ -1,75 +1,57 @@
+use crate::client::Client;
+use crate::config::{AppConfig, LoginConfig};
 use crate::loader::{Modification, Translation};
 
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use anyhow::Context;
 use traduora::api::TermId;
-use traduora::{
-    api::{
-        terms::{CreateTerm, DeleteTerm},
-        translations::EditTranslation,
-    },
-    auth::Authenticated,
-    Query, Traduora,
-};
-
-fn update(
-    term: TermId,
-    translation: String,
-    client: &Traduora<Authenticated>,
-) -> Result<(), (String, anyhow::Error)> {
-    let project_id = crate::config::get().project_id();
-    let locale = crate::config::get().locale();
-    let endpoint = EditTranslation::new(project_id.clone(), locale.clone(), term, translation);
-
-    endpoint
-        .query(client)
+
+fn update(term: TermId, translation: &str, client: &Client) -> anyhow::Result<()> {
+    client
+        .edit_translation(term.clone(), translation.to_string())
         .with_context(|| {
             format!(
                 "Failed to update term {:?} to translation {:?}.",
-                endpoint.term_id, endpoint.value
+                term, translation
             )
-        })
-        .map_err(|e| (endpoint.value, e))?;
+        })?;
 
     Ok(())
 }
 
-fn remove(term: TermId, client: &Traduora<Authenticated>) -> anyhow::Result<()> {
-    let project_id = crate::config::get().project_id();
-    let endpoint = DeleteTerm::new(project_id.clone(), term);
-    endpoint
-        .query(client)
-        .with_context(|| format!("Failed to delete term {:?}.", endpoint.term_id))?;
+fn remove(term: TermId, client: &Client) -> anyhow::Result<()> {
+    client
+        .delete_term(term.clone())
+        .with_context(|| format!("Failed to delete term {:?}.", term))?;
 
     Ok(())
 }
 
-fn add(
-    term: String,
-    translation: String,
-    client: &Traduora<Authenticated>,
-) -> Result<(), (String, String, anyhow::Error)> {
-    let project_id = crate::config::get().project_id();
-    let locale = crate::config::get().locale();
-    let creator = CreateTerm::new(term, project_id.clone());
-    let term = creator
-        .query(client)
-        .with_context(|| format!("Failed to create term {:?}.", creator.term))
-        .map_err(|e| (creator.term.clone(), translation.clone(), e))?;
-
-    let editor = EditTranslation::new(project_id.clone(), locale.clone(), term.id, translation);
-
-    editor
-        .query(client)
-        .with_context(|| format!("Failed to set translation {:?} for new term.", editor.value))
-        .map_err(|e| (creator.term, editor.value, e))?;
+fn create(term: &str, client: &Client) -> anyhow::Result<TermId> {
+    let created = client
+        .create_term(term.to_string())
+        .with_context(|| format!("Failed to create term {:?}.", term))?;
 
-    Ok(())
+    Ok(created.id)
 }
 
 #[derive(Debug)]
 pub enum Error {
+    /// The configuration does not allow changing Traduora.
+    ReadOnly,
     ClientCreation(anyhow::Error),
-    Update(Vec<(String, String, anyhow::Error)>),
+    Lock(anyhow::Error),
+    /// Items that failed and the translations that can be retried.
+    Update(Vec<Failure>, Vec<Translation>),
+    /// The run was cancelled by the user.
+    Cancelled {
+        /// Number of items that were applied before the cancellation.
+        applied: usize,
+        errors: Vec<Failure>,
+        /// Failed items and items that were not attempted.
+        remaining: Vec<Translation>,
+    },
 }
 
 impl std::error::Error for Error {}
@@ -77,15 +59,29 @@ impl std::error::Error for Error {}
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
+            Error::ReadOnly => write!(f, "Traduora is not changed in read-only mode."),
             Error::ClientCreation(e) => write!(f, "Failed to create client: {}", e),
-            Error::Update(errs) => {
+            Error::Lock(e) => write!(f, "{:#}", e),
+            Error::Update(errs, _) => {
                 writeln!(f, "Failed to create/update/delete {} terms:", errs.len())?;
                 for e in errs {
-                    writeln!(
-                        f,
-                        "    Term {:?} with translation {:?}. Reason: {}",
-                        e.0, e.1, e.2
-                    )?;
+                    writeln!(f, "    {}", e)?;
+                }
+                Ok(())
+            }
+            Error::Cancelled {
+                applied,
+                errors,
+                remaining,
+            } => {
+                writeln!(
+                    f,
+                    "Update cancelled. {} terms were applied, {} were not.",
+                    applied,
+                    remaining.len()
+                )?;
+                for e in errors {
+                    writeln!(f, "    {}", e)?;
                 }
                 Ok(())
             }
@@ -95,30 +91,214 @@ impl std::fmt::Display for Error {
 
 pub type UpdateResult = Result<(), Error>;
 
-pub fn run(translations: Vec<Translation>, mut progress: impl FnMut(usize, usize)) -> UpdateResult {
-    let client = crate::config::create_client().map_err(Error::ClientCreation)?;
+/// An item of a run that could not be applied.
+#[derive(Debug)]
+pub struct Failure {
+    pub term: String,
+    pub translation: String,
+    /// `None` if the error does not belong to a single term, e.g. when releasing the lock failed.
+    pub operation: Option<Operation>,
+    pub error: anyhow::Error,
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Term {:?} with translation {:?}. Reason: {}",
+            self.term, self.translation, self.error
+        )
+    }
+}
+
+/// Kind of request that is sent to Traduora for a single term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Create,
+    Update,
+    Delete,
+}
+
+impl From<&Modification> for Operation {
+    fn from(m: &Modification) -> Self {
+        match m {
+            Modification::Removed(_) => Operation::Delete,
+            Modification::Updated(..) => Operation::Update,
+            Modification::Added => Operation::Create,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Started,
+    Succeeded,
+    Failed,
+}
+
+/// Progress of a run, reported when an item is started and when it is finished.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    /// 1-based index of the item.
+    pub current: usize,
+    pub total: usize,
+    pub term: String,
+    pub operation: Operation,
+    pub step: Step,
+}
+
+impl Progress {
+    pub fn fraction(&self) -> f64 {
+        self.current as f64 / self.total.max(1) as f64
+    }
+}
+
+/// Applies the translation to Traduora. On failure, the error is returned together with what is
+/// left to do, e.g. only setting the translation of a term that was created already.
+fn apply(t: Translation, client: &Client) -> Result<(), (Translation, anyhow::Error)> {
+    match t.modification.clone() {
+        Modification::Removed(term_id) => remove(term_id, client).map_err(|e| (t, e)),
+        Modification::Updated(term_id, _) => {
+            update(term_id, &t.translation, client).map_err(|e| (t, e))
+        }
+        Modification::Added => {
+            let term_id = create(&t.term, client).map_err(|e| (t.clone(), e))?;
+            update(term_id.clone(), &t.translation, client).map_err(|e| {
+                let e = e.context(format!(
+                    "Failed to set translation of new term {:?}.",
+                    t.term
+                ));
+                (
+                    Translation::updated(t.term, t.translation, term_id, String::new()),
+                    e,
+                )
+            })
+        }
+    }
+}
+
+/// Applies all given translations to Traduora.
+///
+/// If the server rejects the authentication in the middle of the run, `reauthenticate`
+/// is called to ask for new login data. The failed item is then resumed with a fresh client,
+/// e.g. a term that was created before only gets its translation, and the run continues.
+/// Returning `None` gives up on re-authentication for the rest of the run.
+///
+/// The project is locked for the duration of the run, so concurrent runs are refused.
+///
+/// Setting `cancelled` stops the run before the next item. Items that were not attempted
+/// are reported together with the failed ones.
+pub fn run(
+    config: &AppConfig,
+    translations: Vec<Translation>,
+    mut progress: impl FnMut(Progress),
+    mut reauthenticate: impl FnMut() -> Option<LoginConfig>,
+    cancelled: &AtomicBool,
+) -> UpdateResult {
+    if config.read_only() {
+        return Err(Error::ReadOnly);
+    }
+    let mut client = crate::config::create_client(config).map_err(Error::ClientCreation)?;
+    let count = |kind: fn(&Modification) -> bool| {
+        translations
+            .iter()
+            .filter(|t| kind(&t.modification))
+            .count()
+    };
+    let added = count(|m| matches!(m, Modification::Added));
+    let updated = count(|m| matches!(m, Modification::Updated(..)));
+    let removed = count(|m| matches!(m, Modification::Removed(_)));
+    let lock = crate::lock::acquire(&client).map_err(Error::Lock)?;
     let total = translations.len();
+    let mut may_reauthenticate = true;
+    let mut errors = Vec::new();
+    let mut failed = Vec::new();
+
+    let mut applied = 0;
+    let mut was_cancelled = false;
+    let mut translations = translations.into_iter().enumerate();
 
-    let errors: Vec<_> = translations
-        .into_iter()
-        .enumerate()
-        .filter_map(|(count, t)| {
-            progress(count + 1, total);
-            match t.modification {
-                Modification::Removed(term_id) => remove(term_id, &client)
-                    .err()
-                    .map(|e| (t.term, t.translation, e)),
-                Modification::Updated(term_id) => update(term_id, t.translation, &client)
-                    .err()
-                    .map(|(tl, e)| (t.term, tl, e)),
-                Modification::Added => add(t.term, t.translation, &client).err(),
+    for (count, t) in translations.by_ref() {
+        if cancelled.load(Ordering::Relaxed) {
+            log::info!("Update cancelled before applying {:?}.", t);
+            failed.push(t);
+            was_cancelled = true;
+            break;
+        }
+        let report = |step| Progress {
+            current: count + 1,
+            total,
+            term: t.term.clone(),
+            operation: (&t.modification).into(),
+            step,
+        };
+        progress(report(Step::Started));
+        let operation = (&t.modification).into();
+        let mut result = apply(t.clone(), &client);
+
+        while may_reauthenticate
+            && matches!(&result, Err((_, e)) if crate::compat::is_unauthorized(e))
+        {
+            log::warn!("Authentication rejected while applying {:?}.", t);
+            match reauthenticate().map(|login| crate::config::create_client_with(config, &login)) {
+                Some(Ok(new_client)) => {
+                    client = new_client;
+                    if let Err((remaining, _)) = result {
+                        result = apply(remaining, &client);
+                    }
+                }
+                Some(Err(e)) => log::error!("Re-authentication failed: {:?}", e),
+                None => may_reauthenticate = false,
             }
-        })
-        .collect();
+        }
+
+        match result {
+            Ok(()) => {
+                applied += 1;
+                progress(report(Step::Succeeded));
+            }
+            Err((remaining, error)) => {
+                progress(report(Step::Failed));
+                errors.push(Failure {
+                    term: remaining.term.clone(),
+                    translation: remaining.translation.clone(),
+                    operation: Some(operation),
+                    error,
+                });
+                failed.push(remaining);
+            }
+        }
+    }
+
+    if let Err(e) = lock.release(&client) {
+        log::error!("{:?}", e);
+        errors.push(Failure {
+            term: crate::lock::LOCK_TERM.into(),
+            translation: String::new(),
+            operation: None,
+            error: e,
+        });
+    }
 
-    if errors.is_empty() {
+    if was_cancelled {
+        failed.extend(translations.map(|(_, t)| t));
+        Err(Error::Cancelled {
+            applied,
+            errors,
+            remaining: failed,
+        })
+    } else if errors.is_empty() {
+        if let Some(auto_commit) = config.auto_commit() {
+            let message = auto_commit.message(config.locale().value(), added, updated, removed);
+            // Traduora is updated already, so a failed commit is only reported.
+            if let Err(e) =
+                crate::loader::commit_translation_files(config.translation_files(), &message)
+            {
+                log::error!("Failed to commit the translation files: {:?}", e);
+            }
+        }
         Ok(())
     } else {
-        Err(Error::Update(errors))
+        Err(Error::Update(errors, failed))
     }
 }
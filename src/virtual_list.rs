@@ -0,0 +1,244 @@
+//! A list that only builds the rows that are visible in the surrounding scroll view.
+//!
+//! Rows that were never laid out are assumed to be as high as the average of the
+//! rows measured so far. The visible area is only known while painting, so the
+//! list asks itself to build missing rows through an event sink, the same way
+//! [`crate::lazy::Lazy`] does.
+
+use std::collections::BTreeMap;
+
+use druid::im::Vector;
+use druid::widget::prelude::*;
+use druid::{ExtEventSink, Rect, Selector, WidgetPod};
+
+type Builder<T> = Box<dyn Fn() -> Box<dyn Widget<T>>>;
+type Filter<T> = Box<dyn Fn(&T, &Env) -> bool>;
+
+/// Height of a row before any row was measured.
+const DEFAULT_ROW_HEIGHT: f64 = 30.;
+
+/// Rows above and below the visible area that are built in advance, in multiples of its height.
+const OVERSCAN: f64 = 1.;
+
+pub struct VirtualList<T> {
+    builder: Builder<T>,
+    filter: Option<Filter<T>>,
+    rows: BTreeMap<usize, WidgetPod<T, Box<dyn Widget<T>>>>,
+    /// Measured height of every entry, if it was laid out before.
+    heights: Vec<Option<f64>>,
+    /// Index and top of the entries that pass the filter, in order.
+    shown: Vec<(usize, f64)>,
+    /// Entries that should be built, as a range of positions in `shown`.
+    wanted: (usize, usize),
+    sink: Option<ExtEventSink>,
+    sync_requested: bool,
+}
+
+impl VirtualList<()> {
+    /// Command the list sends to itself to build the rows in the visible area.
+    const SYNC: Selector = Selector::new("me.erik-hennig.traduora-update.virtual-list-sync");
+}
+
+impl<T: Data> VirtualList<T> {
+    pub fn new<W: Widget<T> + 'static>(f: impl Fn() -> W + 'static) -> Self {
+        Self {
+            builder: Box::new(move || Box::new(f())),
+            filter: None,
+            rows: BTreeMap::new(),
+            heights: Vec::new(),
+            shown: Vec::new(),
+            wanted: (0, 0),
+            sink: None,
+            sync_requested: false,
+        }
+    }
+
+    /// Entries for which `filter` returns `false` are neither built nor shown.
+    pub fn with_filter(mut self, filter: impl Fn(&T, &Env) -> bool + 'static) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    fn estimated_height(&self) -> f64 {
+        let (sum, count) = self
+            .heights
+            .iter()
+            .flatten()
+            .fold((0., 0), |(sum, count), h| (sum + h, count + 1));
+        if count == 0 {
+            DEFAULT_ROW_HEIGHT
+        } else {
+            sum / count as f64
+        }
+    }
+
+    /// Builds the wanted rows and drops the ones that are far out of view.
+    ///
+    /// Returns whether the set of rows changed.
+    fn sync(&mut self, data: &Vector<T>) -> bool {
+        // The entries may have changed since the wanted range was determined.
+        let end = self.wanted.1.min(self.shown.len());
+        let start = self.wanted.0.min(end);
+        let wanted: Vec<usize> = self.shown[start..end].iter().map(|&(i, _)| i).collect();
+        let keep = |i: usize| match (wanted.first(), wanted.last()) {
+            (Some(&first), Some(&last)) => {
+                let margin = wanted.len().max(1);
+                i + margin >= first && i <= last + margin && i < data.len()
+            }
+            _ => false,
+        };
+
+        let before = self.rows.len();
+        self.rows.retain(|&i, _| keep(i));
+        let mut changed = before != self.rows.len();
+        for i in wanted {
+            if !self.rows.contains_key(&i) {
+                self.rows.insert(i, WidgetPod::new((self.builder)()));
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn is_shown(&self, index: usize) -> bool {
+        self.shown.binary_search_by_key(&index, |&(i, _)| i).is_ok()
+    }
+}
+
+impl<T: Data> Widget<Vector<T>> for VirtualList<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut Vector<T>, env: &Env) {
+        if let Event::Command(cmd) = event {
+            if cmd.is(VirtualList::SYNC) {
+                self.sync_requested = false;
+                if self.sync(data) {
+                    ctx.children_changed();
+                }
+                ctx.set_handled();
+                return;
+            }
+        }
+
+        let shown: Vec<usize> = self
+            .rows
+            .keys()
+            .copied()
+            .filter(|&i| self.is_shown(i))
+            .collect();
+        for i in shown {
+            let (row, old) = match (self.rows.get_mut(&i), data.get(i)) {
+                (Some(row), Some(old)) => (row, old),
+                _ => continue,
+            };
+            let mut item = old.clone();
+            row.event(ctx, event, &mut item, env);
+            if !old.same(&item) {
+                data.set(i, item);
+            }
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &Vector<T>,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            self.sink = Some(ctx.get_external_handle());
+        }
+        for (&i, row) in self.rows.iter_mut() {
+            if let Some(item) = data.get(i) {
+                row.lifecycle(ctx, event, item, env);
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &Vector<T>, data: &Vector<T>, env: &Env) {
+        if old_data.len() != data.len() {
+            self.heights = vec![None; data.len()];
+            let before = self.rows.len();
+            self.rows.retain(|&i, _| i < data.len());
+            if before != self.rows.len() {
+                ctx.children_changed();
+            }
+            ctx.request_layout();
+        }
+        // Whether an entry passes the filter may change with the entry, e.g. when the group of a
+        // row is expanded, even if its row was never built.
+        if self.filter.is_some() && (ctx.env_changed() || !old_data.same(data)) {
+            ctx.request_layout();
+        }
+        for (&i, row) in self.rows.iter_mut() {
+            if let Some(item) = data.get(i) {
+                row.update(ctx, item, env);
+            }
+        }
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &Vector<T>,
+        env: &Env,
+    ) -> Size {
+        self.heights.resize(data.len(), None);
+        let width = bc.max().width;
+        let row_bc = BoxConstraints::new(Size::new(width, 0.), Size::new(width, f64::INFINITY));
+        let estimate = self.estimated_height();
+
+        self.shown.clear();
+        let mut y = 0.;
+        for (i, item) in data.iter().enumerate() {
+            if let Some(filter) = &self.filter {
+                if !filter(item, env) {
+                    continue;
+                }
+            }
+            self.shown.push((i, y));
+            y += match self.rows.get_mut(&i) {
+                Some(row) => {
+                    let size = row.layout(ctx, &row_bc, item, env);
+                    row.set_layout_rect(ctx, item, env, Rect::from_origin_size((0., y), size));
+                    self.heights[i] = Some(size.height);
+                    size.height
+                }
+                None => self.heights[i].unwrap_or(estimate),
+            };
+        }
+
+        bc.constrain(Size::new(width, y))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &Vector<T>, env: &Env) {
+        let visible = ctx.region().bounding_box();
+        let overscan = visible.height() * OVERSCAN;
+        let (top, bottom) = (visible.y0 - overscan, visible.y1 + overscan);
+        let start = self
+            .shown
+            .partition_point(|&(_, y)| y < top)
+            .saturating_sub(1);
+        let end = self.shown.partition_point(|&(_, y)| y < bottom);
+        self.wanted = (start, end);
+
+        let missing = self.shown[start..end]
+            .iter()
+            .any(|(i, _)| !self.rows.contains_key(i));
+        if missing && !self.sync_requested {
+            // The paint context cannot submit commands, so the request goes through the event sink.
+            if let Some(sink) = &self.sink {
+                match sink.submit_command(VirtualList::SYNC, (), ctx.widget_id()) {
+                    Ok(()) => self.sync_requested = true,
+                    Err(e) => log::error!("Failed to request building list rows: {:?}", e),
+                }
+            }
+        }
+
+        for &(i, _) in &self.shown[start..end] {
+            if let (Some(row), Some(item)) = (self.rows.get_mut(&i), data.get(i)) {
+                row.paint(ctx, item, env);
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Heuristics that flag translations which are probably wrong.
+
+use std::collections::BTreeSet;
+
+use crate::icu::SyntaxError;
+use crate::loader::{Modification, Translation};
+
+/// A translation must keep at least this share of the length it had on Traduora.
+const MIN_LENGTH_RATIO: f64 = 0.5;
+
+/// Translations on Traduora shorter than this are too short to judge changes in length.
+const MIN_LENGTH_TO_COMPARE: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    EmptyTranslation,
+    /// The translation lost more than half of its length.
+    MuchShorter {
+        previous: usize,
+        current: usize,
+    },
+    /// The placeholders differ from the ones in the translation on Traduora.
+    PlaceholdersDiffer {
+        previous: Vec<String>,
+        current: Vec<String>,
+    },
+    /// The translation is no valid ICU message.
+    InvalidMessage(SyntaxError),
+}
+
+/// Checks a new `translation` that replaces the `previous` one from Traduora, if any.
+pub fn check(previous: Option<&str>, translation: &str) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    if translation.trim().is_empty() {
+        warnings.push(Warning::EmptyTranslation);
+    }
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return warnings,
+    };
+
+    let (previous_len, current_len) = (previous.chars().count(), translation.chars().count());
+    if !translation.trim().is_empty()
+        && previous_len >= MIN_LENGTH_TO_COMPARE
+        && (current_len as f64) < previous_len as f64 * MIN_LENGTH_RATIO
+    {
+        warnings.push(Warning::MuchShorter {
+            previous: previous_len,
+            current: current_len,
+        });
+    }
+
+    let (previous, current) = (placeholders(previous), placeholders(translation));
+    if previous != current {
+        warnings.push(Warning::PlaceholdersDiffer {
+            previous: previous.into_iter().map(str::to_string).collect(),
+            current: current.into_iter().map(str::to_string).collect(),
+        });
+    }
+    warnings
+}
+
+/// Checks the syntax of a translation that is an ICU message.
+pub fn check_message(translation: &str) -> Option<Warning> {
+    crate::icu::validate(translation)
+        .err()
+        .map(Warning::InvalidMessage)
+}
+
+/// The translations to upload that are no valid ICU messages, with the first problem of each.
+pub fn invalid_messages(translations: &[Translation]) -> Vec<(&Translation, SyntaxError)> {
+    translations
+        .iter()
+        .filter(|t| !matches!(t.modification, Modification::Removed(_)))
+        .filter_map(|t| crate::icu::validate(&t.translation).err().map(|e| (t, e)))
+        .collect()
+}
+
+/// Finds named placeholders like `{name}` and printf-style ones like `%s` or `%1$d`.
+fn placeholders(text: &str) -> BTreeSet<&str> {
+    let mut found = BTreeSet::new();
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = match bytes[i] {
+            b'{' => text[i + 1..]
+                .find('}')
+                .map(|len| i + len + 2)
+                .filter(|&end| {
+                    let name = &text[i + 1..end - 1];
+                    !name.is_empty()
+                        && name
+                            .chars()
+                            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | ':' | '-'))
+                }),
+            b'%' if bytes.get(i + 1) == Some(&b'%') => {
+                i += 2;
+                continue;
+            }
+            b'%' => {
+                let spec = bytes[i + 1..]
+                    .iter()
+                    .position(|b| !(b.is_ascii_digit() || b"$-+ #.l".contains(b)))
+                    .map(|len| i + 1 + len);
+                spec.filter(|&pos| b"sdifuxXeEgGcp@".contains(&bytes[pos]))
+                    .map(|pos| pos + 1)
+            }
+            _ => None,
+        };
+        match end {
+            Some(end) => {
+                found.insert(&text[i..end]);
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_placeholders() {
+        assert_eq!(
+            vec!["%1$s", "%d", "{count}", "{user.name}"],
+            placeholders("{user.name} has {count} items, %d new and 100%% done by %1$s")
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+        assert!(placeholders("{ not a placeholder } and 50 % off").is_empty());
+    }
+
+    #[test]
+    fn suspicious_changes() {
+        assert_eq!(vec![Warning::EmptyTranslation], check(None, " "));
+        assert!(check(None, "Hello {name}").is_empty());
+        assert!(check(Some("Hello {name}"), "Hallo {name}").is_empty());
+        assert_eq!(
+            vec![Warning::MuchShorter {
+                previous: 24,
+                current: 5
+            }],
+            check(Some("This is a long sentence."), "Short")
+        );
+        assert_eq!(
+            vec![Warning::PlaceholdersDiffer {
+                previous: vec!["{name}".into()],
+                current: vec!["{nmae}".into()],
+            }],
+            check(Some("Hello {name}"), "Hallo {nmae}")
+        );
+    }
+
+    #[test]
+    fn invalid_icu_messages() {
+        assert_eq!(
+            None,
+            check_message("{count, plural, one {# Datei} other {# Dateien}}")
+        );
+        assert!(matches!(
+            check_message("{count, plural, one {# Datei}}"),
+            Some(Warning::InvalidMessage(_))
+        ));
+        let translations = [
+            Translation::added("new".into(), "Hallo {name".into()),
+            Translation::updated("changed".into(), "{n}".into(), "1".into(), "{n".into()),
+            Translation::removed("gone".into(), "{".into(), "2".into()),
+        ];
+        let invalid = invalid_messages(&translations);
+        assert_eq!(1, invalid.len());
+        assert_eq!("new", invalid[0].0.term);
+        assert_eq!(6, invalid[0].1.position);
+    }
+}
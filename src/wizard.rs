@@ -0,0 +1,309 @@
+//! Setup wizard that is shown on the first start, when no config file is found.
+//!
+//! It logs in to Traduora, lets the user pick the project and the locale from the ones on the
+//! server and writes [`FILE_NAME`] to the working directory. The window then starts with it.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use druid::widget::{
+    Button, Controller, CrossAxisAlignment, Either, Flex, Label, LineBreaking, TextBox,
+};
+use druid::{
+    commands, theme, Data, Env, Event, EventCtx, FileDialogOptions, FileInfo, Lens, Selector,
+    Widget, WidgetExt,
+};
+
+use crate::config::{AppConfig, LoginConfig};
+use crate::picker::{self, Picker};
+use crate::strings;
+use crate::tri_state::checkbox;
+
+/// Name of the config file that the wizard writes.
+pub const FILE_NAME: &str = "traduora-update.json";
+
+/// Config file that was written. The window starts with it.
+pub const FINISHED: Selector<PathBuf> =
+    Selector::new("me.erik-hennig.traduora-update.wizard-finished");
+
+const TRANSLATION_FILE_CHOSEN: Selector<FileInfo> =
+    Selector::new("me.erik-hennig.traduora-update.wizard-translation-file-chosen");
+
+#[derive(Data, Debug, Clone, Lens)]
+pub struct Wizard {
+    host: String,
+    with_ssl: bool,
+    client_credentials: bool,
+    user: String,
+    secret: String,
+    picker: Picker,
+    translation_file: String,
+    /// Whether the config file was written and the window is starting.
+    starting: bool,
+    /// Error of saving the config file.
+    status: String,
+}
+
+impl Default for Wizard {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            with_ssl: true,
+            client_credentials: false,
+            user: String::new(),
+            secret: String::new(),
+            picker: Picker::default(),
+            translation_file: String::new(),
+            starting: false,
+            status: String::new(),
+        }
+    }
+}
+
+impl Wizard {
+    fn login(&self) -> LoginConfig {
+        if self.client_credentials {
+            LoginConfig::ClientCredentials {
+                client_id: self.user.clone(),
+                client_secret: self.secret.clone(),
+            }
+        } else {
+            LoginConfig::Password {
+                mail: self.user.clone(),
+                password: self.secret.clone(),
+            }
+        }
+    }
+
+    fn draft(&self) -> AppConfig {
+        crate::config::draft(self.host.trim(), self.with_ssl, self.login())
+    }
+
+    /// Whether everything for the config file is chosen.
+    fn complete(&self, _: &Env) -> bool {
+        !self.starting && self.picker.chosen().is_some() && !self.translation_file.trim().is_empty()
+    }
+
+    /// Content of the config file for the entered values and the chosen project and locale.
+    fn to_json(&self, project_id: &str, locale: &str) -> serde_json::Value {
+        let (user_key, secret_key) = if self.client_credentials {
+            ("client_id", "client_secret")
+        } else {
+            ("mail", "password")
+        };
+        let mut files = serde_json::Map::new();
+        files.insert(
+            locale.to_string(),
+            self.translation_file.trim().to_string().into(),
+        );
+        serde_json::json!({
+            "host": self.host.trim(),
+            "with_ssl": self.with_ssl,
+            user_key: self.user,
+            secret_key: self.secret,
+            "project_id": project_id,
+            "files": files,
+        })
+    }
+}
+
+/// Writes the config file to the working directory.
+fn save(data: &Wizard) -> Result<PathBuf> {
+    let (project_id, locale) = data
+        .picker
+        .chosen()
+        .context("No project and locale are chosen.")?;
+    let path = std::env::current_dir()
+        .context("Failed to determine the working directory.")?
+        .join(FILE_NAME);
+    let json = serde_json::to_string_pretty(&data.to_json(project_id, locale))?;
+    crate::atomic::write(&path, json)
+        .with_context(|| format!("Failed to write config file {}", path.display()))?;
+    Ok(path)
+}
+
+/// Path relative to the working directory if the file is inside of it, as in the config file.
+fn relative(path: &Path) -> PathBuf {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Fills in the translation file chosen in the file dialog.
+struct WizardController;
+
+impl<W: Widget<Wizard>> Controller<Wizard, W> for WizardController {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut Wizard,
+        env: &Env,
+    ) {
+        match event {
+            Event::Command(cmd) if cmd.is(TRANSLATION_FILE_CHOSEN) => {
+                let file = cmd.get_unchecked(TRANSLATION_FILE_CHOSEN);
+                data.translation_file = relative(file.path()).display().to_string();
+                ctx.set_handled();
+            }
+            event => child.event(ctx, event, data, env),
+        }
+    }
+}
+
+fn labeled(
+    label: impl Widget<Wizard> + 'static,
+    input: impl Widget<Wizard> + 'static,
+) -> Flex<Wizard> {
+    Flex::row()
+        .with_child(label.fix_width(150.))
+        .with_child(input)
+}
+
+pub fn build() -> impl Widget<Wizard> {
+    let user_label = Label::new(|w: &Wizard, _: &_| {
+        if w.client_credentials {
+            strings::get().client_id
+        } else {
+            strings::get().mail
+        }
+        .to_string()
+    });
+    let secret_label = Label::new(|w: &Wizard, _: &_| {
+        if w.client_credentials {
+            strings::get().client_secret
+        } else {
+            strings::get().password
+        }
+        .to_string()
+    });
+
+    let connect_button = Either::new(
+        |w: &Wizard, _: &_| w.picker.is_busy() || w.starting,
+        Label::new(strings::get().wizard_connect).with_text_color(theme::PLACEHOLDER_COLOR),
+        Button::new(strings::get().wizard_connect).on_click(|ctx, data: &mut Wizard, _| {
+            let draft = data.draft();
+            data.picker.load_projects(ctx.get_external_handle(), draft);
+        }),
+    );
+
+    let translation_file = Either::new(
+        |w: &Wizard, _: &_| w.picker.chosen().is_none(),
+        Flex::column(),
+        labeled(
+            Label::new(strings::get().wizard_translation_file),
+            Flex::row()
+                .with_child(
+                    TextBox::new()
+                        .fix_width(300.)
+                        .lens(Wizard::translation_file),
+                )
+                .with_default_spacer()
+                .with_child(
+                    Button::new(strings::get().wizard_browse).on_click(|ctx, _, _| {
+                        let options =
+                            FileDialogOptions::new().accept_command(TRANSLATION_FILE_CHOSEN);
+                        ctx.submit_command(commands::SHOW_OPEN_PANEL.with(options));
+                    }),
+                ),
+        ),
+    );
+
+    let save_button = Either::new(
+        Wizard::complete,
+        Button::new(strings::get().wizard_save).on_click(|ctx, data: &mut Wizard, _| {
+            match save(data) {
+                Ok(path) => {
+                    data.starting = true;
+                    data.status = strings::get().wizard_starting.into();
+                    ctx.submit_command(FINISHED.with(path));
+                }
+                Err(e) => {
+                    log::error!("Setup wizard failed to save the config file: {:?}", e);
+                    data.status = (strings::get().picker_failed)(&e);
+                }
+            }
+        }),
+        Label::new(strings::get().wizard_save).with_text_color(theme::PLACEHOLDER_COLOR),
+    );
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Label::new(strings::get().wizard_intro).with_line_break_mode(LineBreaking::WordWrap),
+        )
+        .with_default_spacer()
+        .with_child(labeled(
+            Label::new(strings::get().wizard_host),
+            Flex::row()
+                .with_child(TextBox::new().fix_width(250.).lens(Wizard::host))
+                .with_default_spacer()
+                .with_child(checkbox(strings::get().wizard_with_ssl).lens(Wizard::with_ssl)),
+        ))
+        .with_default_spacer()
+        .with_child(
+            checkbox(strings::get().wizard_client_credentials).lens(Wizard::client_credentials),
+        )
+        .with_default_spacer()
+        .with_child(labeled(
+            user_label,
+            TextBox::new().fix_width(250.).lens(Wizard::user),
+        ))
+        .with_default_spacer()
+        .with_child(labeled(
+            secret_label,
+            TextBox::new().fix_width(250.).lens(Wizard::secret),
+        ))
+        .with_default_spacer()
+        .with_child(connect_button)
+        .with_default_spacer()
+        .with_child(picker::build().lens(Wizard::picker))
+        .with_default_spacer()
+        .with_child(translation_file)
+        .with_default_spacer()
+        .with_child(
+            Label::new(strings::get().wizard_secret_hint)
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .with_text_color(theme::PLACEHOLDER_COLOR),
+        )
+        .with_default_spacer()
+        .with_child(save_button)
+        .with_default_spacer()
+        .with_child(
+            Label::new(|w: &Wizard, _: &_| w.status.clone())
+                .with_line_break_mode(LineBreaking::WordWrap),
+        )
+        .padding(10.)
+        .controller(WizardController)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_entered_values() {
+        let wizard = Wizard {
+            host: " localhost:8080 ".into(),
+            with_ssl: false,
+            client_credentials: true,
+            user: "id".into(),
+            secret: "secret".into(),
+            translation_file: "de.json".into(),
+            ..Wizard::default()
+        };
+        assert_eq!(
+            wizard.to_json("p", "de_DE"),
+            serde_json::json!({
+                "host": "localhost:8080",
+                "with_ssl": false,
+                "client_id": "id",
+                "client_secret": "secret",
+                "project_id": "p",
+                "files": { "de_DE": "de.json" },
+            })
+        );
+    }
+}